@@ -0,0 +1,111 @@
+use std::time::{Duration, Instant};
+
+use rsbdd::arena::{ArenaBDD, NodeId};
+use rsbdd::bdd::BDDEnv;
+
+mod common;
+
+use common::queens;
+
+const N: usize = 7;
+const ITERATIONS: usize = 5;
+
+// the lines of cells of common::queens, and whether every line must contain exactly one queen or at most one
+fn queen_lines(n: usize) -> Vec<(Vec<usize>, bool)> {
+    let rows = (0..n).map(|i| ((0..n).map(|j| j + i * n).collect(), true));
+    let cols = (0..n).map(|i| ((0..n).map(|j| j * n + i).collect(), true));
+    let diag_hl = (0..n).map(|i| ((0..n - i).map(|j| i + j * (n + 1)).collect(), false));
+    let diag_vl = (1..n).map(|i| ((0..n - i).map(|j| i * n + j * (n + 1)).collect(), false));
+    let diag_hr = (0..n).map(|i| ((0..=i).map(|j| i + j * (n - 1)).collect(), false));
+    let diag_vr = (1..n).map(|i| {
+        (
+            (0..n - i).map(|j| i * n + (n - 1) + j * (n - 1)).collect(),
+            false,
+        )
+    });
+
+    rows.chain(cols)
+        .chain(diag_hl)
+        .chain(diag_vl)
+        .chain(diag_hr)
+        .chain(diag_vr)
+        .collect()
+}
+
+// both representations build the same constraints using only and, or and not
+
+fn queens_rc(e: &BDDEnv<usize>, n: usize) -> usize {
+    let mut result = e.mk_const(true);
+
+    for (line, exactly) in queen_lines(n) {
+        for (i, &a) in line.iter().enumerate() {
+            for &b in &line[i + 1..] {
+                result = e.and(result, e.not(e.and(e.var(a), e.var(b))));
+            }
+        }
+
+        let any = line
+            .iter()
+            .fold(e.mk_const(!exactly), |acc, &c| e.or(acc, e.var(c)));
+        result = e.and(result, any);
+    }
+
+    e.size()
+}
+
+fn queens_arena(e: &ArenaBDD<usize>, n: usize) -> NodeId {
+    let mut result = e.mk_const(true);
+
+    for (line, exactly) in queen_lines(n) {
+        for (i, &a) in line.iter().enumerate() {
+            for &b in &line[i + 1..] {
+                result = e.and(result, e.not(e.and(e.var(a), e.var(b))));
+            }
+        }
+
+        let any = line
+            .iter()
+            .fold(e.mk_const(!exactly), |acc, &c| e.or(acc, e.var(c)));
+        result = e.and(result, any);
+    }
+
+    result
+}
+
+// the average time of running f, which returns the number of nodes created
+fn measure(name: &str, mut f: impl FnMut() -> usize) -> Duration {
+    let mut total = Duration::ZERO;
+    let mut size = 0;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        size = f();
+        total += start.elapsed();
+    }
+
+    let elapsed = total / ITERATIONS as u32;
+
+    println!("{name}: {elapsed:?} per iteration ({size} nodes)");
+
+    elapsed
+}
+
+fn main() {
+    // both representations describe the n-queens function
+    let e = BDDEnv::new();
+    let a = ArenaBDD::new();
+    assert_eq!(a.to_bdd(queens_arena(&a, N), &e), queens(&e, N));
+
+    let rc = measure("rc", || queens_rc(&BDDEnv::new(), N));
+
+    let arena = measure("arena", || {
+        let e = ArenaBDD::new();
+        queens_arena(&e, N);
+        e.size()
+    });
+
+    println!(
+        "speedup: {:.2}x",
+        rc.as_secs_f64() / arena.as_secs_f64().max(f64::EPSILON)
+    );
+}
@@ -0,0 +1,66 @@
+use std::rc::Rc;
+
+use rsbdd::bdd::{BDDEnv, BDD};
+
+// conjunction of all constraints for the n-queens problem, similar to the duplicates test in tests/hash.rs
+pub fn queens(e: &BDDEnv<usize>, n: usize) -> Rc<BDD<usize>> {
+    let conjunction = |exprs: Vec<Rc<BDD<usize>>>| {
+        exprs
+            .into_iter()
+            .fold(e.mk_const(true), |acc, k| e.and(acc, k))
+    };
+
+    let rows = (0..n)
+        .map(|i| e.exn(&(0..n).map(|j| e.var(j + i * n)).collect::<Vec<_>>(), 1))
+        .collect();
+    let cols = (0..n)
+        .map(|i| e.exn(&(0..n).map(|j| e.var(j * n + i)).collect::<Vec<_>>(), 1))
+        .collect();
+    let diag_hl = (0..n)
+        .map(|i| {
+            e.amn(
+                &(0..n - i)
+                    .map(|j| e.var(i + j * (n + 1)))
+                    .collect::<Vec<_>>(),
+                1,
+            )
+        })
+        .collect();
+    let diag_vl = (1..n)
+        .map(|i| {
+            e.amn(
+                &(0..n - i)
+                    .map(|j| e.var(i * n + j * (n + 1)))
+                    .collect::<Vec<_>>(),
+                1,
+            )
+        })
+        .collect();
+    let diag_hr = (0..n)
+        .map(|i| {
+            e.amn(
+                &(0..=i).map(|j| e.var(i + j * (n - 1))).collect::<Vec<_>>(),
+                1,
+            )
+        })
+        .collect();
+    let diag_vr = (1..n)
+        .map(|i| {
+            e.amn(
+                &(0..n - i)
+                    .map(|j| e.var(i * n + (n - 1) + j * (n - 1)))
+                    .collect::<Vec<_>>(),
+                1,
+            )
+        })
+        .collect();
+
+    conjunction(vec![
+        conjunction(rows),
+        conjunction(cols),
+        conjunction(diag_hl),
+        conjunction(diag_vl),
+        conjunction(diag_hr),
+        conjunction(diag_vr),
+    ])
+}
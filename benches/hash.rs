@@ -0,0 +1,78 @@
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rustc_hash::FxHasher;
+
+use rsbdd::bdd::{BDDEnv, BDD};
+
+mod common;
+
+use common::queens;
+
+const N: usize = 7;
+const ITERATIONS: usize = 10;
+
+// the previous (derived) hash implementation, which visits all descendants of a node
+fn recursive_hash(node: &BDD<usize>, state: &mut FxHasher) {
+    match node {
+        BDD::False => state.write_u8(0),
+        BDD::True => state.write_u8(1),
+        BDD::Choice(t, v, f, _) => {
+            state.write_u8(2);
+            recursive_hash(t, state);
+            v.hash(state);
+            recursive_hash(f, state);
+        }
+    }
+}
+
+fn measure(name: &str, mut f: impl FnMut() -> u64) -> Duration {
+    let start = Instant::now();
+    let mut checksum = 0u64;
+    for _ in 0..ITERATIONS {
+        checksum = checksum.wrapping_add(f());
+    }
+    let elapsed = start.elapsed() / ITERATIONS as u32;
+
+    println!("{name}: {elapsed:?} per iteration (checksum {checksum:x})");
+
+    elapsed
+}
+
+fn main() {
+    let e = BDDEnv::new();
+    let root = queens(&e, N);
+    let nodes = root.node_list();
+
+    println!(
+        "{N}-queens: {} nodes visited, {} unique nodes",
+        nodes.len(),
+        e.size_reachable(&root)
+    );
+
+    let recursive = measure("recursive hash", || {
+        nodes
+            .iter()
+            .map(|n| {
+                let mut s = FxHasher::default();
+                recursive_hash(n, &mut s);
+                s.finish()
+            })
+            .fold(0, u64::wrapping_add)
+    });
+
+    let cached = measure("cached hash", || {
+        nodes
+            .iter()
+            .map(|n| n.get_hash())
+            .fold(0, u64::wrapping_add)
+    });
+
+    measure("duplicates", || e.duplicates(Rc::clone(&root)) as u64);
+
+    println!(
+        "speedup: {:.1}x",
+        recursive.as_secs_f64() / cached.as_secs_f64().max(f64::EPSILON)
+    );
+}
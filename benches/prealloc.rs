@@ -0,0 +1,49 @@
+use std::time::{Duration, Instant};
+
+use rsbdd::bdd::BDDEnv;
+
+mod common;
+
+use common::queens;
+
+const N: usize = 7;
+const ITERATIONS: usize = 5;
+
+// build the n-queens bdd in a fresh environment created by new_env, returning the average build time
+fn measure(name: &str, new_env: impl Fn() -> BDDEnv<usize>) -> Duration {
+    let mut total = Duration::ZERO;
+    let mut size = 0;
+
+    for _ in 0..ITERATIONS {
+        let e = new_env();
+
+        let start = Instant::now();
+        queens(&e, N);
+        total += start.elapsed();
+
+        size = e.size();
+    }
+
+    let elapsed = total / ITERATIONS as u32;
+
+    println!("{name}: {elapsed:?} per iteration ({size} nodes)");
+
+    elapsed
+}
+
+fn main() {
+    // the final size of the lookup table determines the capacity of the preallocated environment
+    let e = BDDEnv::new();
+    queens(&e, N);
+    let capacity = e.size();
+
+    println!("{N}-queens: {capacity} nodes in the lookup table");
+
+    let default = measure("default", BDDEnv::new);
+    let sized = measure("preallocated", || BDDEnv::new_sized(capacity));
+
+    println!(
+        "speedup: {:.2}x",
+        default.as_secs_f64() / sized.as_secs_f64().max(f64::EPSILON)
+    );
+}
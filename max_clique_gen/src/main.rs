@@ -32,8 +32,8 @@ fn main() -> Result<()> {
     let version = env!("CARGO_PKG_VERSION");
     let args = Args::parse();
 
-    let reader = if args.input.is_some() {
-        let file = File::open(args.input.unwrap())?;
+    let reader = if let Some(input) = args.input {
+        let file = File::open(input)?;
         Box::new(BufReader::new(file)) as Box<dyn BufRead>
     } else {
         Box::new(BufReader::new(io::stdin())) as Box<dyn BufRead>
@@ -78,8 +78,8 @@ fn main() -> Result<()> {
         }
     }
 
-    let mut writer = if args.output.is_some() {
-        let file = File::create(args.output.unwrap())?;
+    let mut writer = if let Some(output) = args.output {
+        let file = File::create(output)?;
         Box::new(BufWriter::new(file)) as Box<dyn Write>
     } else {
         Box::new(BufWriter::new(io::stdout())) as Box<dyn Write>
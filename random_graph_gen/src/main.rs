@@ -39,9 +39,26 @@ struct Args {
     /// If this argument is provided, the provided edge-list will be used to generate a graph
     convert: Option<PathBuf>,
 
+    #[clap(long, value_parser, value_name = "FILE", conflicts_with = "convert")]
+    /// Read the edges of a graph in dot (GraphViz) format, attributes and subgraph structure are ignored
+    from_dot: Option<PathBuf>,
+
     #[clap(short, long, value_parser, value_name = "N")]
     /// Generate a graph-coloring problem with N colors
     colors: Option<usize>,
+
+    #[clap(long, value_parser, value_name = "W")]
+    /// Drop all edges with a weight below W before generating the output
+    min_weight: Option<f64>,
+}
+
+// an edge from the first to the second vertex with a weight, unweighted edges have weight 1
+type Edge = (String, String, f64);
+
+struct EdgeList {
+    edges: Vec<Edge>,
+    // whether the weights are part of the output
+    weighted: bool,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -51,14 +68,14 @@ fn main() -> anyhow::Result<()> {
         let file = File::open(file_to_convert)?;
         let mut bufreader = BufReader::new(file);
         read_graph(&mut bufreader, args.undirected)?
+    } else if let Some(dot_file) = args.from_dot {
+        let file = File::open(dot_file)?;
+        let mut bufreader = BufReader::new(file);
+        read_dot_graph(&mut bufreader, args.undirected)?
     } else if args.complete {
-        if args.vertices.is_none() {
-            Err(anyhow::anyhow!(
-                "Must provide vertices for a complete graph"
-            ))?
-        }
-
-        let vertices = args.vertices.unwrap();
+        let vertices = args
+            .vertices
+            .ok_or_else(|| anyhow::anyhow!("Must provide vertices for a complete graph"))?;
 
         let edges = if args.undirected {
             (vertices * (vertices - 1)) / 2
@@ -66,19 +83,28 @@ fn main() -> anyhow::Result<()> {
             vertices * (vertices - 1)
         };
 
-        generate_graph(vertices, edges, args.undirected)?
+        unweighted(generate_graph(vertices, edges, args.undirected)?)
     } else {
-        if args.vertices.is_none() || args.edges.is_none() {
+        let (Some(vertices), Some(edges)) = (args.vertices, args.edges) else {
             Err(anyhow::anyhow!(
                 "Must provide vertices and edges if not converting a graph"
             ))?
-        }
-        generate_graph(args.vertices.unwrap(), args.edges.unwrap(), args.undirected)?
+        };
+        unweighted(generate_graph(vertices, edges, args.undirected)?)
     };
 
-    // convert to a graph-coloring problem
+    if let Some(min_weight) = args.min_weight {
+        selection.edges.retain(|edge| edge.2 >= min_weight);
+    }
+
+    // convert to a graph-coloring problem, the weights are not part of the encoding
     if let Some(num_colors) = args.colors {
-        selection = augment_colors(&selection, num_colors)?;
+        let pairs = selection
+            .edges
+            .into_iter()
+            .map(|(v1, v2, _)| (v1, v2))
+            .collect();
+        selection = unweighted(augment_colors(&pairs, num_colors)?);
     }
 
     let mut writer = if let Some(output_file) = args.output {
@@ -89,22 +115,32 @@ fn main() -> anyhow::Result<()> {
     };
 
     if args.dot {
-        if args.undirected {
+        let connector = if args.undirected {
             writeln!(writer, "graph G {{")?;
-            for edge in selection {
-                writeln!(writer, "    {} -- {}", edge.0, edge.1)?;
-            }
-            writeln!(writer, "}}")?;
+            "--"
         } else {
             writeln!(writer, "digraph G {{")?;
-            for edge in selection {
-                writeln!(writer, "    {} -> {}", edge.0, edge.1)?;
+            "->"
+        };
+        for edge in selection.edges {
+            if selection.weighted {
+                writeln!(
+                    writer,
+                    "    {} {} {} [label=\"{}\"]",
+                    edge.0, connector, edge.1, edge.2
+                )?;
+            } else {
+                writeln!(writer, "    {} {} {}", edge.0, connector, edge.1)?;
             }
-            writeln!(writer, "}}")?;
         }
+        writeln!(writer, "}}")?;
     } else {
-        for edge in selection {
-            writeln!(writer, "{},{}", edge.0, edge.1)?;
+        for edge in selection.edges {
+            if selection.weighted {
+                writeln!(writer, "{},{},{}", edge.0, edge.1, edge.2)?;
+            } else {
+                writeln!(writer, "{},{}", edge.0, edge.1)?;
+            }
         }
     }
 
@@ -114,24 +150,305 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn read_graph<R: Read>(reader: R, undirected: bool) -> io::Result<Vec<(String, String)>> {
+// wrap a list of vertex pairs as edges with weight 1
+fn unweighted(edges: Vec<(String, String)>) -> EdgeList {
+    EdgeList {
+        edges: edges.into_iter().map(|(v1, v2)| (v1, v2, 1.0)).collect(),
+        weighted: false,
+    }
+}
+
+// read an edge list with two (u,v) or three (u,v,w) columns per line, where the weight w defaults to 1
+fn read_graph<R: Read>(reader: R, undirected: bool) -> io::Result<EdgeList> {
     let mut csv_reader = csv::ReaderBuilder::new()
         .has_headers(false)
+        .flexible(true)
         .from_reader(reader);
 
-    let mut edges: Vec<(String, String)> = Vec::new();
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut weighted = false;
 
     for edge_record in csv_reader.records() {
         let edge = edge_record?;
 
-        assert!(edge.len() == 2);
+        let weight = match edge.len() {
+            2 => 1.0,
+            3 => {
+                weighted = true;
+                edge[2].trim().parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Cannot parse {} as edge weight", &edge[2]),
+                    )
+                })?
+            }
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Expected 2 or 3 columns per edge, got {n}"),
+                ))
+            }
+        };
+
+        push_edge(
+            &mut edges,
+            (edge[0].to_string(), edge[1].to_string(), weight),
+            undirected,
+        );
+    }
+
+    Ok(EdgeList { edges, weighted })
+}
+
+// read the edges of a dot graph, e.g. `a -> b [label="x"];`, `a -- b -- c` or `a -> {b c}`
+fn read_dot_graph<R: Read>(mut reader: R, undirected: bool) -> io::Result<EdgeList> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+
+    let mut tokens = tokenize_dot(&contents)?.into_iter().peekable();
+    let mut edges: Vec<Edge> = Vec::new();
+
+    while tokens.peek().is_some() {
+        // the graph header, e.g. `strict digraph G`
+        tokens.next_if(|t| is_dot_keyword(t, "strict"));
+        if tokens
+            .next_if(|t| is_dot_keyword(t, "graph") || is_dot_keyword(t, "digraph"))
+            .is_none()
+        {
+            return Err(dot_error("Expected a graph or digraph header"));
+        }
+        tokens.next_if(|t| matches!(t, DotToken::Id(..)));
+
+        expect_dot(&mut tokens, &DotToken::Open)?;
+        parse_dot_statements(&mut tokens, &mut edges, undirected)?;
+        expect_dot(&mut tokens, &DotToken::Close)?;
+    }
+
+    Ok(EdgeList {
+        edges,
+        weighted: false,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DotToken {
+    // an identifier, numeral or quoted string, quoted ids are never keywords
+    Id(String, bool),
+    Edge,
+    Open,
+    Close,
+    Equals,
+    Separator,
+}
+
+type DotTokens = std::iter::Peekable<std::vec::IntoIter<DotToken>>;
+
+fn dot_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn is_dot_keyword(token: &DotToken, keyword: &str) -> bool {
+    matches!(token, DotToken::Id(id, false) if id.eq_ignore_ascii_case(keyword))
+}
+
+fn expect_dot(tokens: &mut DotTokens, expected: &DotToken) -> io::Result<()> {
+    match tokens.next() {
+        Some(token) if &token == expected => Ok(()),
+        Some(token) => Err(dot_error(&format!("Expected {expected:?}, got {token:?}"))),
+        None => Err(dot_error(&format!(
+            "Expected {expected:?}, got end of input"
+        ))),
+    }
+}
+
+// split a dot graph into tokens, skipping comments and attribute lists
+fn tokenize_dot(contents: &str) -> io::Result<Vec<DotToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = contents.chars().peekable();
+    // whether only whitespace precedes the current character on its line, for `#` preprocessor lines
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                line_start = true;
+                continue;
+            }
+            _ if c.is_whitespace() => continue,
+            '#' if line_start => {
+                chars.by_ref().take_while(|&c| c != '\n').for_each(drop);
+                continue;
+            }
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                chars.by_ref().take_while(|&c| c != '\n').for_each(drop);
+                line_start = true;
+                continue;
+            }
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => previous = c,
+                        None => return Err(dot_error("Unterminated comment")),
+                    }
+                }
+            }
+            '-' if chars.next_if(|&c| c == '>' || c == '-').is_some() => {
+                tokens.push(DotToken::Edge);
+            }
+            '{' => tokens.push(DotToken::Open),
+            '}' => tokens.push(DotToken::Close),
+            '=' => tokens.push(DotToken::Equals),
+            ';' | ',' => tokens.push(DotToken::Separator),
+            '[' => loop {
+                match chars.next() {
+                    Some(']') => break,
+                    Some('"') => {
+                        read_dot_string(&mut chars)?;
+                    }
+                    Some(_) => {}
+                    None => return Err(dot_error("Unterminated attribute list")),
+                }
+            },
+            '"' => tokens.push(DotToken::Id(read_dot_string(&mut chars)?, true)),
+            _ if c.is_alphanumeric()
+                || c == '_'
+                || c == '.'
+                || (c == '-' && chars.peek().is_some_and(char::is_ascii_digit)) =>
+            {
+                let mut id = c.to_string();
+                while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_' || c == '.')
+                {
+                    id.push(c);
+                }
+                tokens.push(DotToken::Id(id, false));
+            }
+            _ => {
+                return Err(dot_error(&format!(
+                    "Unsupported character {c:?} in dot graph"
+                )))
+            }
+        }
 
-        if !(undirected && edges.contains(&(edge[1].to_string(), edge[0].to_string()))) {
-            edges.push((edge[0].to_string(), edge[1].to_string()));
+        line_start = false;
+    }
+
+    Ok(tokens)
+}
+
+// read a quoted string up to and including the closing quote, of which the opening quote is already read
+fn read_dot_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> io::Result<String> {
+    let mut string = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            // an escaped quote is part of the string, an escaped newline continues the string on the next line
+            Some('\\') => match chars.next() {
+                Some('"') => string.push('"'),
+                Some('\n') => {}
+                Some(c) => {
+                    string.push('\\');
+                    string.push(c);
+                }
+                None => return Err(dot_error("Unterminated string")),
+            },
+            Some(c) => string.push(c),
+            None => return Err(dot_error("Unterminated string")),
         }
     }
+}
+
+// read statements up to the closing brace of the enclosing graph or subgraph, adding the edges
+// returns the vertices in the statements, which are the endpoints of edges to or from a subgraph
+fn parse_dot_statements(
+    tokens: &mut DotTokens,
+    edges: &mut Vec<Edge>,
+    undirected: bool,
+) -> io::Result<Vec<String>> {
+    let mut vertices: Vec<String> = Vec::new();
+
+    while let Some(token) = tokens.peek() {
+        match token {
+            DotToken::Close => break,
+            DotToken::Separator => {
+                tokens.next();
+            }
+            // attribute statements, the attribute lists are skipped by the tokenizer
+            t if ["graph", "node", "edge"]
+                .iter()
+                .any(|keyword| is_dot_keyword(t, keyword)) =>
+            {
+                tokens.next();
+            }
+            _ => {
+                let mut left = parse_dot_operand(tokens, edges, undirected)?;
+
+                // an assignment to a graph attribute, e.g. `label = "cluster"`
+                if tokens.next_if_eq(&DotToken::Equals).is_some() {
+                    parse_dot_operand(tokens, edges, undirected)?;
+                    continue;
+                }
 
-    Ok(edges)
+                add_vertices(&mut vertices, &left);
+
+                while tokens.next_if_eq(&DotToken::Edge).is_some() {
+                    let right = parse_dot_operand(tokens, edges, undirected)?;
+
+                    // every vertex of the left operand is connected to every vertex of the right operand
+                    for v1 in &left {
+                        for v2 in &right {
+                            push_edge(edges, (v1.clone(), v2.clone(), 1.0), undirected);
+                        }
+                    }
+
+                    add_vertices(&mut vertices, &right);
+                    left = right;
+                }
+            }
+        }
+    }
+
+    Ok(vertices)
+}
+
+// read a vertex or a (named) subgraph, returning the vertices it contains
+fn parse_dot_operand(
+    tokens: &mut DotTokens,
+    edges: &mut Vec<Edge>,
+    undirected: bool,
+) -> io::Result<Vec<String>> {
+    if tokens.next_if(|t| is_dot_keyword(t, "subgraph")).is_some() {
+        tokens.next_if(|t| matches!(t, DotToken::Id(..)));
+    } else if let Some(DotToken::Id(id, _)) = tokens.next_if(|t| matches!(t, DotToken::Id(..))) {
+        return Ok(vec![id]);
+    }
+
+    expect_dot(tokens, &DotToken::Open)?;
+    let vertices = parse_dot_statements(tokens, edges, undirected)?;
+    expect_dot(tokens, &DotToken::Close)?;
+
+    Ok(vertices)
+}
+
+fn add_vertices(vertices: &mut Vec<String>, new: &[String]) {
+    for v in new {
+        if !vertices.contains(v) {
+            vertices.push(v.clone());
+        }
+    }
+}
+
+// add an edge, unless the reversed edge is already present in an undirected graph
+fn push_edge(edges: &mut Vec<Edge>, edge: Edge, undirected: bool) {
+    if !(undirected
+        && edges
+            .iter()
+            .any(|(v1, v2, _)| v1 == &edge.1 && v2 == &edge.0))
+    {
+        edges.push(edge);
+    }
 }
 
 fn generate_graph(
@@ -169,13 +486,10 @@ fn generate_graph(
 
     edges.shuffle(&mut rng);
 
-    if let Some(edges) = edges.get(0..num_edges) {
-        Ok(edges.to_vec())
-    } else {
-        Err(anyhow::anyhow!(
-            "Cannot satisfy the desired amount of edges"
-        ))
-    }
+    edges
+        .get(0..num_edges)
+        .map(<[(String, String)]>::to_vec)
+        .ok_or_else(|| anyhow::anyhow!("Cannot satisfy the desired amount of edges"))
 }
 
 fn augment_colors(
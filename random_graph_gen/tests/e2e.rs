@@ -0,0 +1,116 @@
+use std::process::Command;
+
+fn convert(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_random_graph_gen"))
+        .args(args)
+        .output()
+        .expect("failed to run random_graph_gen");
+
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).expect("output is not valid utf-8")
+}
+
+#[test]
+fn test_unweighted_edge_list() {
+    assert_eq!(
+        convert(&["--convert", "tests/data/unweighted.csv"]),
+        "a,b\nb,c\n"
+    );
+}
+
+#[test]
+fn test_weighted_edge_list() {
+    // the weights are carried through, and missing weights default to 1
+    assert_eq!(
+        convert(&["--convert", "tests/data/weighted.csv"]),
+        "a,b,2.5\nb,c,0.5\nc,a,1\nb,a,4\n"
+    );
+
+    // the reversed edge b,a is dropped for undirected graphs
+    assert_eq!(
+        convert(&["--convert", "tests/data/weighted.csv", "-u"]),
+        "a,b,2.5\nb,c,0.5\nc,a,1\n"
+    );
+
+    assert_eq!(
+        convert(&["--convert", "tests/data/weighted.csv", "--min-weight", "1"]),
+        "a,b,2.5\nc,a,1\nb,a,4\n"
+    );
+
+    assert_eq!(
+        convert(&[
+            "--convert",
+            "tests/data/weighted.csv",
+            "--min-weight",
+            "3",
+            "-d"
+        ]),
+        "digraph G {\n    b -> a [label=\"4\"]\n}\n"
+    );
+}
+
+#[test]
+fn test_invalid_edge_list() {
+    let output = Command::new(env!("CARGO_BIN_EXE_random_graph_gen"))
+        .args(["--convert", "tests/data/invalid.csv"])
+        .output()
+        .expect("failed to run random_graph_gen");
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_from_dot() {
+    // attributes, node statements and subgraph structure are ignored, chains result in an edge per pair
+    assert_eq!(
+        convert(&["--from-dot", "tests/data/graph.dot"]),
+        "a,b\nb,c\nc,d\ne,a\nb,a\n"
+    );
+
+    assert_eq!(
+        convert(&["--from-dot", "tests/data/graph.dot", "--undirected"]),
+        "a,b\nb,c\nc,d\ne,a\n"
+    );
+
+    // the edges are used as input for the coloring problem
+    let colored = convert(&["--from-dot", "tests/data/graph.dot", "-u", "-c", "2"]);
+    assert!(colored.lines().all(|l| l.contains("_c")));
+    assert!(!colored.is_empty());
+}
+
+#[test]
+fn test_from_dot_groups() {
+    // an edge to or from a group connects every vertex in the group
+    assert_eq!(
+        convert(&["--from-dot", "tests/data/groups.dot"]),
+        "a,b\na,c\nd,f\ne,f\nf,g\n"
+    );
+}
+
+#[test]
+fn test_from_dot_comments() {
+    assert_eq!(
+        convert(&["--from-dot", "tests/data/comments.dot"]),
+        "a,e\ne,f\n"
+    );
+}
+
+#[test]
+fn test_from_dot_quoted() {
+    // quoted names are not split on spaces or edge operators
+    assert_eq!(
+        convert(&["--from-dot", "tests/data/quoted.dot"]),
+        "queen at a1,queen at b2\nx -> y,node\n"
+    );
+}
+
+#[test]
+fn test_from_dot_invalid() {
+    let output = Command::new(env!("CARGO_BIN_EXE_random_graph_gen"))
+        .args(["--from-dot", "tests/data/unterminated.dot"])
+        .output()
+        .expect("failed to run random_graph_gen");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unterminated string"));
+}
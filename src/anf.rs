@@ -0,0 +1,75 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+
+use crate::bdd::*;
+use crate::BDDSymbol;
+
+/// Render a list of monomials in algebraic normal form, e.g. `1 ^ a ^ a&b`.
+///
+/// The empty monomial is rendered as `1`, and an empty list of monomials as `0`.
+pub fn format_anf<S: BDDSymbol>(monomials: &[Vec<S>]) -> String {
+    if monomials.is_empty() {
+        return "0".to_string();
+    }
+
+    monomials
+        .iter()
+        .map(|monomial| {
+            if monomial.is_empty() {
+                "1".to_string()
+            } else {
+                monomial.iter().join("&")
+            }
+        })
+        .join(" ^ ")
+}
+
+impl<S: BDDSymbol> BDDEnv<S> {
+    /// Compute the algebraic normal form (Reed-Muller expansion) of the bdd.
+    ///
+    /// The result is the list of monomials, each a sorted list of variables, of which the exclusive or equals the function.
+    /// The empty monomial represents the constant `1`. Monomials are ordered by degree, then by their variables.
+    pub fn to_anf(&self, root: &Rc<BDD<S>>) -> Vec<Vec<S>> {
+        let mut cache = FxHashMap::default();
+
+        Self::anf_recursive(root, &mut cache)
+            .into_iter()
+            .sorted_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+            .collect()
+    }
+
+    // f = v ? t : e has the anf of e ^ v&(t ^ e), where the symmetric difference of the monomial sets implements the xor
+    fn anf_recursive(
+        node: &Rc<BDD<S>>,
+        cache: &mut FxHashMap<Rc<BDD<S>>, BTreeSet<Vec<S>>>,
+    ) -> BTreeSet<Vec<S>> {
+        if let Some(monomials) = cache.get(node) {
+            return monomials.clone();
+        }
+
+        let monomials = match node.as_ref() {
+            BDD::False => BTreeSet::new(),
+            BDD::True => BTreeSet::from([Vec::new()]),
+            BDD::Choice(t, v, e, _) => {
+                let t_anf = Self::anf_recursive(t, cache);
+                let e_anf = Self::anf_recursive(e, cache);
+
+                // all variables below v are larger than v, so prepending v keeps the monomials sorted
+                let with_v = t_anf.symmetric_difference(&e_anf).map(|monomial| {
+                    let mut extended = Vec::with_capacity(monomial.len() + 1);
+                    extended.push(v.clone());
+                    extended.extend(monomial.iter().cloned());
+                    extended
+                });
+
+                e_anf.iter().cloned().chain(with_v).collect()
+            }
+        };
+
+        cache.insert(Rc::clone(node), monomials.clone());
+        monomials
+    }
+}
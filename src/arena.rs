@@ -0,0 +1,257 @@
+//! An alternative bdd representation storing all nodes in a single arena.
+//!
+//! Nodes refer to their children by index rather than by reference-counted pointers. Creating or copying a reference
+//! to a node is therefore free, and nodes created close in time are stored close in memory. Nodes are never removed
+//! from the arena, so the memory use only grows until the arena is dropped.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use rustc_hash::FxHashMap;
+
+use crate::bdd::{BDDEnv, BDD};
+use crate::BDDSymbol;
+
+/// The index of a node in an [`ArenaBDD`].
+pub type NodeId = u32;
+
+/// The index of the false terminal, which is the same in every arena.
+pub const FALSE: NodeId = 0;
+
+/// The index of the true terminal, which is the same in every arena.
+pub const TRUE: NodeId = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArenaNode<Symbol: BDDSymbol> {
+    False,
+    True,
+    // Choice (true-node, symbol, false-node)
+    Choice(NodeId, Symbol, NodeId),
+}
+
+// key of a binary operation in the computed table, the operands are ordered since both operations are commutative
+type OperationKey = (NodeId, NodeId);
+
+#[derive(Debug)]
+pub struct ArenaBDD<Symbol: BDDSymbol> {
+    nodes: RefCell<Vec<ArenaNode<Symbol>>>,
+    unique: RefCell<FxHashMap<(NodeId, Symbol, NodeId), NodeId>>,
+    and_cache: RefCell<FxHashMap<OperationKey, NodeId>>,
+    or_cache: RefCell<FxHashMap<OperationKey, NodeId>>,
+    not_cache: RefCell<FxHashMap<NodeId, NodeId>>,
+}
+
+impl<S: BDDSymbol> Default for ArenaBDD<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: BDDSymbol> ArenaBDD<S> {
+    /// Create a new arena containing only the terminal nodes
+    pub fn new() -> Self {
+        Self {
+            nodes: RefCell::new(vec![ArenaNode::False, ArenaNode::True]),
+            unique: RefCell::new(FxHashMap::default()),
+            and_cache: RefCell::new(FxHashMap::default()),
+            or_cache: RefCell::new(FxHashMap::default()),
+            not_cache: RefCell::new(FxHashMap::default()),
+        }
+    }
+
+    /// The number of nodes in the arena, including the terminal nodes
+    pub fn size(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    /// Obtain a copy of the node at the given index.
+    ///
+    /// Panics if the index does not belong to this arena.
+    pub fn node(&self, id: NodeId) -> ArenaNode<S> {
+        self.nodes.borrow()[id as usize].clone()
+    }
+
+    pub const fn mk_const(&self, v: bool) -> NodeId {
+        if v {
+            TRUE
+        } else {
+            FALSE
+        }
+    }
+
+    pub fn var(&self, s: S) -> NodeId {
+        self.mk_choice(TRUE, s, FALSE)
+    }
+
+    /// Make a new choice based on the given symbol and the true and false nodes, reusing an existing node if possible.
+    pub fn mk_choice(&self, true_node: NodeId, symbol: S, false_node: NodeId) -> NodeId {
+        if true_node == false_node {
+            return true_node;
+        }
+
+        let key = (true_node, symbol, false_node);
+
+        if let Some(id) = self.unique.borrow().get(&key) {
+            return *id;
+        }
+
+        let mut nodes = self.nodes.borrow_mut();
+        let id =
+            NodeId::try_from(nodes.len()).expect("the arena exceeds the maximum number of nodes");
+        nodes.push(ArenaNode::Choice(key.0, key.1.clone(), key.2));
+        self.unique.borrow_mut().insert(key, id);
+
+        id
+    }
+
+    /// Logic negation
+    pub fn not(&self, a: NodeId) -> NodeId {
+        let (t, s, f) = match self.node(a) {
+            ArenaNode::False => return TRUE,
+            ArenaNode::True => return FALSE,
+            ArenaNode::Choice(t, s, f) => (t, s, f),
+        };
+
+        if let Some(result) = self.not_cache.borrow().get(&a) {
+            return *result;
+        }
+
+        let result = self.mk_choice(self.not(t), s, self.not(f));
+        self.not_cache.borrow_mut().insert(a, result);
+
+        result
+    }
+
+    /// Logic conjunction
+    pub fn and(&self, a: NodeId, b: NodeId) -> NodeId {
+        if a == FALSE || b == FALSE {
+            return FALSE;
+        }
+        if a == TRUE || a == b {
+            return b;
+        }
+        if b == TRUE {
+            return a;
+        }
+
+        self.apply(a, b, &self.and_cache, Self::and)
+    }
+
+    /// Disjunction
+    pub fn or(&self, a: NodeId, b: NodeId) -> NodeId {
+        if a == TRUE || b == TRUE {
+            return TRUE;
+        }
+        if a == FALSE || a == b {
+            return b;
+        }
+        if b == FALSE {
+            return a;
+        }
+
+        self.apply(a, b, &self.or_cache, Self::or)
+    }
+
+    /// Implication
+    pub fn implies(&self, a: NodeId, b: NodeId) -> NodeId {
+        self.or(self.not(a), b)
+    }
+
+    // apply a commutative operation to two choices by splitting on the smallest top symbol
+    fn apply(
+        &self,
+        a: NodeId,
+        b: NodeId,
+        cache: &RefCell<FxHashMap<OperationKey, NodeId>>,
+        op: fn(&Self, NodeId, NodeId) -> NodeId,
+    ) -> NodeId {
+        let key = (a.min(b), a.max(b));
+
+        if let Some(result) = cache.borrow().get(&key) {
+            return *result;
+        }
+
+        let (at, sa, af) = self.choice(a);
+        let (bt, sb, bf) = self.choice(b);
+
+        let result = match sa.cmp(&sb) {
+            Ordering::Less => self.mk_choice(op(self, at, b), sa, op(self, af, b)),
+            Ordering::Greater => self.mk_choice(op(self, a, bt), sb, op(self, a, bf)),
+            Ordering::Equal => self.mk_choice(op(self, at, bt), sa, op(self, af, bf)),
+        };
+
+        cache.borrow_mut().insert(key, result);
+
+        result
+    }
+
+    // the children and symbol of a choice, terminals are handled by the callers
+    fn choice(&self, a: NodeId) -> (NodeId, S, NodeId) {
+        match self.node(a) {
+            ArenaNode::Choice(t, s, f) => (t, s, f),
+            _ => unreachable!("constants are handled by the callers"),
+        }
+    }
+
+    /// Convert the function represented by the node to the standard representation in the given environment.
+    pub fn to_bdd(&self, a: NodeId, env: &BDDEnv<S>) -> Rc<BDD<S>> {
+        let mut cache: FxHashMap<NodeId, Rc<BDD<S>>> = FxHashMap::default();
+        self.to_bdd_recursive(a, env, &mut cache)
+    }
+
+    fn to_bdd_recursive(
+        &self,
+        a: NodeId,
+        env: &BDDEnv<S>,
+        cache: &mut FxHashMap<NodeId, Rc<BDD<S>>>,
+    ) -> Rc<BDD<S>> {
+        if let Some(result) = cache.get(&a) {
+            return Rc::clone(result);
+        }
+
+        let result = match self.node(a) {
+            ArenaNode::False => env.mk_const(false),
+            ArenaNode::True => env.mk_const(true),
+            ArenaNode::Choice(t, v, f) => env.mk_choice(
+                self.to_bdd_recursive(t, env, cache),
+                v,
+                self.to_bdd_recursive(f, env, cache),
+            ),
+        };
+
+        cache.insert(a, Rc::clone(&result));
+
+        result
+    }
+
+    /// Convert a bdd in the standard representation to a node in this arena.
+    pub fn from_bdd(&self, a: &Rc<BDD<S>>) -> NodeId {
+        let mut cache: FxHashMap<*const BDD<S>, NodeId> = FxHashMap::default();
+        self.import_recursive(a, &mut cache)
+    }
+
+    fn import_recursive(
+        &self,
+        a: &Rc<BDD<S>>,
+        cache: &mut FxHashMap<*const BDD<S>, NodeId>,
+    ) -> NodeId {
+        if let Some(result) = cache.get(&Rc::as_ptr(a)) {
+            return *result;
+        }
+
+        let result = match a.as_ref() {
+            BDD::False => FALSE,
+            BDD::True => TRUE,
+            BDD::Choice(t, v, f, _) => {
+                let t = self.import_recursive(t, cache);
+                let f = self.import_recursive(f, cache);
+                self.mk_choice(t, v.clone(), f)
+            }
+        };
+
+        cache.insert(Rc::as_ptr(a), result);
+
+        result
+    }
+}
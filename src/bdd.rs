@@ -1,10 +1,12 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 use itertools::Itertools;
-use rustc_hash::{FxHashMap, FxHasher};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
 
 use crate::{BDDSymbol, NamedSymbol, TruthTableEntry};
 
@@ -21,13 +23,85 @@ macro_rules! bdd {
     };
 }
 
-#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Default)]
 pub enum BDD<Symbol: BDDSymbol> {
     #[default]
     False,
     True,
-    // Choice (true-subtree, symbol, false-subtree)
-    Choice(Rc<BDD<Symbol>>, Symbol, Rc<BDD<Symbol>>),
+    // Choice (true-subtree, symbol, false-subtree, structural hash)
+    // the structural hash is computed once from the hashes of the subtrees, use BDD::choice to construct a choice
+    Choice(Rc<Self>, Symbol, Rc<Self>, StructuralHash),
+}
+
+/// The structural hash cached in a choice node.
+///
+/// The hash can only be computed by [`BDD::choice`], such that the cached hash always matches the subtrees.
+/// Use [`BDD::get_hash`] to obtain its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructuralHash(u64);
+
+// the structural hashes of the terminal nodes
+const FALSE_HASH: u64 = 0;
+const TRUE_HASH: u64 = 1;
+
+impl<Symbol: BDDSymbol> PartialEq for BDD<Symbol> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::False, Self::False) | (Self::True, Self::True) => true,
+            // compare the cached hashes first, the subtrees only need to be compared if they are not shared
+            (Self::Choice(at, va, af, ha), Self::Choice(bt, vb, bf, hb)) => {
+                ha == hb
+                    && va == vb
+                    && (Rc::ptr_eq(at, bt) || at == bt)
+                    && (Rc::ptr_eq(af, bf) || af == bf)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<Symbol: BDDSymbol> Eq for BDD<Symbol> {}
+
+// a canonical order independent of the memory location of the nodes: the terminals precede all choices, and choices
+// are ordered by symbol and the structural hashes of their subtrees, only comparing the subtrees on hash collisions
+impl<Symbol: BDDSymbol> Ord for BDD<Symbol> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Choice(at, va, af, _), Self::Choice(bt, vb, bf, _)) => va
+                .cmp(vb)
+                .then_with(|| at.get_hash().cmp(&bt.get_hash()))
+                .then_with(|| af.get_hash().cmp(&bf.get_hash()))
+                .then_with(|| {
+                    if Rc::ptr_eq(at, bt) {
+                        Equal
+                    } else {
+                        at.cmp(bt)
+                    }
+                })
+                .then_with(|| {
+                    if Rc::ptr_eq(af, bf) {
+                        Equal
+                    } else {
+                        af.cmp(bf)
+                    }
+                }),
+            (Self::Choice(..), _) => Greater,
+            (_, Self::Choice(..)) => Less,
+            _ => self.get_hash().cmp(&other.get_hash()),
+        }
+    }
+}
+
+impl<Symbol: BDDSymbol> PartialOrd for BDD<Symbol> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Symbol: BDDSymbol> Hash for BDD<Symbol> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.get_hash());
+    }
 }
 
 impl<Symbol> BDD<Symbol>
@@ -35,7 +109,7 @@ where
     Symbol: BDDSymbol,
 {
     pub const fn is_choice(&self) -> bool {
-        matches!(self, Self::Choice(_, _, _))
+        matches!(self, Self::Choice(_, _, _, _))
     }
 
     pub const fn is_const(&self) -> bool {
@@ -52,7 +126,7 @@ where
 
     pub fn node_list(self: &Rc<Self>) -> Vec<Rc<Self>> {
         match self.as_ref() {
-            Self::Choice(l, _, r) => {
+            Self::Choice(l, _, r, _) => {
                 let l_nodes = l.node_list();
                 let r_nodes = r.node_list();
 
@@ -66,6 +140,28 @@ where
             Self::True | Self::False => vec![Rc::clone(self)],
         }
     }
+
+    /// The length of the longest path from this node to a terminal, i.e. the largest number of variables on a path.
+    pub fn depth(&self) -> usize {
+        Self::depth_recursive(self, &mut FxHashMap::default())
+    }
+
+    fn depth_recursive(node: &Self, cache: &mut FxHashMap<*const Self, usize>) -> usize {
+        match node {
+            Self::True | Self::False => 0,
+            Self::Choice(t, _, f, _) => {
+                if let Some(depth) = cache.get(&(node as *const Self)) {
+                    return *depth;
+                }
+
+                let depth =
+                    1 + Self::depth_recursive(t, cache).max(Self::depth_recursive(f, cache));
+                cache.insert(node as *const Self, depth);
+
+                depth
+            }
+        }
+    }
 }
 
 impl From<BDD<NamedSymbol>> for BDD<usize> {
@@ -73,7 +169,7 @@ impl From<BDD<NamedSymbol>> for BDD<usize> {
         match bdd {
             BDD::False => Self::False,
             BDD::True => Self::True,
-            BDD::Choice(true_subtree, symbol, false_subtree) => Self::Choice(
+            BDD::Choice(true_subtree, symbol, false_subtree, _) => Self::choice(
                 Rc::new(Self::from(true_subtree.as_ref().clone())),
                 symbol.into(),
                 Rc::new(Self::from(false_subtree.as_ref().clone())),
@@ -82,19 +178,157 @@ impl From<BDD<NamedSymbol>> for BDD<usize> {
     }
 }
 
+impl BDD<NamedSymbol> {
+    /// Restore the named symbols of a bdd previously converted to its compact `usize` form.
+    /// Every `usize` symbol is mapped to the symbol in `names` with the corresponding id.
+    ///
+    /// Panics if a symbol in the bdd has no corresponding name.
+    pub fn relabel(bdd: &BDD<usize>, names: &[NamedSymbol]) -> Self {
+        let name_map: FxHashMap<usize, &NamedSymbol> = names.iter().map(|n| (n.id, n)).collect();
+
+        Self::relabel_recursive(bdd, &name_map)
+    }
+
+    fn relabel_recursive(bdd: &BDD<usize>, name_map: &FxHashMap<usize, &NamedSymbol>) -> Self {
+        match bdd {
+            BDD::False => Self::False,
+            BDD::True => Self::True,
+            BDD::Choice(true_subtree, symbol, false_subtree, _) => Self::choice(
+                Rc::new(Self::relabel_recursive(true_subtree, name_map)),
+                (*name_map
+                    .get(symbol)
+                    .unwrap_or_else(|| panic!("no name provided for variable with id {symbol}")))
+                .clone(),
+                Rc::new(Self::relabel_recursive(false_subtree, name_map)),
+            ),
+        }
+    }
+}
+
 impl<S: BDDSymbol> BDD<S> {
-    pub fn get_hash(&self) -> u64 {
+    /// Construct a new choice node, the structural hash is derived from the (cached) hashes of both subtrees.
+    pub fn choice(true_subtree: Rc<Self>, symbol: S, false_subtree: Rc<Self>) -> Self {
         let mut s = FxHasher::default();
-        self.hash(&mut s);
-        s.finish()
+        s.write_u64(true_subtree.get_hash());
+        symbol.hash(&mut s);
+        s.write_u64(false_subtree.get_hash());
+        let hash = s.finish();
+
+        Self::Choice(true_subtree, symbol, false_subtree, StructuralHash(hash))
+    }
+
+    /// Obtain the structural hash of this node in constant time.
+    pub const fn get_hash(&self) -> u64 {
+        match self {
+            Self::False => FALSE_HASH,
+            Self::True => TRUE_HASH,
+            Self::Choice(_, _, _, hash) => hash.0,
+        }
+    }
+}
+
+// the forced variables of a subtree, or None if the subtree is unsatisfiable
+type ForcedVariablesCache<S> = FxHashMap<*const BDD<S>, Option<Vec<(S, bool)>>>;
+
+// the generalized cofactors computed so far, keyed by the function and the care set
+type ConstrainCache<S> = FxHashMap<(*const BDD<S>, *const BDD<S>), Rc<BDD<S>>>;
+
+/// Node creation statistics collected by a profiling environment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    /// The number of calls to [`BDDEnv::mk_choice`]
+    pub mk_choice_calls: usize,
+    /// The number of choices that were already present in the lookup table
+    pub cache_hits: usize,
+    /// The number of choices that were newly inserted in the lookup table
+    pub cache_misses: usize,
+    /// The largest observed size of the lookup table
+    pub peak_size: usize,
+}
+
+/// The number of satisfying assignments of a formula, distinguishing only none, one, or more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Uniqueness {
+    None,
+    Unique,
+    Multiple,
+}
+
+impl std::fmt::Display for Uniqueness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Unique => write!(f, "unique"),
+            Self::Multiple => write!(f, "multiple"),
+        }
     }
 }
 
+/// A violation of the canonical form of a reduced ordered bdd, found by [`BDDEnv::assert_canonical`].
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonicalityError<S: BDDSymbol> {
+    /// Two distinct nodes with the same structure, identified by their structural hash
+    Duplicate(u64),
+    /// A choice on the symbol with equal subtrees
+    Redundant(S),
+    /// A choice on the parent symbol with a subtree rooted at a symbol which is not ordered after the parent
+    Unordered { parent: S, child: S },
+}
+
+impl<S: BDDSymbol> std::fmt::Display for CanonicalityError<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Duplicate(hash) => {
+                write!(f, "multiple distinct nodes with structural hash {hash:x}")
+            }
+            Self::Redundant(s) => write!(f, "the choice on {s} has equal subtrees"),
+            Self::Unordered { parent, child } => {
+                write!(f, "the choice on {parent} has a subtree rooted at {child}, which is not ordered after {parent}")
+            }
+        }
+    }
+}
+
+impl<S: BDDSymbol> std::error::Error for CanonicalityError<S> {}
+
+/// The panic payload raised when a limited environment would grow beyond its maximum number of nodes,
+/// see [`BDDEnv::new_with_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeLimitExceeded {
+    /// The maximum number of nodes in the lookup table
+    pub limit: usize,
+}
+
+impl std::fmt::Display for NodeLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "node limit of {} exceeded", self.limit)
+    }
+}
+
+impl std::error::Error for NodeLimitExceeded {}
+
+/// The environment holding the lookup table of all unique nodes.
+///
+/// Nodes are shared using `Rc` and the lookup table lives in a `RefCell`, so an environment (and the bdds built in it)
+/// cannot be sent to other threads. All operations run sequentially on the calling thread; there is no thread pool
+/// involved in evaluation, so benchmark timings are not affected by the number of available cores.
+#[derive(Debug, Clone)]
 pub struct BDDEnv<Symbol: BDDSymbol> {
     pub nodes: RefCell<FxHashMap<BDD<Symbol>, Rc<BDD<Symbol>>>>,
+    profiling: bool,
+    profile: Cell<ProfileReport>,
+    max_nodes: Option<usize>,
 }
 
+// profiling statistics do not contribute to the equivalence of two environments
+impl<S: BDDSymbol> PartialEq for BDDEnv<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+    }
+}
+
+impl<S: BDDSymbol> Eq for BDDEnv<S> {}
+
 impl<S: BDDSymbol> Default for BDDEnv<S> {
     fn default() -> Self {
         Self::new()
@@ -107,11 +341,73 @@ impl<S: BDDSymbol> BDDEnv<S> {
         self.nodes.borrow().len()
     }
 
+    /// Compute the number of nodes reachable from the given root.
+    /// Unlike [`BDDEnv::size`], this excludes dead nodes left behind by intermediate computations.
+    /// The terminals are only counted if they are reachable.
+    pub fn size_reachable(&self, root: &Rc<BDD<S>>) -> usize {
+        root.node_list()
+            .iter()
+            .unique_by(|&n| Rc::as_ptr(n))
+            .count()
+    }
+
+    /// Remove all nodes from the lookup table that are not reachable from any of the given roots.
+    /// The 'true' and 'false' terminals are always retained.
+    ///
+    /// Outstanding references to removed nodes remain valid, but can no longer be found using [`BDDEnv::find`].
+    /// New nodes equivalent to a removed node will not share memory with the removed node.
+    pub fn gc(&self, roots: &[Rc<BDD<S>>]) {
+        let mut reachable: FxHashSet<*const BDD<S>> = FxHashSet::default();
+        let mut stack: Vec<Rc<BDD<S>>> = roots.to_vec();
+
+        while let Some(node) = stack.pop() {
+            if reachable.insert(Rc::as_ptr(&node)) {
+                if let BDD::Choice(t, _, f, _) = node.as_ref() {
+                    stack.push(Rc::clone(t));
+                    stack.push(Rc::clone(f));
+                }
+            }
+        }
+
+        self.nodes
+            .borrow_mut()
+            .retain(|k, v| k.is_const() || reachable.contains(&Rc::as_ptr(v)));
+    }
+
+    /// The number of distinct nodes labeled with each variable occurring in root, ordered by the variable ordering.
+    /// The i-th entry is the width of the level of the i-th smallest variable in root.
+    pub fn level_widths(&self, root: &Rc<BDD<S>>) -> Vec<usize> {
+        self.level_map(root)
+            .into_values()
+            .map(|level| level.len())
+            .collect()
+    }
+
+    /// Group the choice nodes reachable from root by their variable, e.g. for a layered visualization.
+    /// Every node occurs once, in order of a depth-first traversal taking the true branch first. The terminals are not included.
+    pub fn level_map(&self, root: &Rc<BDD<S>>) -> BTreeMap<S, Vec<Rc<BDD<S>>>> {
+        let mut visited: FxHashSet<*const BDD<S>> = FxHashSet::default();
+        let mut levels: BTreeMap<S, Vec<Rc<BDD<S>>>> = BTreeMap::new();
+        let mut stack: Vec<&Rc<BDD<S>>> = vec![root];
+
+        while let Some(node) = stack.pop() {
+            if let BDD::Choice(t, v, f, _) = node.as_ref() {
+                if visited.insert(Rc::as_ptr(node)) {
+                    levels.entry(v.clone()).or_default().push(Rc::clone(node));
+                    stack.push(f);
+                    stack.push(t);
+                }
+            }
+        }
+
+        levels
+    }
+
     // clean tries to reduce all duplicate subtrees to single nodes in the lookup table
     // this function currently has no effect, might be removed later
     pub fn clean(&self, root: Rc<BDD<S>>) -> Rc<BDD<S>> {
         match root.as_ref() {
-            BDD::Choice(l, s, r) => {
+            BDD::Choice(l, s, r, _) => {
                 let _l = self.find(l);
                 let _r = self.find(r);
 
@@ -121,6 +417,52 @@ impl<S: BDDSymbol> BDDEnv<S> {
         }
     }
 
+    /// Check that the bdd is in canonical form: all nodes are unique, no choice has equal subtrees,
+    /// and the symbols on every path are strictly increasing.
+    ///
+    /// Bdds built using the operations of the environment are always canonical; this check is a debugging aid for bdds
+    /// built using the low-level constructors such as [`BDD::choice`] and [`BDDEnv::mk_choice_unchecked`].
+    pub fn assert_canonical(&self, root: &Rc<BDD<S>>) -> Result<(), CanonicalityError<S>> {
+        let mut visited: FxHashSet<*const BDD<S>> = FxHashSet::default();
+        let mut unique: FxHashMap<BDD<S>, *const BDD<S>> = FxHashMap::default();
+        let mut stack = vec![Rc::clone(root)];
+
+        while let Some(node) = stack.pop() {
+            if !visited.insert(Rc::as_ptr(&node)) {
+                continue;
+            }
+
+            // structurally equal nodes at different addresses violate the sharing of the unique table
+            if let Some(&other) = unique.get(node.as_ref()) {
+                if other != Rc::as_ptr(&node) {
+                    return Err(CanonicalityError::Duplicate(node.get_hash()));
+                }
+            }
+            unique.insert(node.as_ref().clone(), Rc::as_ptr(&node));
+
+            if let BDD::Choice(t, v, f, _) = node.as_ref() {
+                if t == f {
+                    return Err(CanonicalityError::Redundant(v.clone()));
+                }
+
+                for child in [t, f] {
+                    if let BDD::Choice(_, w, _, _) = child.as_ref() {
+                        if w <= v {
+                            return Err(CanonicalityError::Unordered {
+                                parent: v.clone(),
+                                child: w.clone(),
+                            });
+                        }
+                    }
+
+                    stack.push(Rc::clone(child));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn duplicates(&self, root: Rc<BDD<S>>) -> usize {
         let all_nodes: Vec<Rc<BDD<S>>> = root.node_list();
 
@@ -152,22 +494,94 @@ impl<S: BDDSymbol> BDDEnv<S> {
         false_subtree: Rc<BDD<S>>,
     ) -> Rc<BDD<S>> {
         // early simplification step
-        let ins = self.simplify(&Rc::new(BDD::Choice(true_subtree, symbol, false_subtree)));
+        let ins = self.simplify(&Rc::new(BDD::choice(true_subtree, symbol, false_subtree)));
+
+        self.canonicalize(ins)
+    }
+
+    /// Make a new choice based on the given symbol and the left and right subtree, without simplifying it first.
+    /// The new choice is still looked up in (or added to) the lookup table.
+    ///
+    /// Precondition: both subtrees must already be reduced nodes of this environment, they must be different,
+    /// and the symbol must be strictly less than the root symbols of both subtrees.
+    /// Violating this precondition results in a non-canonical BDD; in debug builds it causes a panic.
+    pub fn mk_choice_unchecked(
+        &self,
+        true_subtree: Rc<BDD<S>>,
+        symbol: S,
+        false_subtree: Rc<BDD<S>>,
+    ) -> Rc<BDD<S>> {
+        debug_assert!(
+            true_subtree != false_subtree,
+            "mk_choice_unchecked: both subtrees of {symbol} are equal"
+        );
+        debug_assert!(
+            [&true_subtree, &false_subtree]
+                .iter()
+                .all(|c| match c.as_ref() {
+                    BDD::Choice(_, s, _, _) => &symbol < s,
+                    _ => true,
+                }),
+            "mk_choice_unchecked: {symbol} is not ordered before the root symbols of its subtrees"
+        );
+
+        self.canonicalize(Rc::new(BDD::choice(true_subtree, symbol, false_subtree)))
+    }
 
+    // look up the node in the lookup table, or insert it if it does not exist yet
+    fn canonicalize(&self, ins: Rc<BDD<S>>) -> Rc<BDD<S>> {
         // pre-borrow the nodes as mutable
         let mut nodes_borrow = self.nodes.borrow_mut();
 
         #[allow(clippy::option_if_let_else)]
         // if the node already exists, return a reference to it
         if let Some(subtree) = nodes_borrow.get(&ins) {
+            self.record_choice(true, nodes_borrow.len());
             Rc::clone(subtree)
         } else {
+            if let Some(limit) = self.max_nodes {
+                if nodes_borrow.len() >= limit {
+                    drop(nodes_borrow);
+                    std::panic::panic_any(NodeLimitExceeded { limit });
+                }
+            }
+
             // only insert if it is not already in the lookup table
             nodes_borrow.insert(ins.as_ref().clone(), Rc::clone(&ins));
+            self.record_choice(false, nodes_borrow.len());
             Rc::clone(&ins)
         }
     }
 
+    // update the profiling counters after a call to mk_choice, this is a no-op if profiling is disabled
+    #[inline]
+    fn record_choice(&self, hit: bool, size: usize) {
+        if self.profiling {
+            let mut report = self.profile.get();
+
+            report.mk_choice_calls += 1;
+            if hit {
+                report.cache_hits += 1;
+            } else {
+                report.cache_misses += 1;
+            }
+            report.peak_size = report.peak_size.max(size);
+
+            self.profile.set(report);
+        }
+    }
+
+    /// Retrieve the node creation statistics collected so far.
+    /// All counters remain zero if the environment was not created using [`BDDEnv::with_profiling`].
+    pub const fn profile_report(&self) -> ProfileReport {
+        self.profile.get()
+    }
+
+    /// Returns true if this environment collects node creation statistics
+    pub const fn is_profiling(&self) -> bool {
+        self.profiling
+    }
+
     /// Find the true or false node in the lookup table and return a reference to it.
     pub fn mk_const(&self, v: bool) -> Rc<BDD<S>> {
         if v {
@@ -186,13 +600,58 @@ impl<S: BDDSymbol> BDDEnv<S> {
 
     /// Create a new BDD graph
     pub fn new() -> Self {
-        let mut nodes = FxHashMap::default();
+        Self::new_sized(0)
+    }
+
+    /// Create a new BDD graph with room for at least capacity nodes in the lookup table.
+    /// Pre-sizing the table avoids repeated rehashing while building large bdds.
+    pub fn new_sized(capacity: usize) -> Self {
+        let mut nodes = FxHashMap::with_capacity_and_hasher(capacity, Default::default());
 
         nodes.insert(BDD::True, Rc::new(BDD::True));
         nodes.insert(BDD::False, Rc::new(BDD::False));
 
         Self {
             nodes: RefCell::new(nodes),
+            profiling: false,
+            profile: Cell::default(),
+            max_nodes: None,
+        }
+    }
+
+    /// Create a new BDD graph of which the lookup table may contain at most max_nodes nodes, including the terminals.
+    ///
+    /// Creating a node beyond this limit panics with a [`NodeLimitExceeded`] payload,
+    /// which can be recovered using [`std::panic::catch_unwind`] or reported by a panic hook.
+    pub fn new_with_limits(max_nodes: usize) -> Self {
+        Self::new().with_max_nodes(max_nodes)
+    }
+
+    /// Limit the number of nodes in the lookup table of this environment, see [`BDDEnv::new_with_limits`].
+    #[must_use]
+    pub fn with_max_nodes(self, max_nodes: usize) -> Self {
+        Self {
+            max_nodes: Some(max_nodes),
+            ..self
+        }
+    }
+
+    /// The maximum number of nodes in the lookup table, if the environment is limited
+    pub const fn max_nodes(&self) -> Option<usize> {
+        self.max_nodes
+    }
+
+    /// Create a new BDD graph which collects node creation statistics, see [`BDDEnv::profile_report`]
+    pub fn with_profiling() -> Self {
+        let env = Self::new();
+
+        Self {
+            profiling: true,
+            profile: Cell::new(ProfileReport {
+                peak_size: env.size(),
+                ..Default::default()
+            }),
+            ..env
         }
     }
 
@@ -202,17 +661,17 @@ impl<S: BDDSymbol> BDDEnv<S> {
             (BDD::False, _) | (_, &BDD::False) => self.mk_const(false),
             (BDD::True, _) => Rc::clone(&b),
             (_, BDD::True) => Rc::clone(&a),
-            (BDD::Choice(at, va, af), BDD::Choice(_, vb, _)) if va < vb => self.mk_choice(
+            (BDD::Choice(at, va, af, _), BDD::Choice(_, vb, _, _)) if va < vb => self.mk_choice(
                 self.and(Rc::clone(at), Rc::clone(&b)),
                 va.clone(),
                 self.and(Rc::clone(af), Rc::clone(&b)),
             ),
-            (BDD::Choice(_, va, _), BDD::Choice(bt, vb, bf)) if vb < va => self.mk_choice(
+            (BDD::Choice(_, va, _, _), BDD::Choice(bt, vb, bf, _)) if vb < va => self.mk_choice(
                 self.and(Rc::clone(bt), Rc::clone(&a)),
                 vb.clone(),
                 self.and(Rc::clone(bf), Rc::clone(&a)),
             ),
-            (BDD::Choice(at, va, af), BDD::Choice(bt, vb, bf)) if va == vb => self.mk_choice(
+            (BDD::Choice(at, va, af, _), BDD::Choice(bt, vb, bf, _)) if va == vb => self.mk_choice(
                 self.and(Rc::clone(at), Rc::clone(bt)),
                 va.clone(),
                 self.and(Rc::clone(af), Rc::clone(bf)),
@@ -221,6 +680,36 @@ impl<S: BDDSymbol> BDDEnv<S> {
         }
     }
 
+    /// Conjunction of all given BDDs, combined pairwise in a balanced manner to keep the intermediate results small.
+    /// The conjunction of an empty list is true.
+    /// The computation stops as soon as an operand or intermediate result is false.
+    pub fn and_all(&self, mut branches: Vec<Rc<BDD<S>>>) -> Rc<BDD<S>> {
+        if branches.iter().any(|b| b.is_false()) {
+            return self.mk_const(false);
+        }
+
+        while branches.len() > 1 {
+            let mut combined = Vec::with_capacity(branches.len().div_ceil(2));
+
+            for pair in branches.chunks(2) {
+                let result = match pair {
+                    [a, b] => self.and(Rc::clone(a), Rc::clone(b)),
+                    _ => Rc::clone(&pair[0]),
+                };
+
+                if result.is_false() {
+                    return result;
+                }
+
+                combined.push(result);
+            }
+
+            branches = combined;
+        }
+
+        branches.pop().unwrap_or_else(|| self.mk_const(true))
+    }
+
     /// Disjunction
     pub fn or(&self, a: Rc<BDD<S>>, b: Rc<BDD<S>>) -> Rc<BDD<S>> {
         // self.not(self.nor(a, b))
@@ -230,17 +719,17 @@ impl<S: BDDSymbol> BDDEnv<S> {
             (BDD::False, _) => Rc::clone(&b),
             (_, &BDD::False) => Rc::clone(&a),
             // todo:
-            (BDD::Choice(at, va, af), BDD::Choice(_, vb, _)) if va < vb => self.mk_choice(
+            (BDD::Choice(at, va, af, _), BDD::Choice(_, vb, _, _)) if va < vb => self.mk_choice(
                 self.or(Rc::clone(at), Rc::clone(&b)),
                 va.clone(),
                 self.or(Rc::clone(af), Rc::clone(&b)),
             ),
-            (BDD::Choice(_, va, _), BDD::Choice(bt, vb, bf)) if vb < va => self.mk_choice(
+            (BDD::Choice(_, va, _, _), BDD::Choice(bt, vb, bf, _)) if vb < va => self.mk_choice(
                 self.or(Rc::clone(bt), Rc::clone(&a)),
                 vb.clone(),
                 self.or(Rc::clone(bf), Rc::clone(&a)),
             ),
-            (BDD::Choice(at, va, af), BDD::Choice(bt, vb, bf)) if va == vb => self.mk_choice(
+            (BDD::Choice(at, va, af, _), BDD::Choice(bt, vb, bf, _)) if va == vb => self.mk_choice(
                 self.or(Rc::clone(at), Rc::clone(bt)),
                 va.clone(),
                 self.or(Rc::clone(af), Rc::clone(bf)),
@@ -254,7 +743,7 @@ impl<S: BDDSymbol> BDDEnv<S> {
         match a.as_ref() {
             BDD::False => self.mk_const(true),
             BDD::True => self.mk_const(false),
-            BDD::Choice(at, va, af) => {
+            BDD::Choice(at, va, af, _) => {
                 self.mk_choice(self.not(Rc::clone(at)), va.clone(), self.not(Rc::clone(af)))
             }
         }
@@ -267,12 +756,43 @@ impl<S: BDDSymbol> BDDEnv<S> {
 
     /// ite computes if a then b else c
     pub fn ite(&self, a: Rc<BDD<S>>, b: Rc<BDD<S>>, c: Rc<BDD<S>>) -> Rc<BDD<S>> {
+        match a.as_ref() {
+            BDD::True => return b,
+            BDD::False => return c,
+            BDD::Choice(t, v, f, _) if t.is_true() && f.is_false() => {
+                return self.ite_var(v.clone(), b, c)
+            }
+            BDD::Choice(..) if b == c => return b,
+            BDD::Choice(..) => {}
+        }
+
         self.and(
             self.implies(Rc::clone(&a), Rc::clone(&b)),
             self.implies(self.not(Rc::clone(&a)), Rc::clone(&c)),
         )
     }
 
+    /// ite_var computes if s then b else c for a single variable s.
+    /// If s is ordered before all variables in b and c, the choice is built directly.
+    pub fn ite_var(&self, s: S, b: Rc<BDD<S>>, c: Rc<BDD<S>>) -> Rc<BDD<S>> {
+        if b == c {
+            return b;
+        }
+
+        let ordered = [&b, &c].iter().all(|n| match n.as_ref() {
+            BDD::Choice(_, v, _, _) => &s < v,
+            _ => true,
+        });
+
+        if ordered {
+            self.mk_choice_unchecked(b, s, c)
+        } else {
+            let a = self.var(s);
+
+            self.and(self.implies(Rc::clone(&a), b), self.implies(self.not(a), c))
+        }
+    }
+
     /// eq computes a iff b
     pub fn eq(&self, a: Rc<BDD<S>>, b: Rc<BDD<S>>) -> Rc<BDD<S>> {
         self.and(
@@ -281,6 +801,15 @@ impl<S: BDDSymbol> BDDEnv<S> {
         )
     }
 
+    /// all_equal computes whether all items have the same truth value, i.e. either all items are true or all are false.
+    /// The result is true for fewer than two items.
+    pub fn all_equal(&self, items: &[Rc<BDD<S>>]) -> Rc<BDD<S>> {
+        self.or(
+            self.and_all(items.to_vec()),
+            self.and_all(items.iter().map(|b| self.not(Rc::clone(b))).collect()),
+        )
+    }
+
     // exclusive disjunction
     pub fn xor(&self, a: Rc<BDD<S>>, b: Rc<BDD<S>>) -> Rc<BDD<S>> {
         self.or(
@@ -301,7 +830,7 @@ impl<S: BDDSymbol> BDDEnv<S> {
 
     /// var constructs a new BDD for a given variable.
     pub fn var(&self, s: S) -> Rc<BDD<S>> {
-        self.mk_choice(self.mk_const(true), s, self.mk_const(false))
+        self.mk_choice_unchecked(self.mk_const(true), s, self.mk_const(false))
     }
 
     fn cmp_count<CmpFn: Fn(i64) -> bool + Copy>(
@@ -310,17 +839,22 @@ impl<S: BDDSymbol> BDDEnv<S> {
         n: i64,
         cmp: CmpFn,
     ) -> Rc<BDD<S>> {
-        if branches.is_empty() {
-            self.mk_const(cmp(n))
-        } else {
-            let first = &branches[0];
-            let remainder = branches[1..].to_vec();
+        // short-circuit if the outcome no longer depends on the remaining branches
+        if (0..=branches.len() as i64).map(|k| cmp(n - k)).all_equal() {
+            return self.mk_const(cmp(n));
+        }
 
-            self.ite(
+        let first = &branches[0];
+        let remainder = &branches[1..];
+
+        match first.as_ref() {
+            BDD::True => self.cmp_count(remainder, n - 1, cmp),
+            BDD::False => self.cmp_count(remainder, n, cmp),
+            BDD::Choice(..) => self.ite(
                 Rc::clone(first),
-                self.cmp_count(&remainder, n - 1, cmp),
-                self.cmp_count(&remainder, n, cmp),
-            )
+                self.cmp_count(remainder, n - 1, cmp),
+                self.cmp_count(remainder, n, cmp),
+            ),
         }
     }
 
@@ -339,12 +873,103 @@ impl<S: BDDSymbol> BDDEnv<S> {
         self.cmp_count(branches, n, |n| n == 0)
     }
 
+    /// the assignment of vars differs from the reference assignment in at most d positions, i.e. the Hamming distance
+    /// is at most d.
+    ///
+    /// Panics if vars and reference differ in length.
+    pub fn hamming_leq(&self, vars: &[S], reference: &[bool], d: i64) -> Rc<BDD<S>> {
+        assert_eq!(
+            vars.len(),
+            reference.len(),
+            "the reference must assign a value to every variable"
+        );
+
+        // a position differs if the variable is the negation of its reference bit
+        let mismatches: Vec<Rc<BDD<S>>> = vars
+            .iter()
+            .zip(reference)
+            .map(|(v, &bit)| {
+                let var = self.var(v.clone());
+                if bit {
+                    self.not(var)
+                } else {
+                    var
+                }
+            })
+            .collect();
+
+        self.amn(&mismatches, d)
+    }
+
+    /// the weighted sum of the true branches is at most bound, i.e. the pseudo-boolean constraint
+    /// `w1 * b1 + w2 * b2 + ... <= bound`. The weights may be negative.
+    pub fn pb_leq(&self, weighted: &[(i64, Rc<BDD<S>>)], bound: i64) -> Rc<BDD<S>> {
+        // the smallest and largest sum of the weights from every position to the end
+        let mut min_sum = vec![0; weighted.len() + 1];
+        let mut max_sum = vec![0; weighted.len() + 1];
+        for (i, (w, _)) in weighted.iter().enumerate().rev() {
+            min_sum[i] = min_sum[i + 1] + w.min(&0);
+            max_sum[i] = max_sum[i + 1] + w.max(&0);
+        }
+
+        self.pb_leq_recursive(
+            weighted,
+            0,
+            bound,
+            &min_sum,
+            &max_sum,
+            &mut FxHashMap::default(),
+        )
+    }
+
+    fn pb_leq_recursive(
+        &self,
+        weighted: &[(i64, Rc<BDD<S>>)],
+        i: usize,
+        bound: i64,
+        min_sum: &[i64],
+        max_sum: &[i64],
+        cache: &mut FxHashMap<(usize, i64), Rc<BDD<S>>>,
+    ) -> Rc<BDD<S>> {
+        // short-circuit if the outcome no longer depends on the remaining branches
+        if max_sum[i] <= bound {
+            return self.mk_const(true);
+        }
+        if min_sum[i] > bound {
+            return self.mk_const(false);
+        }
+
+        if let Some(result) = cache.get(&(i, bound)) {
+            return Rc::clone(result);
+        }
+
+        let (w, branch) = &weighted[i];
+        let result = self.ite(
+            Rc::clone(branch),
+            self.pb_leq_recursive(weighted, i + 1, bound - w, min_sum, max_sum, cache),
+            self.pb_leq_recursive(weighted, i + 1, bound, min_sum, max_sum, cache),
+        );
+
+        cache.insert((i, bound), Rc::clone(&result));
+        result
+    }
+
     pub fn count_leq(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>]) -> Rc<BDD<S>> {
-        self.count_leq_recursive(a, b, 0)
+        self.count_leq_offset(a, b, 0)
     }
 
     pub fn count_lt(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>]) -> Rc<BDD<S>> {
-        self.count_leq_recursive(a, b, 1)
+        self.count_lt_offset(a, b, 0)
+    }
+
+    /// the number of true branches in a is at most the number of true branches in b plus k
+    pub fn count_leq_offset(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>], k: i64) -> Rc<BDD<S>> {
+        self.count_leq_recursive(a, b, -k)
+    }
+
+    /// the number of true branches in a is less than the number of true branches in b plus k
+    pub fn count_lt_offset(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>], k: i64) -> Rc<BDD<S>> {
+        self.count_leq_recursive(a, b, 1 - k)
     }
 
     fn count_leq_recursive(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>], n: i64) -> Rc<BDD<S>> {
@@ -378,15 +1003,42 @@ impl<S: BDDSymbol> BDDEnv<S> {
     }
 
     pub fn count_gt(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>]) -> Rc<BDD<S>> {
-        self.count_geq_recursive(a, b, -1)
+        self.count_gt_offset(a, b, 0)
     }
 
     pub fn count_geq(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>]) -> Rc<BDD<S>> {
-        self.count_geq_recursive(a, b, 0)
+        self.count_geq_offset(a, b, 0)
     }
 
     pub fn count_eq(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>]) -> Rc<BDD<S>> {
-        self.and(self.count_leq(a, b), self.count_geq(a, b))
+        self.count_eq_offset(a, b, 0)
+    }
+
+    pub fn count_ne(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>]) -> Rc<BDD<S>> {
+        self.count_ne_offset(a, b, 0)
+    }
+
+    /// the number of true branches in a is more than the number of true branches in b plus k
+    pub fn count_gt_offset(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>], k: i64) -> Rc<BDD<S>> {
+        self.count_geq_recursive(a, b, -1 - k)
+    }
+
+    /// the number of true branches in a is at least the number of true branches in b plus k
+    pub fn count_geq_offset(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>], k: i64) -> Rc<BDD<S>> {
+        self.count_geq_recursive(a, b, -k)
+    }
+
+    /// the number of true branches in a is exactly the number of true branches in b plus k
+    pub fn count_eq_offset(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>], k: i64) -> Rc<BDD<S>> {
+        self.and(
+            self.count_leq_offset(a, b, k),
+            self.count_geq_offset(a, b, k),
+        )
+    }
+
+    /// the number of true branches in a is not equal to the number of true branches in b plus k
+    pub fn count_ne_offset(&self, a: &[Rc<BDD<S>>], b: &[Rc<BDD<S>>], k: i64) -> Rc<BDD<S>> {
+        self.not(self.count_eq_offset(a, b, k))
     }
 
     pub fn exists(&self, s: Vec<S>, b: Rc<BDD<S>>) -> Rc<BDD<S>> {
@@ -400,12 +1052,53 @@ impl<S: BDDSymbol> BDDEnv<S> {
         }
     }
 
+    /// Project b onto the variables in keep, by existentially quantifying every variable in all_vars which is not kept.
+    ///
+    /// Variables which occur in b but not in all_vars are not quantified.
+    pub fn project(&self, keep: &[S], b: Rc<BDD<S>>, all_vars: &[S]) -> Rc<BDD<S>> {
+        let eliminated: Vec<S> = all_vars
+            .iter()
+            .filter(|v| !keep.contains(v))
+            .cloned()
+            .collect();
+
+        self.exists(eliminated, b)
+    }
+
+    /// Compute `exists quantify # (c1 & c2 & ... & cn)` without constructing the full conjunction.
+    ///
+    /// The conjuncts are conjoined in order, and every quantified variable is eliminated as soon as it no longer occurs
+    /// in the remaining conjuncts (early quantification). This keeps the intermediate results small when the conjuncts
+    /// only share few variables, e.g. in image computations.
+    pub fn and_exists_chain(&self, conjuncts: &[Rc<BDD<S>>], quantify: &[S]) -> Rc<BDD<S>> {
+        let supports: Vec<Vec<S>> = conjuncts.iter().map(|c| self.support(c)).collect();
+
+        // group the quantified variables by the last conjunct they occur in, other variables need no quantification
+        let mut eliminate: Vec<Vec<S>> = vec![Vec::new(); conjuncts.len()];
+        for v in quantify {
+            if let Some(last) = supports.iter().rposition(|s| s.binary_search(v).is_ok()) {
+                eliminate[last].push(v.clone());
+            }
+        }
+
+        let mut result = self.mk_const(true);
+        for (c, vars) in conjuncts.iter().zip(eliminate) {
+            result = self.exists(vars, self.and(result, Rc::clone(c)));
+
+            if result.is_false() {
+                break;
+            }
+        }
+
+        result
+    }
+
     // existential quantification
     pub fn exists_impl(&self, s: &S, b: Rc<BDD<S>>) -> Rc<BDD<S>> {
         match b.as_ref() {
             BDD::False | &BDD::True => b,
-            BDD::Choice(t, v, f) if v == s => self.or(Rc::clone(t), Rc::clone(f)),
-            BDD::Choice(t, v, f) => self.mk_choice(
+            BDD::Choice(t, v, f, _) if v == s => self.or(Rc::clone(t), Rc::clone(f)),
+            BDD::Choice(t, v, f, _) => self.mk_choice(
                 self.exists_impl(s, Rc::clone(t)),
                 v.clone(),
                 self.exists_impl(s, Rc::clone(f)),
@@ -413,6 +1106,271 @@ impl<S: BDDSymbol> BDDEnv<S> {
         }
     }
 
+    /// Existential quantification over a single variable, while retaining a witness for the eliminated variable.
+    ///
+    /// Returns a tuple `(result, witness)`, where `result` is equivalent to `exists(vec![s], b)` and `witness` is a
+    /// function over the remaining variables that provides a value for `s` satisfying `b` whenever `result` holds.
+    pub fn exists_with_witness(&self, s: &S, b: Rc<BDD<S>>) -> (Rc<BDD<S>>, Rc<BDD<S>>) {
+        let positive = self.restrict(Rc::clone(&b), s, true);
+        let negative = self.restrict(b, s, false);
+
+        // choose 'true' for s whenever the positive cofactor is satisfiable, fall back to 'false' otherwise
+        (self.or(Rc::clone(&positive), negative), positive)
+    }
+
+    /// Evaluate the bdd under a complete assignment, by following the assigned branch of every choice from the root to
+    /// a terminal. Unlike [`BDDEnv::restrict`], no nodes are constructed.
+    ///
+    /// Panics if a variable on the followed path is missing from the assignment.
+    pub fn eval_assignment(&self, root: &Rc<BDD<S>>, assignment: &FxHashMap<S, bool>) -> bool {
+        let mut node = root;
+
+        loop {
+            match node.as_ref() {
+                BDD::True => return true,
+                BDD::False => return false,
+                BDD::Choice(t, v, f, _) => {
+                    let value = assignment
+                        .get(v)
+                        .unwrap_or_else(|| panic!("{v} is not assigned a value"));
+
+                    node = if *value { t } else { f };
+                }
+            }
+        }
+    }
+
+    /// Restrict the variable s to the given value in bdd b (the positive or negative cofactor of b).
+    pub fn restrict(&self, b: Rc<BDD<S>>, s: &S, value: bool) -> Rc<BDD<S>> {
+        match b.as_ref() {
+            BDD::False | &BDD::True => b,
+            BDD::Choice(t, v, f, _) if v == s => {
+                if value {
+                    Rc::clone(t)
+                } else {
+                    Rc::clone(f)
+                }
+            }
+            // the variable cannot occur further down the bdd
+            BDD::Choice(_, v, _, _) if v > s => b,
+            BDD::Choice(t, v, f, _) => self.mk_choice(
+                self.restrict(Rc::clone(t), s, value),
+                v.clone(),
+                self.restrict(Rc::clone(f), s, value),
+            ),
+        }
+    }
+
+    /// Compute both the positive and negative cofactor of b with respect to s in a single descent.
+    /// Equivalent to `(restrict(b, s, true), restrict(b, s, false))`.
+    pub fn cofactors(&self, b: &Rc<BDD<S>>, s: &S) -> (Rc<BDD<S>>, Rc<BDD<S>>) {
+        match b.as_ref() {
+            BDD::False | &BDD::True => (Rc::clone(b), Rc::clone(b)),
+            BDD::Choice(t, v, f, _) if v == s => (Rc::clone(t), Rc::clone(f)),
+            // the variable cannot occur further down the bdd
+            BDD::Choice(_, v, _, _) if v > s => (Rc::clone(b), Rc::clone(b)),
+            BDD::Choice(t, v, f, _) => {
+                let (t_pos, t_neg) = self.cofactors(t, s);
+                let (f_pos, f_neg) = self.cofactors(f, s);
+
+                (
+                    self.mk_choice(t_pos, v.clone(), f_pos),
+                    self.mk_choice(t_neg, v.clone(), f_neg),
+                )
+            }
+        }
+    }
+
+    /// Compute the generalized cofactor (Coudert's constrain) of f with respect to the care set.
+    ///
+    /// The result agrees with f wherever care holds, i.e. `and(constrain(f, care), care) == and(f, care)`, and is
+    /// usually smaller than f since its value outside of the care set is chosen freely. If care is false, the result is false.
+    pub fn constrain(&self, f: &Rc<BDD<S>>, care: &Rc<BDD<S>>) -> Rc<BDD<S>> {
+        self.constrain_recursive(f, care, &mut FxHashMap::default())
+    }
+
+    fn constrain_recursive(
+        &self,
+        f: &Rc<BDD<S>>,
+        care: &Rc<BDD<S>>,
+        cache: &mut ConstrainCache<S>,
+    ) -> Rc<BDD<S>> {
+        match (f.as_ref(), care.as_ref()) {
+            (_, BDD::False) => return self.mk_const(false),
+            (_, BDD::True) | (BDD::False | BDD::True, _) => return Rc::clone(f),
+            _ if f == care => return self.mk_const(true),
+            _ => {}
+        }
+
+        let key = (Rc::as_ptr(f), Rc::as_ptr(care));
+        if let Some(result) = cache.get(&key) {
+            return Rc::clone(result);
+        }
+
+        // split on the smallest root symbol of both operands
+        let v = match (f.as_ref(), care.as_ref()) {
+            (BDD::Choice(_, fv, _, _), BDD::Choice(_, cv, _, _)) => fv.min(cv).clone(),
+            (_, BDD::Choice(_, cv, _, _)) => cv.clone(),
+            _ => unreachable!("the care set is a choice at this point"),
+        };
+
+        let (f_pos, f_neg) = self.cofactors(f, &v);
+        let (care_pos, care_neg) = self.cofactors(care, &v);
+
+        // if one of the branches of the care set is empty, the choice on v is irrelevant
+        let result = if care_neg.is_false() {
+            self.constrain_recursive(&f_pos, &care_pos, cache)
+        } else if care_pos.is_false() {
+            self.constrain_recursive(&f_neg, &care_neg, cache)
+        } else {
+            self.mk_choice(
+                self.constrain_recursive(&f_pos, &care_pos, cache),
+                v,
+                self.constrain_recursive(&f_neg, &care_neg, cache),
+            )
+        };
+
+        cache.insert(key, Rc::clone(&result));
+        result
+    }
+
+    /// Minimize f using the assignments outside of the care set as don't-cares.
+    ///
+    /// The result agrees with f wherever care holds. It is the generalized cofactor of f with respect to care, unless
+    /// the cofactor is larger than f, which can happen in rare cases, in which case f is returned unchanged.
+    pub fn simplify_with_dontcare(&self, f: &Rc<BDD<S>>, care: &Rc<BDD<S>>) -> Rc<BDD<S>> {
+        let constrained = self.constrain(f, care);
+
+        if self.size_reachable(&constrained) <= self.size_reachable(f) {
+            constrained
+        } else {
+            Rc::clone(f)
+        }
+    }
+
+    /// Rename the variables in b according to the given mapping, variables without an image are left unchanged.
+    /// The result is re-canonicalized, such that the ordering of the renamed variables is respected.
+    ///
+    /// Returns an error if the mapping is not a bijection on the variables occurring in b.
+    pub fn permute(&self, b: &Rc<BDD<S>>, mapping: &FxHashMap<S, S>) -> anyhow::Result<Rc<BDD<S>>> {
+        let mut occurring: Vec<S> = Vec::new();
+        self.collect_vars(b, &mut FxHashSet::default(), &mut occurring);
+
+        let mut images: FxHashSet<&S> = FxHashSet::default();
+        for v in &occurring {
+            let image = mapping.get(v).unwrap_or(v);
+            if !images.insert(image) {
+                return Err(anyhow::anyhow!(
+                    "mapping is not a bijection: multiple variables are renamed to {image}"
+                ));
+            }
+        }
+
+        let mut cache: FxHashMap<*const BDD<S>, Rc<BDD<S>>> = FxHashMap::default();
+        Ok(self.permute_recursive(b, mapping, &mut cache))
+    }
+
+    fn permute_recursive(
+        &self,
+        b: &Rc<BDD<S>>,
+        mapping: &FxHashMap<S, S>,
+        cache: &mut FxHashMap<*const BDD<S>, Rc<BDD<S>>>,
+    ) -> Rc<BDD<S>> {
+        if let Some(result) = cache.get(&Rc::as_ptr(b)) {
+            return Rc::clone(result);
+        }
+
+        let result = match b.as_ref() {
+            BDD::False | BDD::True => Rc::clone(b),
+            // the renamed variable can end up anywhere in the new order, therefore rebuild the choice using ite
+            BDD::Choice(t, v, f, _) => self.ite_var(
+                mapping.get(v).unwrap_or(v).clone(),
+                self.permute_recursive(t, mapping, cache),
+                self.permute_recursive(f, mapping, cache),
+            ),
+        };
+
+        cache.insert(Rc::as_ptr(b), Rc::clone(&result));
+
+        result
+    }
+
+    /// The image of a set of states under a transition relation: the states reachable in a single transition.
+    ///
+    /// The transition relation relates the current variables to the next variables at the same position. The result
+    /// is computed as `exists current # (states & trans)`, after which the next variables are renamed to the current
+    /// variables. Returns an error if the lists of variables do not match.
+    pub fn image(
+        &self,
+        states: &Rc<BDD<S>>,
+        trans: &Rc<BDD<S>>,
+        current: &[S],
+        next: &[S],
+    ) -> anyhow::Result<Rc<BDD<S>>> {
+        let successors = self.and_exists_chain(&[Rc::clone(states), Rc::clone(trans)], current);
+
+        self.permute(&successors, &Self::renaming(next, current)?)
+    }
+
+    /// The preimage of a set of states under a transition relation: the states which reach the set in a single
+    /// transition.
+    ///
+    /// The states are renamed from the current to the next variables, after which the result is computed as
+    /// `exists next # (states' & trans)`. Returns an error if the lists of variables do not match.
+    pub fn preimage(
+        &self,
+        states: &Rc<BDD<S>>,
+        trans: &Rc<BDD<S>>,
+        current: &[S],
+        next: &[S],
+    ) -> anyhow::Result<Rc<BDD<S>>> {
+        let next_states = self.permute(states, &Self::renaming(current, next)?)?;
+
+        Ok(self.and_exists_chain(&[next_states, Rc::clone(trans)], next))
+    }
+
+    // the mapping renaming every variable in from to the variable at the same position in to
+    fn renaming(from: &[S], to: &[S]) -> anyhow::Result<FxHashMap<S, S>> {
+        if from.len() != to.len() {
+            return Err(anyhow::anyhow!(
+                "expected as many current as next variables, found {} and {}",
+                from.len(),
+                to.len()
+            ));
+        }
+
+        Ok(from.iter().cloned().zip(to.iter().cloned()).collect())
+    }
+
+    /// Returns the variables b depends on, i.e. the variables occurring on any path of the reduced bdd, in order.
+    /// This can be a subset of the (syntactically) free variables of the formula b originates from.
+    pub fn support(&self, b: &Rc<BDD<S>>) -> Vec<S> {
+        let mut vars: Vec<S> = Vec::new();
+        self.collect_vars(b, &mut FxHashSet::default(), &mut vars);
+        vars.sort();
+
+        vars
+    }
+
+    // collect all variables occurring in b in depth-first order
+    fn collect_vars(
+        &self,
+        b: &Rc<BDD<S>>,
+        visited: &mut FxHashSet<*const BDD<S>>,
+        vars: &mut Vec<S>,
+    ) {
+        if let BDD::Choice(t, v, f, _) = b.as_ref() {
+            if visited.insert(Rc::as_ptr(b)) {
+                if !vars.contains(v) {
+                    vars.push(v.clone());
+                }
+
+                self.collect_vars(t, visited, vars);
+                self.collect_vars(f, visited, vars);
+            }
+        }
+    }
+
     // forall quantification
     pub fn all(&self, s: Vec<S>, b: Rc<BDD<S>>) -> Rc<BDD<S>> {
         self.not(self.exists(s, self.not(b)))
@@ -434,9 +1392,92 @@ impl<S: BDDSymbol> BDDEnv<S> {
         s
     }
 
+    /// Compute the fixed point like `fp`, calling progress with the iteration number (starting at 1)
+    /// and the number of nodes reachable from the current approximation after every application of the transformer,
+    /// see [`BDDEnv::size_reachable`].
+    pub fn fp_with_progress<F, P>(&self, a: Rc<BDD<S>>, t: F, mut progress: P) -> Rc<BDD<S>>
+    where
+        F: Fn(Rc<BDD<S>>) -> Rc<BDD<S>>,
+        P: FnMut(usize, usize),
+    {
+        let mut s = Rc::clone(&a);
+        for iteration in 1.. {
+            let snew = t(Rc::clone(&s));
+            progress(iteration, self.size_reachable(&snew));
+            if snew == s {
+                break;
+            }
+            s = snew;
+        }
+        s
+    }
+
+    /// The complete binary decision tree of root over the given variables, testing every variable in order on every path.
+    ///
+    /// The tree is not reduced: skipped variables are re-introduced with two identical subtrees, and no node is shared,
+    /// such that the tree has 2^n leaves for n variables. Therefore the nodes are not part of the lookup table.
+    /// All variables occurring in root must be part of vars.
+    pub fn to_decision_tree(&self, root: &Rc<BDD<S>>, vars: &[S]) -> Rc<BDD<S>> {
+        let ordered: Vec<&S> = vars.iter().sorted().dedup().collect();
+
+        Self::decision_tree_recursive(root, &ordered)
+    }
+
+    fn decision_tree_recursive(node: &Rc<BDD<S>>, vars: &[&S]) -> Rc<BDD<S>> {
+        let Some((&v, remaining)) = vars.split_first() else {
+            // every leaf is a separate node
+            return match node.as_ref() {
+                BDD::Choice(_, v, _, _) => panic!("{v:?} is not part of the variables"),
+                terminal => Rc::new(terminal.clone()),
+            };
+        };
+
+        let (t, f) = match node.as_ref() {
+            BDD::Choice(t, nv, f, _) if nv == v => (t, f),
+            BDD::Choice(_, nv, _, _) if nv < v => panic!("{nv:?} is not part of the variables"),
+            _ => (node, node),
+        };
+
+        Rc::new(BDD::choice(
+            Self::decision_tree_recursive(t, remaining),
+            v.clone(),
+            Self::decision_tree_recursive(f, remaining),
+        ))
+    }
+
+    /// The conjunction of the literals, where a literal is a variable and its polarity.
+    ///
+    /// The result is built directly as a single path ordered by variable, without intermediate conjunctions.
+    /// Literals may occur in any order and more than once; a variable occurring with both polarities results in false.
+    pub fn cube(&self, literals: &[(S, bool)]) -> Rc<BDD<S>> {
+        let mut ordered = literals.to_vec();
+        ordered.sort();
+        ordered.dedup();
+
+        if ordered
+            .iter()
+            .tuple_windows()
+            .any(|((a, _), (b, _))| a == b)
+        {
+            return self.mk_const(false);
+        }
+
+        // build the path from the bottom, such that every choice only refers to larger variables
+        ordered
+            .into_iter()
+            .rev()
+            .fold(self.mk_const(true), |acc, (v, value)| {
+                if value {
+                    self.mk_choice_unchecked(acc, v, self.mk_const(false))
+                } else {
+                    self.mk_choice_unchecked(self.mk_const(false), v, acc)
+                }
+            })
+    }
+
     pub fn model(&self, a: Rc<BDD<S>>) -> Rc<BDD<S>> {
         match a.as_ref() {
-            BDD::Choice(t, v, f) => {
+            BDD::Choice(t, v, f, _) => {
                 let lhs = self.model(Rc::clone(t));
                 let rhs = self.model(Rc::clone(f));
                 if lhs != self.mk_const(false) {
@@ -451,22 +1492,506 @@ impl<S: BDDSymbol> BDDEnv<S> {
         }
     }
 
+    /// Find an assignment maximizing the total weight of the satisfied clauses (weighted MaxSAT).
+    /// Returns the optimal weight and the assignment as a model, i.e. a single path to true.
+    /// Variables not occurring in the model can take any value.
+    ///
+    /// The search branches on the variables in order, and prunes branches which cannot improve the best weight found so far.
+    /// Total weights exceeding u64::MAX saturate at u64::MAX.
+    pub fn max_weight_model(&self, clauses: &[(Rc<BDD<S>>, u64)]) -> (u64, Rc<BDD<S>>) {
+        let mut best: Option<(u64, Vec<(S, bool)>)> = None;
+        self.max_weight_recursive(clauses.to_vec(), 0, &mut Vec::new(), &mut best);
+
+        let (weight, path) = best.unwrap_or_default();
+
+        (weight, self.cube(&path))
+    }
+
+    /// The lexicographically smallest model of a, preferring false over true for every variable in the order.
+    /// Variables not occurring in the model can take any value, in the smallest assignment they are false.
+    pub fn model_min(&self, a: &Rc<BDD<S>>) -> Rc<BDD<S>> {
+        self.model_preferring(a, false)
+    }
+
+    /// The lexicographically largest model of a, preferring true over false for every variable in the order.
+    /// Variables not occurring in the model can take any value, in the largest assignment they are true.
+    pub fn model_max(&self, a: &Rc<BDD<S>>) -> Rc<BDD<S>> {
+        self.model_preferring(a, true)
+    }
+
+    // every node other than false is satisfiable in a reduced bdd, so the preferred branch can be chosen without search
+    fn model_preferring(&self, a: &Rc<BDD<S>>, preferred: bool) -> Rc<BDD<S>> {
+        if a.is_false() {
+            return self.mk_const(false);
+        }
+
+        let mut path = Vec::new();
+        let mut node = Rc::clone(a);
+
+        while let BDD::Choice(t, v, f, _) = node.as_ref() {
+            let (value, next) = match (preferred, t.is_false(), f.is_false()) {
+                (true, false, _) | (false, _, true) => (true, Rc::clone(t)),
+                _ => (false, Rc::clone(f)),
+            };
+
+            path.push((v.clone(), value));
+            node = next;
+        }
+
+        self.cube(&path)
+    }
+
+    /// A prime implicant of a with few literals, as a list of literals ordered by variable.
+    ///
+    /// The implicant starts from a shortest path to true, i.e. the path skipping the most variables. Then every literal
+    /// which is not needed for the cube to imply a is removed. The result is prime, but not necessarily the smallest
+    /// implicant of a. Returns an empty list for true, and None for false (which has no implicants).
+    pub fn largest_implicant(&self, a: &Rc<BDD<S>>) -> Option<Vec<(S, bool)>> {
+        if a.is_false() {
+            return None;
+        }
+
+        let mut distances: FxHashMap<*const BDD<S>, usize> = FxHashMap::default();
+        let mut cube = Vec::new();
+        let mut node = Rc::clone(a);
+
+        while let BDD::Choice(t, v, f, _) = node.as_ref() {
+            let value =
+                Self::true_distance(t, &mut distances) <= Self::true_distance(f, &mut distances);
+
+            cube.push((v.clone(), value));
+            node = Rc::clone(if value { t } else { f });
+        }
+
+        // remove the literals which are not needed for the cube to imply a
+        let mut i = 0;
+        while i < cube.len() {
+            let mut reduced = cube.clone();
+            reduced.remove(i);
+
+            if self.implies(self.cube(&reduced), Rc::clone(a)).is_true() {
+                cube = reduced;
+            } else {
+                i += 1;
+            }
+        }
+
+        Some(cube)
+    }
+
+    // the number of choices on the shortest path from a to true, or usize::MAX if there is none
+    fn true_distance(a: &Rc<BDD<S>>, cache: &mut FxHashMap<*const BDD<S>, usize>) -> usize {
+        match a.as_ref() {
+            BDD::True => 0,
+            BDD::False => usize::MAX,
+            BDD::Choice(t, _, f, _) => {
+                if let Some(&distance) = cache.get(&Rc::as_ptr(a)) {
+                    return distance;
+                }
+
+                let distance = Self::true_distance(t, cache)
+                    .min(Self::true_distance(f, cache))
+                    .saturating_add(1);
+
+                cache.insert(Rc::as_ptr(a), distance);
+                distance
+            }
+        }
+    }
+
+    fn max_weight_recursive(
+        &self,
+        clauses: Vec<(Rc<BDD<S>>, u64)>,
+        mut weight: u64,
+        path: &mut Vec<(S, bool)>,
+        best: &mut Option<(u64, Vec<(S, bool)>)>,
+    ) {
+        // collect the weight of satisfied clauses, and drop the falsified clauses
+        let mut open: Vec<(Rc<BDD<S>>, u64)> = Vec::with_capacity(clauses.len());
+        for (clause, w) in clauses {
+            match clause.as_ref() {
+                BDD::True => weight = weight.saturating_add(w),
+                BDD::False => {}
+                BDD::Choice(..) => open.push((clause, w)),
+            }
+        }
+
+        // prune if satisfying all open clauses cannot improve the best solution
+        let bound = open
+            .iter()
+            .fold(weight, |bound, (_, w)| bound.saturating_add(*w));
+        if best.as_ref().is_some_and(|(b, _)| bound <= *b) {
+            return;
+        }
+
+        let Some(v) = open
+            .iter()
+            .filter_map(|(c, _)| match c.as_ref() {
+                BDD::Choice(_, v, _, _) => Some(v),
+                _ => None,
+            })
+            .min()
+            .cloned()
+        else {
+            *best = Some((weight, path.clone()));
+            return;
+        };
+
+        for value in [true, false] {
+            let cofactors = open
+                .iter()
+                .map(|(c, w)| match c.as_ref() {
+                    BDD::Choice(t, u, f, _) if *u == v => {
+                        (Rc::clone(if value { t } else { f }), *w)
+                    }
+                    _ => (Rc::clone(c), *w),
+                })
+                .collect();
+
+            path.push((v.clone(), value));
+            self.max_weight_recursive(cofactors, weight, path, best);
+            path.pop();
+        }
+    }
+
+    /// Count the number of satisfying assignments of a over num_vars variables.
+    /// All variables occurring in a must be part of these num_vars variables.
+    ///
+    /// Panics if num_vars is 128 or more, as the number of assignments does not fit in a u128.
+    pub fn satcount(&self, a: &Rc<BDD<S>>, num_vars: usize) -> u128 {
+        let mut cache: FxHashMap<*const BDD<S>, u128> = FxHashMap::default();
+
+        let total = u32::try_from(num_vars)
+            .ok()
+            .and_then(|n| 1u128.checked_shl(n))
+            .unwrap_or_else(|| {
+                panic!("the number of assignments over {num_vars} variables does not fit in a u128")
+            });
+
+        self.satcount_recursive(a, total, &mut cache)
+    }
+
+    /// Count the number of satisfying assignments of a over num_vars variables which agree with the partial assignment.
+    ///
+    /// The variables of the partial assignment are part of the num_vars variables, and must occur at most once.
+    /// The result is the satcount of a restricted by the partial assignment over the remaining variables.
+    pub fn count_models_matching(
+        &self,
+        a: &Rc<BDD<S>>,
+        partial: &[(S, bool)],
+        num_vars: usize,
+    ) -> u128 {
+        let restricted = partial
+            .iter()
+            .fold(Rc::clone(a), |b, (v, value)| self.restrict(b, v, *value));
+
+        self.satcount(&restricted, num_vars.saturating_sub(partial.len()))
+    }
+
+    /// The fraction of the satisfying assignments of root over num_vars variables in which var is true.
+    ///
+    /// The result is computed from the satcounts of root and its positive cofactor, an unsatisfiable root results in 0.
+    pub fn marginal_prob(&self, root: &Rc<BDD<S>>, var: &S, num_vars: usize) -> f64 {
+        let total = self.satcount(root, num_vars);
+
+        if total == 0 {
+            return 0.0;
+        }
+
+        let positive = self.count_models_matching(root, &[(var.clone(), true)], num_vars);
+
+        positive as f64 / total as f64
+    }
+
+    /// For every variable, the number of satisfying assignments of root over vars in which it is true and false.
+    ///
+    /// All variables occurring in root must be part of vars. Rather than computing the satcounts of the cofactors for
+    /// every variable separately, the counts of all variables are aggregated in a single pass over the nodes.
+    ///
+    /// Panics if vars contains 128 or more distinct variables, as the number of assignments does not fit in a u128.
+    pub fn marginal_counts(&self, root: &Rc<BDD<S>>, vars: &[S]) -> Vec<(u128, u128)> {
+        // the levels of the variables in the order of the bdd, the terminals are at the bottom level
+        let ordered: Vec<&S> = vars.iter().sorted().dedup().collect();
+        let num_levels = ordered.len();
+        assert!(
+            num_levels < u128::BITS as usize,
+            "the number of assignments over {num_levels} variables does not fit in a u128"
+        );
+        let position = |v: &S| {
+            ordered
+                .binary_search(&v)
+                .unwrap_or_else(|_| panic!("{v:?} is not part of the variables"))
+        };
+        let level = |node: &BDD<S>| match node {
+            BDD::Choice(_, v, _, _) => position(v),
+            _ => num_levels,
+        };
+
+        // all nodes ordered by level, such that parents precede their children
+        let mut nodes: Vec<Rc<BDD<S>>> = Vec::new();
+        let mut visited: FxHashSet<*const BDD<S>> = FxHashSet::default();
+        let mut stack = vec![Rc::clone(root)];
+        while let Some(node) = stack.pop() {
+            if visited.insert(Rc::as_ptr(&node)) {
+                if let BDD::Choice(t, _, f, _) = node.as_ref() {
+                    stack.push(Rc::clone(t));
+                    stack.push(Rc::clone(f));
+                }
+                nodes.push(node);
+            }
+        }
+        nodes.sort_by_key(|node| level(node));
+
+        // the number of assignments to the skipped levels of an edge from level `from` to level `to`
+        let skipped = |from: usize, to: usize| 1u128 << (to - from - 1);
+
+        // the number of satisfying assignments to the levels below every node
+        let mut below: FxHashMap<*const BDD<S>, u128> = FxHashMap::default();
+        for node in nodes.iter().rev() {
+            let count = match node.as_ref() {
+                BDD::False => 0,
+                BDD::True => 1,
+                BDD::Choice(t, _, f, _) => {
+                    let l = level(node);
+                    below[&Rc::as_ptr(t)] * skipped(l, level(t))
+                        + below[&Rc::as_ptr(f)] * skipped(l, level(f))
+                }
+            };
+            below.insert(Rc::as_ptr(node), count);
+        }
+
+        // the number of assignments to the levels above every node which lead to it
+        let mut above: FxHashMap<*const BDD<S>, u128> = FxHashMap::default();
+        above.insert(Rc::as_ptr(root), 1 << level(root));
+
+        let total = below[&Rc::as_ptr(root)] << level(root);
+
+        // per level, the number of models passing through a node of that level, and the models taking its true edge
+        let mut through = vec![0; num_levels];
+        let mut positive = vec![0; num_levels];
+
+        for node in &nodes {
+            if let BDD::Choice(t, _, f, _) = node.as_ref() {
+                let l = level(node);
+                let reach = above[&Rc::as_ptr(node)];
+
+                through[l] += reach * below[&Rc::as_ptr(node)];
+                positive[l] += reach * below[&Rc::as_ptr(t)] * skipped(l, level(t));
+
+                for child in [t, f] {
+                    *above.entry(Rc::as_ptr(child)).or_default() +=
+                        reach * skipped(l, level(child));
+                }
+            }
+        }
+
+        // models which skip a level take either value for its variable
+        vars.iter()
+            .map(|v| {
+                let l = position(v);
+                let t = positive[l] + (total - through[l]) / 2;
+                (t, total - t)
+            })
+            .collect()
+    }
+
+    /// Determine whether a has no, exactly one, or multiple satisfying assignments over num_vars variables.
+    ///
+    /// Rather than counting all assignments, the count stops at 2, see [`BDDEnv::count_up_to`].
+    pub fn uniqueness(&self, a: &Rc<BDD<S>>, num_vars: usize) -> Uniqueness {
+        match self.count_up_to(a, num_vars, 2) {
+            0 => Uniqueness::None,
+            1 => Uniqueness::Unique,
+            _ => Uniqueness::Multiple,
+        }
+    }
+
+    /// Count the number of satisfying assignments of a over num_vars variables, up to the given limit.
+    ///
+    /// The result is the minimum of the satcount and the limit. Every node is counted once, with its count saturating
+    /// at the limit, such that the number of assignments does not need to fit in a u128.
+    pub fn count_up_to(&self, a: &Rc<BDD<S>>, num_vars: usize, limit: u128) -> u128 {
+        let mut cache: FxHashMap<*const BDD<S>, (usize, u128)> = FxHashMap::default();
+
+        let (height, count) = Self::count_up_to_recursive(a, limit, &mut cache);
+
+        // every variable not tested on the longest path doubles the number of assignments
+        Self::saturating_shl(count, num_vars.saturating_sub(height), limit)
+    }
+
+    // the length of the longest path from a to a terminal, and the number of satisfying assignments of a over that many
+    // variables, saturated at the limit
+    fn count_up_to_recursive(
+        a: &Rc<BDD<S>>,
+        limit: u128,
+        cache: &mut FxHashMap<*const BDD<S>, (usize, u128)>,
+    ) -> (usize, u128) {
+        match a.as_ref() {
+            BDD::False => (0, 0),
+            BDD::True => (0, limit.min(1)),
+            BDD::Choice(t, _, f, _) => {
+                if let Some(&result) = cache.get(&Rc::as_ptr(a)) {
+                    return result;
+                }
+
+                let (t_height, t_count) = Self::count_up_to_recursive(t, limit, cache);
+                let (f_height, f_count) = Self::count_up_to_recursive(f, limit, cache);
+                let height = t_height.max(f_height) + 1;
+
+                let count = Self::saturating_shl(t_count, height - 1 - t_height, limit)
+                    .saturating_add(Self::saturating_shl(f_count, height - 1 - f_height, limit))
+                    .min(limit);
+
+                cache.insert(Rc::as_ptr(a), (height, count));
+                (height, count)
+            }
+        }
+    }
+
+    // count * 2^shift, saturated at the limit
+    fn saturating_shl(count: u128, shift: usize, limit: u128) -> u128 {
+        if count == 0 {
+            return 0;
+        }
+
+        u32::try_from(shift)
+            .ok()
+            .filter(|&shift| shift <= count.leading_zeros())
+            .map_or(limit, |shift| (count << shift).min(limit))
+    }
+
+    /// Count the number of paths from a to true, i.e. the number of cubes in the disjoint sum of products read from a.
+    ///
+    /// Unlike [`BDDEnv::satcount`], a path skipping variables is counted once instead of once per assignment of the
+    /// skipped variables, so the path count is at most the model count.
+    pub fn path_count(&self, a: &Rc<BDD<S>>) -> u128 {
+        let mut cache: FxHashMap<*const BDD<S>, u128> = FxHashMap::default();
+
+        Self::path_count_recursive(a, &mut cache)
+    }
+
+    fn path_count_recursive(a: &Rc<BDD<S>>, cache: &mut FxHashMap<*const BDD<S>, u128>) -> u128 {
+        match a.as_ref() {
+            BDD::False => 0,
+            BDD::True => 1,
+            BDD::Choice(t, _, f, _) => {
+                if let Some(&count) = cache.get(&Rc::as_ptr(a)) {
+                    return count;
+                }
+
+                let count =
+                    Self::path_count_recursive(t, cache) + Self::path_count_recursive(f, cache);
+
+                cache.insert(Rc::as_ptr(a), count);
+                count
+            }
+        }
+    }
+
+    // every choice halves the number of assignments for each branch, so the count can be computed without knowing the
+    // position of the skipped variables in the ordering
+    fn satcount_recursive(
+        &self,
+        a: &Rc<BDD<S>>,
+        total: u128,
+        cache: &mut FxHashMap<*const BDD<S>, u128>,
+    ) -> u128 {
+        match a.as_ref() {
+            BDD::False => 0,
+            BDD::True => total,
+            BDD::Choice(t, _, f, _) => {
+                if let Some(&count) = cache.get(&Rc::as_ptr(a)) {
+                    return count;
+                }
+
+                let count = (self.satcount_recursive(t, total, cache)
+                    + self.satcount_recursive(f, total, cache))
+                    / 2;
+
+                cache.insert(Rc::as_ptr(a), count);
+                count
+            }
+        }
+    }
+
     // determine whether variable b is always true or false for a given bdd a
     // returns a tuple (bool, bool) where the first item determines whether b is bound
     // the second item determines the truth value for b
     pub fn infer(&self, a: Rc<BDD<S>>, b: S) -> (bool, bool) {
         let ff = self.implies(a, self.var(b));
         match ff.as_ref() {
-            BDD::Choice(_, _, _) => (false, false),
+            BDD::Choice(_, _, _, _) => (false, false),
             BDD::True => (true, true),
             BDD::False => (true, false),
         }
     }
 
+    /// Find all variables that take a fixed value in every model of b, in a single traversal of b.
+    /// Equivalent to calling [`BDDEnv::infer`] for every variable, returns an empty list if b is unsatisfiable.
+    pub fn forced_variables(&self, b: &Rc<BDD<S>>) -> Vec<(S, bool)> {
+        let mut cache: ForcedVariablesCache<S> = FxHashMap::default();
+        self.forced_variables_recursive(b, &mut cache)
+            .unwrap_or_default()
+    }
+
+    // the variables (sorted by symbol) forced in every model of b, or None if b is unsatisfiable
+    fn forced_variables_recursive(
+        &self,
+        b: &Rc<BDD<S>>,
+        cache: &mut ForcedVariablesCache<S>,
+    ) -> Option<Vec<(S, bool)>> {
+        if let Some(result) = cache.get(&Rc::as_ptr(b)) {
+            return result.clone();
+        }
+
+        let result = match b.as_ref() {
+            BDD::False => None,
+            BDD::True => Some(Vec::new()),
+            BDD::Choice(t, v, f, _) => {
+                match (
+                    self.forced_variables_recursive(t, cache),
+                    self.forced_variables_recursive(f, cache),
+                ) {
+                    (None, None) => None,
+                    // the variable is forced if only one of the branches is satisfiable
+                    (Some(forced), None) => Some(Self::prepend(v, true, forced)),
+                    (None, Some(forced)) => Some(Self::prepend(v, false, forced)),
+                    // otherwise only the variables forced to the same value in both branches remain
+                    (Some(t_forced), Some(f_forced)) => Some(
+                        t_forced
+                            .into_iter()
+                            .filter(|literal| f_forced.contains(literal))
+                            .collect(),
+                    ),
+                }
+            }
+        };
+
+        cache.insert(Rc::as_ptr(b), result.clone());
+
+        result
+    }
+
+    // variables further down the bdd are larger, so prepending keeps the list sorted
+    fn prepend(v: &S, value: bool, forced: Vec<(S, bool)>) -> Vec<(S, bool)> {
+        std::iter::once((v.clone(), value)).chain(forced).collect()
+    }
+
+    /// Determine whether `assumptions & a => b` is a tautology, i.e. b holds in every model of a satisfying the assumptions.
+    /// Unsatisfiable assumptions entail everything.
+    pub fn entails_under(&self, assumptions: Rc<BDD<S>>, a: Rc<BDD<S>>, b: Rc<BDD<S>>) -> bool {
+        self.implies(self.and(assumptions, a), b).is_true()
+    }
+
+    /// Determine whether a and b are equivalent in every model satisfying the assumptions.
+    pub fn equivalent_under(&self, assumptions: Rc<BDD<S>>, a: Rc<BDD<S>>, b: Rc<BDD<S>>) -> bool {
+        self.implies(assumptions, self.eq(a, b)).is_true()
+    }
+
     // simplify removes a choice node if both subtrees are equivalent
     pub fn simplify(&self, a: &Rc<BDD<S>>) -> Rc<BDD<S>> {
         match a.as_ref() {
-            BDD::Choice(t, _, f) if t.as_ref() == f.as_ref() => Rc::clone(t),
+            BDD::Choice(t, _, f, _) if t.as_ref() == f.as_ref() => Rc::clone(t),
             _ => Rc::clone(a),
         }
     }
@@ -478,7 +2003,7 @@ impl<S: BDDSymbol> BDDEnv<S> {
             // otherwise, remove nodes depending on truth value of the filter
             _ => {
                 match src.as_ref() {
-                    BDD::Choice(left, symbol, right) => {
+                    BDD::Choice(left, symbol, right, _) => {
                         // recursively run the retain function
                         let left = self.retain_choice_bottom_up(Rc::clone(left), filter);
                         let right = self.retain_choice_bottom_up(Rc::clone(right), filter);
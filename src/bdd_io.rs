@@ -1,12 +1,15 @@
 extern crate dot;
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::io;
 use std::io::Write;
 use std::rc::Rc;
 
 use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHashSet};
 
+use crate::escape::{dot_label, xml_escape};
 use crate::{bdd::*, BDDSymbol, TruthTableEntry};
 
 type GraphEdge<S> = (Rc<BDD<S>>, bool, Rc<BDD<S>>);
@@ -15,19 +18,144 @@ type GraphNode<S> = Rc<BDD<S>>;
 pub struct BDDGraph<S: BDDSymbol> {
     root: Rc<BDD<S>>,
     filter: TruthTableEntry,
+    compact: bool,
+    ranked: bool,
+    tree: bool,
 }
 
 impl<S: BDDSymbol> BDDGraph<S> {
     pub fn render_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        dot::render(self, writer)
+        if !self.ranked {
+            return dot::render(self, writer);
+        }
+
+        let mut buffer = Vec::new();
+        dot::render(self, &mut buffer)?;
+        let rendered =
+            String::from_utf8(buffer).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        // insert the rank constraints before the closing brace of the graph
+        let (body, tail) = rendered
+            .rsplit_once('}')
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unterminated dot graph"))?;
+
+        write!(writer, "{body}")?;
+
+        for ids in self.ranks().values() {
+            writeln!(writer, "    {{ rank=same; {}; }}", ids.join("; "))?;
+        }
+
+        let terminals: Vec<String> = self
+            .nodes_recursive(self.root.clone())
+            .iter()
+            .filter(|n| !matches!(n.as_ref(), BDD::Choice(..)))
+            .map(|n| dot::Labeller::node_id(self, n).name().to_string())
+            .collect();
+
+        if !terminals.is_empty() {
+            writeln!(writer, "    {{ rank=sink; {}; }}", terminals.join("; "))?;
+        }
+
+        write!(writer, "}}{tail}")
+    }
+
+    // the node ids of every variable, ordered by variable
+    fn ranks(&self) -> BTreeMap<S, Vec<String>> {
+        let mut ranks: BTreeMap<S, Vec<String>> = BTreeMap::new();
+
+        for n in self.nodes_recursive(self.root.clone()).iter() {
+            if let BDD::Choice(_, v, _, _) = n.as_ref() {
+                let id = dot::Labeller::node_id(self, n).name().to_string();
+                let ids = ranks.entry(v.clone()).or_default();
+
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        ranks
+    }
+
+    /// Render the graph in GraphML format, e.g. for yEd or Gephi.
+    /// Nodes carry a label with their variable (or true/false), edges carry a T/F value.
+    /// The node ids and the filter are the same as in the dot output.
+    pub fn render_graphml<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="value" for="edge" attr.name="value" attr.type="string"/>"#
+        )?;
+        writeln!(writer, r#"  <graph id="bdd_graph" edgedefault="directed">"#)?;
+
+        for n in self.nodes_recursive(self.root.clone()).iter() {
+            let label = match n.as_ref() {
+                BDD::True => "true".to_string(),
+                BDD::False => "false".to_string(),
+                BDD::Choice(_, v, _, _) => xml_escape(&v.to_string()),
+            };
+
+            writeln!(
+                writer,
+                r#"    <node id="{}"><data key="label">{}</data></node>"#,
+                dot::Labeller::node_id(self, n).name(),
+                label
+            )?;
+        }
+
+        for (a, e, b) in self.edges_recursive(self.root.clone()).iter() {
+            writeln!(
+                writer,
+                r#"    <edge source="{}" target="{}"><data key="value">{}</data></edge>"#,
+                dot::Labeller::node_id(self, a).name(),
+                dot::Labeller::node_id(self, b).name(),
+                if *e { "T" } else { "F" }
+            )?;
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")
     }
 
     pub fn new(root: &Rc<BDD<S>>, filter: TruthTableEntry) -> Self {
         Self {
             root: root.clone(),
             filter,
+            compact: false,
+            ranked: false,
+            tree: false,
         }
     }
+
+    /// Identify nodes by their structural hash instead of their pointer,
+    /// such that structurally identical subtrees are rendered as a single node.
+    /// The pointer-based rendering remains useful to find duplicate nodes.
+    pub const fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Place all nodes of the same variable on the same level in the dot output, ordered by variable from top to bottom.
+    /// The terminals are placed on the bottom level, resembling the layered diagrams of reduced ordered bdds.
+    pub const fn with_ranked(mut self, ranked: bool) -> Self {
+        self.ranked = ranked;
+        self
+    }
+
+    /// Render every node separately, including the terminals, such that an unreduced decision tree
+    /// (see [`crate::bdd::BDDEnv::to_decision_tree`]) is rendered as a tree.
+    pub const fn with_tree(mut self, tree: bool) -> Self {
+        self.tree = tree;
+        self
+    }
 }
 
 impl<'a, S: BDDSymbol> dot::Labeller<'a, GraphNode<S>, GraphEdge<S>> for BDDGraph<S> {
@@ -37,6 +165,12 @@ impl<'a, S: BDDSymbol> dot::Labeller<'a, GraphNode<S>, GraphEdge<S>> for BDDGrap
 
     fn node_id(&self, n: &GraphNode<S>) -> dot::Id<'a> {
         match n.as_ref() {
+            // every leaf of a tree is a separate node
+            BDD::True | BDD::False if self.tree => {
+                let name = if n.is_true() { "true" } else { "false" };
+                dot::Id::new(format!("n_{name}_{:p}", Rc::as_ptr(n)))
+                    .unwrap_or_else(|_| panic!("cannot create Id named 'n_{name}_{n:p}'"))
+            }
             // use grep -v n_true or grep -v n_false to filter nodes adjacent to true or false
             BDD::True => {
                 dot::Id::new("n_true".to_string()).expect("cannot create Id named 'n_true'")
@@ -44,9 +178,12 @@ impl<'a, S: BDDSymbol> dot::Labeller<'a, GraphNode<S>, GraphEdge<S>> for BDDGrap
             BDD::False => {
                 dot::Id::new("n_false".to_string()).expect("cannot create Id named 'n_false'")
             }
+            // use the hash for optimal sharing
+            _ if self.compact => dot::Id::new(format!("n_{:x}", n.get_hash()))
+                .unwrap_or_else(|_| panic!("cannot create Id named 'n_{:x}'", n.get_hash())),
+            // use pointers to test issue with duplicates
             _ => dot::Id::new(format!("n_{:p}", Rc::into_raw(n.clone())))
                 .unwrap_or_else(|_| panic!("cannot create Id named 'n_{n:p}'")),
-            // _ => dot::Id::new(format!("n_{}", n.get_hash())).unwrap(), // use the hash for optimal sharing, use (above) pointers to test issue with duplicates
         }
     }
 
@@ -54,7 +191,7 @@ impl<'a, S: BDDSymbol> dot::Labeller<'a, GraphNode<S>, GraphEdge<S>> for BDDGrap
         match n.as_ref() {
             BDD::True => dot::LabelText::label("true"),
             BDD::False => dot::LabelText::label("false"),
-            BDD::Choice(_, v, _) => dot::LabelText::label(format!("{}", v)),
+            BDD::Choice(_, v, _, _) => dot_label(&v.to_string()),
         }
     }
 
@@ -88,17 +225,22 @@ impl<'a, S: BDDSymbol> dot::GraphWalk<'a, GraphNode<S>, GraphEdge<S>> for BDDGra
 impl<'a, S: BDDSymbol> BDDGraph<S> {
     fn nodes_recursive(&self, root: Rc<BDD<S>>) -> dot::Nodes<'a, GraphNode<S>> {
         match root.as_ref() {
-            BDD::Choice(l, _, r) => {
+            BDD::Choice(l, _, r, _) => {
                 let l_nodes = self.nodes_recursive(l.clone());
                 let r_nodes = self.nodes_recursive(r.clone());
 
-                l_nodes
+                let nodes = l_nodes
                     .iter()
-                    .chain(&vec![root.clone()])
-                    .chain(r_nodes.iter())
-                    .unique()
                     .cloned()
-                    .collect()
+                    .chain(std::iter::once(root.clone()))
+                    .chain(r_nodes.iter().cloned());
+
+                // the nodes of a tree are never shared
+                if self.tree {
+                    nodes.collect()
+                } else {
+                    nodes.unique().collect()
+                }
             }
             c if (self.filter == TruthTableEntry::Any)
                 || (self.filter == TruthTableEntry::True && *c == BDD::True)
@@ -112,7 +254,7 @@ impl<'a, S: BDDSymbol> BDDGraph<S> {
 
     fn edges_recursive(&self, root: Rc<BDD<S>>) -> dot::Edges<'a, GraphEdge<S>> {
         match root.as_ref() {
-            BDD::Choice(l, _, r) => {
+            BDD::Choice(l, _, r, _) => {
                 let l_edges = self.edges_recursive(l.clone());
                 let r_edges = self.edges_recursive(r.clone());
 
@@ -134,15 +276,116 @@ impl<'a, S: BDDSymbol> BDDGraph<S> {
                     self_edges.push((root.clone(), false, r.clone()));
                 }
 
-                l_edges
+                let edges = l_edges
                     .iter()
                     .chain(r_edges.iter())
                     .chain(self_edges.iter())
-                    .unique() // disable unique edges when testing for duplicates
-                    .cloned()
-                    .collect()
+                    .cloned();
+
+                // disable unique edges when testing for duplicates, the edges of a tree are never shared
+                if self.tree {
+                    edges.collect()
+                } else {
+                    edges.unique().collect()
+                }
             }
             _ => vec![].into(),
         }
     }
 }
+
+/// Write the bdd as a standalone Rust function `fn <fn_name>(vars: &HashMap<&str, bool>) -> bool`.
+///
+/// The function body mirrors the bdd as nested `if` expressions. Subtrees shared by multiple parents are hoisted into
+/// closures, such that their code is written once and they are only evaluated on the paths reaching them.
+/// The function panics if a variable the result depends on is missing from the map.
+pub fn to_rust_fn<S: BDDSymbol, W: Write>(
+    root: &Rc<BDD<S>>,
+    fn_name: &str,
+    w: &mut W,
+) -> io::Result<()> {
+    // count the number of parents of every node, visiting every unique node once
+    let mut parents: FxHashMap<*const BDD<S>, usize> = FxHashMap::default();
+    let mut visited: FxHashSet<*const BDD<S>> = FxHashSet::default();
+    let mut order: Vec<Rc<BDD<S>>> = Vec::new();
+    count_parents(root, &mut parents, &mut visited, &mut order);
+
+    // the shared choices in post-order, such that every binding only refers to earlier bindings
+    let shared: Vec<&Rc<BDD<S>>> = order
+        .iter()
+        .filter(|n| {
+            matches!(n.as_ref(), BDD::Choice(..))
+                && parents.get(&Rc::as_ptr(n)).copied().unwrap_or(0) > 1
+        })
+        .collect();
+    let names: FxHashMap<*const BDD<S>, usize> = shared
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (Rc::as_ptr(n), i))
+        .collect();
+
+    writeln!(
+        w,
+        "fn {fn_name}(vars: &std::collections::HashMap<&str, bool>) -> bool {{"
+    )?;
+
+    for (i, n) in shared.iter().enumerate() {
+        write!(w, "    let n_{i} = || ")?;
+        write_rust_expr(n, true, &names, 1, w)?;
+        writeln!(w, ";")?;
+    }
+
+    write!(w, "    ")?;
+    write_rust_expr(root, true, &names, 1, w)?;
+    writeln!(w)?;
+    writeln!(w, "}}")
+}
+
+// collect the unique nodes in post-order and count the number of distinct parents of every node
+fn count_parents<S: BDDSymbol>(
+    node: &Rc<BDD<S>>,
+    parents: &mut FxHashMap<*const BDD<S>, usize>,
+    visited: &mut FxHashSet<*const BDD<S>>,
+    order: &mut Vec<Rc<BDD<S>>>,
+) {
+    if !visited.insert(Rc::as_ptr(node)) {
+        return;
+    }
+
+    if let BDD::Choice(t, _, f, _) = node.as_ref() {
+        for child in [t, f] {
+            *parents.entry(Rc::as_ptr(child)).or_default() += 1;
+            count_parents(child, parents, visited, order);
+        }
+    }
+
+    order.push(Rc::clone(node));
+}
+
+// write the expression of a node, calling the closure of a shared node unless it is the node being defined
+fn write_rust_expr<S: BDDSymbol, W: Write>(
+    node: &Rc<BDD<S>>,
+    defining: bool,
+    names: &FxHashMap<*const BDD<S>, usize>,
+    depth: usize,
+    w: &mut W,
+) -> io::Result<()> {
+    match (node.as_ref(), names.get(&Rc::as_ptr(node))) {
+        (BDD::True, _) => write!(w, "true"),
+        (BDD::False, _) => write!(w, "false"),
+        (BDD::Choice(..), Some(name)) if !defining => write!(w, "n_{name}()"),
+        (BDD::Choice(t, v, f, _), _) => {
+            let indent = "    ".repeat(depth);
+
+            writeln!(w, "if vars[{:?}] {{", v.to_string())?;
+            write!(w, "{indent}    ")?;
+            write_rust_expr(t, false, names, depth + 1, w)?;
+            writeln!(w)?;
+            writeln!(w, "{indent}}} else {{")?;
+            write!(w, "{indent}    ")?;
+            write_rust_expr(f, false, names, depth + 1, w)?;
+            writeln!(w)?;
+            write!(w, "{indent}}}")
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use clap::Parser;
+use rustc_hash::FxHashMap;
+
+use rsbdd::bdd::BDDEnv;
+use rsbdd::set::BDDSet;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about = "Evaluate set operations on BDD-encoded sets", long_about = None)]
+struct Args {
+    #[clap(value_parser, value_name = "FILE")]
+    /// The input file containing ';'-separated set commands (or stdin if not provided).
+    input: Option<PathBuf>,
+
+    #[clap(short, long, value_parser = clap::value_parser!(u8).range(1..=64), default_value_t = 8)]
+    /// The number of bits used to encode a single element.
+    bits: u8,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let mut reader = if let Some(input_filename) = args.input {
+        let file = File::open(input_filename)?;
+        Box::new(BufReader::new(file)) as Box<dyn BufRead>
+    } else {
+        Box::new(BufReader::new(io::stdin())) as Box<dyn BufRead>
+    };
+
+    let mut src = String::new();
+    reader.read_to_string(&mut src)?;
+
+    let bits = usize::from(args.bits);
+    let env = Rc::new(BDDEnv::new());
+
+    // the set all commands operate on, and the sets stored by name
+    let current = BDDSet::with_env(bits, &env);
+    let mut stored: FxHashMap<String, BDDSet> = FxHashMap::default();
+
+    for statement in src.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        let mut tokens = statement.split_whitespace();
+        let command = tokens.next().unwrap_or_default();
+        let arguments: Vec<&str> = tokens.collect();
+
+        match command {
+            "insert" => {
+                expect_arguments(command, &arguments)?;
+                for literal in &arguments {
+                    current.insert(parse_element(literal, bits)?);
+                }
+            }
+            "contains" => {
+                expect_arguments(command, &arguments)?;
+                for literal in &arguments {
+                    println!(
+                        "{literal}: {}",
+                        current.contains(parse_element(literal, bits)?)
+                    );
+                }
+            }
+            "card" => println!("{}", current.cardinality()),
+            "clear" => {
+                current.empty();
+            }
+            "store" => {
+                let name = single_argument(command, &arguments)?;
+                stored.insert(name.to_string(), current.clone());
+            }
+            "union" => {
+                let name = single_argument(command, &arguments)?;
+                current.union(find_set(&stored, name)?);
+            }
+            "intersect" => {
+                let name = single_argument(command, &arguments)?;
+                current.intersect(find_set(&stored, name)?);
+            }
+            other => return Err(anyhow::anyhow!("unknown command '{other}'")),
+        }
+    }
+
+    Ok(())
+}
+
+// parse a decimal, hexadecimal (0x) or binary (0b) literal and check whether it fits in the given number of bits
+fn parse_element(literal: &str, bits: usize) -> anyhow::Result<usize> {
+    let (digits, radix) = literal
+        .strip_prefix("0x")
+        .map(|hex| (hex, 16))
+        .or_else(|| literal.strip_prefix("0b").map(|bin| (bin, 2)))
+        .unwrap_or((literal, 10));
+
+    let value = usize::from_str_radix(digits, radix)
+        .map_err(|e| anyhow::anyhow!("cannot parse literal '{literal}': {e}"))?;
+
+    if bits < usize::BITS as usize && value >> bits != 0 {
+        return Err(anyhow::anyhow!(
+            "literal '{literal}' does not fit in {bits} bits"
+        ));
+    }
+
+    Ok(value)
+}
+
+fn expect_arguments(command: &str, arguments: &[&str]) -> anyhow::Result<()> {
+    if arguments.is_empty() {
+        Err(anyhow::anyhow!("missing argument for '{command}'"))
+    } else {
+        Ok(())
+    }
+}
+
+fn single_argument<'a>(command: &str, arguments: &[&'a str]) -> anyhow::Result<&'a str> {
+    match arguments {
+        [argument] => Ok(argument),
+        _ => Err(anyhow::anyhow!(
+            "'{command}' expects a single argument, got '{}'",
+            arguments.join(" ")
+        )),
+    }
+}
+
+fn find_set<'a>(stored: &'a FxHashMap<String, BDDSet>, name: &str) -> anyhow::Result<&'a BDDSet> {
+    stored
+        .get(name)
+        .ok_or_else(|| anyhow::anyhow!("unknown set '{name}'"))
+}
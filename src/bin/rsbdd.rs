@@ -2,21 +2,27 @@ use std::cmp::max;
 use std::fmt::Display;
 use std::fs::File;
 use std::io;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::ops::Index;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver};
 use std::time::{Duration, Instant};
 
 use clap::Parser;
+use notify::{Event, RecursiveMode, Watcher};
+use rustc_hash::FxHashMap;
 
 use rsbdd::bdd::*;
 use rsbdd::bdd_io::*;
+use rsbdd::dimacs::read_dimacs;
+use rsbdd::escape::json_string;
+use rsbdd::ordering::*;
 use rsbdd::parser::*;
 use rsbdd::parser_io::*;
 use rsbdd::plot::*;
-use rsbdd::BDDSymbol;
 use rsbdd::NamedSymbol;
 use rsbdd::TruthTableEntry;
 
@@ -27,18 +33,70 @@ struct Args {
     /// The input file containing a logic formula in rsbdd format.
     input: Option<PathBuf>,
 
+    #[clap(long, value_parser, value_name = "FORMAT", default_value_t = InputFormat::Auto)]
+    /// The format of the input: auto, rsbdd or dimacs. Auto detects DIMACS CNF input by its 'p cnf' header.
+    input_format: InputFormat,
+
     #[clap(short, long, value_parser)]
     /// Write the parse tree in dot format to the specified file.
     parsetree: Option<PathBuf>,
 
+    #[clap(long, requires = "parsetree")]
+    /// Render chains of &, | and ^ as a single node with a child per operand in the parse tree.
+    parsetree_flat: bool,
+
+    #[clap(long, value_parser, value_name = "FILE")]
+    /// Write the parse tree in json format to the specified file.
+    parsetree_json: Option<PathBuf>,
+
     #[clap(short, long)]
     /// Print the truth table to stdout.
     truthtable: bool,
 
+    #[clap(long)]
+    /// Print the truth table to stdout in csv format, using T, F and * for true, false and any.
+    truthtable_csv: bool,
+
+    #[clap(long)]
+    /// Enumerate both values of variables that can take any value in the truth table, such that every row is a complete assignment.
+    expand: bool,
+
     #[clap(short, long, value_parser)]
     /// Write the bdd to a dot graphviz file.
     dot: Option<PathBuf>,
 
+    #[clap(long, requires = "dot")]
+    /// Merge structurally identical subtrees into a single node in the dot output.
+    dot_compact: bool,
+
+    #[clap(long, requires = "dot")]
+    /// Place all nodes of the same variable on the same level in the dot output.
+    dot_ranked: bool,
+
+    #[clap(long, requires = "dot", conflicts_with = "dot_compact")]
+    /// Write the complete (unreduced) decision tree over the free variables to the dot file, which has 2^n leaves.
+    decision_tree: bool,
+
+    #[clap(long, value_parser, value_name = "DIR")]
+    /// Write the output in the format given by --format to a file per input or statement in the directory, named after the input file.
+    out_dir: Option<PathBuf>,
+
+    #[clap(long, value_parser, value_name = "FORMAT", default_value_t = OutputFormat::Dot, requires = "out_dir")]
+    /// The format of the files written to --out-dir: dot for the bdd, or json for the parse tree.
+    format: OutputFormat,
+
+    #[clap(long, value_parser, value_name = "FILE")]
+    /// Write the bdd to a GraphML file.
+    graphml: Option<PathBuf>,
+
+    #[clap(long, value_parser, value_name = "FILE")]
+    /// Write the bdd as a single-output BLIF model to the specified file.
+    blif: Option<PathBuf>,
+
+    #[clap(long, value_parser, value_name = "FILE")]
+    /// Write the bdd as a standalone Rust function `fn decide(vars: &HashMap<&str, bool>) -> bool` to the specified file.
+    emit_rust: Option<PathBuf>,
+
     #[clap(short, long)]
     /// Compute a single satisfying model as output.
     model: bool,
@@ -47,6 +105,50 @@ struct Args {
     /// Print all satisfying variables leading to a truth value.
     vars: bool,
 
+    #[clap(long)]
+    /// Print the variables the result depends on to stdout.
+    support: bool,
+
+    #[clap(long)]
+    /// Print the number of satisfying assignments of the free variables.
+    count: bool,
+
+    #[clap(long)]
+    /// Print the number of paths to true in the bdd; unlike the model count, a path skipping variables is counted once.
+    count_paths: bool,
+
+    #[clap(long)]
+    /// Print a table with the number of models in which every free variable is true and false.
+    marginals: bool,
+
+    #[clap(long)]
+    /// Print a table with the number of nodes at every level of the bdd, showing where the bdd is widest.
+    level_stats: bool,
+
+    #[clap(long)]
+    /// Print a json object with the satisfiability, model count, node count, free variables and evaluation time to stdout, instead of the other output.
+    summary_json: bool,
+
+    #[clap(long, conflicts_with_all = ["is_contradiction", "summary_json"])]
+    /// Exit with 0 if the result is a tautology, i.e. true for every assignment, with 1 otherwise, and with 2 on errors.
+    is_tautology: bool,
+
+    #[clap(long, conflicts_with = "summary_json")]
+    /// Exit with 0 if the result is a contradiction, i.e. unsatisfiable, with 1 otherwise, and with 2 on errors.
+    is_contradiction: bool,
+
+    #[clap(long)]
+    /// Print the outcome of --is-tautology and --is-contradiction, which are silent otherwise.
+    verbose: bool,
+
+    #[clap(long)]
+    /// Report whether the result has no (none), exactly one (unique), or more (multiple) satisfying assignments.
+    check_unique_sat: bool,
+
+    #[clap(long, value_parser, value_name = "N", default_value_t = 20)]
+    /// Refuse to print the truth table or variables when the formula has more than N free variables (0 disables the check).
+    limit_vars: usize,
+
     #[clap(short, long, value_parser, default_value_t = TruthTableEntry::Any)]
     /// Only show true or false entries in the output.
     filter: TruthTableEntry,
@@ -59,10 +161,26 @@ struct Args {
     /// Repeat the solving process n times for more accurate performance reports.
     benchmark: Option<usize>,
 
+    #[clap(long, requires = "benchmark")]
+    /// Start every benchmark iteration with an empty environment to measure cold-cache performance.
+    cold: bool,
+
+    #[clap(long)]
+    /// Report node creation statistics to stderr after evaluation.
+    profile: bool,
+
+    #[clap(long, value_parser, value_name = "N")]
+    /// Abort with an error when the evaluation creates more than n nodes, including the terminals.
+    max_nodes: Option<usize>,
+
     #[clap(short = 'g', long)]
     /// Use GNUPlot to plot the runtime distribution.
     plot: bool,
 
+    #[clap(long)]
+    /// Print a histogram of the runtime distribution to stderr, this is also used when GNUPlot is not available.
+    plot_ascii: bool,
+
     #[clap(short, long, value_parser)]
     /// Parse the formula as string.
     evaluate: Option<String>,
@@ -71,20 +189,149 @@ struct Args {
     /// Read a custom variable ordering from file.
     ordering: Option<PathBuf>,
 
+    #[clap(
+        long,
+        value_parser,
+        value_name = "HEURISTIC",
+        conflicts_with = "ordering"
+    )]
+    /// Compute a static variable ordering before evaluation: appearance, dfs or force.
+    ordering_heuristic: Option<OrderingHeuristic>,
+
+    #[clap(
+        long,
+        value_parser,
+        value_name = "FILE",
+        conflicts_with_all = &["ordering", "statements"]
+    )]
+    /// Read the variable ordering from file if it exists, and write the final ordering back to it after evaluation.
+    warm_ordering: Option<PathBuf>,
+
     #[clap(short = 'r', long)]
     /// Export the automatically derived ordering to stdout.
     export_ordering: bool,
+
+    #[clap(short, long, value_parser = parse_assignment, value_delimiter = ',', value_name = "VAR=VALUE")]
+    /// Assign constant values (1 or 0) to variables before printing the results, e.g. a=1,b=0.
+    assign: Vec<(String, bool)>,
+
+    #[clap(long, value_parser, default_value_t = DomainEncoding::OneHot)]
+    /// The boolean encoding of domain variables: one-hot or log.
+    domain_encoding: DomainEncoding,
+
+    #[clap(long)]
+    /// Parse every line of the input as a separate formula and take the conjunction of all lines.
+    conjunction: bool,
+
+    #[clap(long, conflicts_with_all = ["conjunction", "benchmark"])]
+    /// Parse the input as independent formulas separated by `;`, and print the satisfiability and a model of each.
+    statements: bool,
+
+    #[clap(long)]
+    /// If the formula is unsatisfiable, print a minimal subset of its top-level conjuncts that is still unsatisfiable.
+    explain_unsat: bool,
+
+    #[clap(long, conflicts_with_all = ["summary_json", "is_tautology", "is_contradiction"])]
+    /// Print the comments preceding the formula, such as the provenance written by the generators, to stdout.
+    show_metadata: bool,
+
+    #[clap(long)]
+    /// Print the id, name and free index of every variable to stderr, for debugging the variable mapping.
+    dump_vars: bool,
+
+    #[clap(long)]
+    /// Print the iteration number and node count of every fixed-point iteration to stderr.
+    fp_progress: bool,
+
+    #[clap(long)]
+    /// Assign variable ids in sorted name order instead of the order of first occurrence, for reproducible output.
+    sort_vars: bool,
+
+    #[clap(long)]
+    /// Print warnings about likely modeling mistakes, such as unsatisfiable countable constraints, to stderr.
+    validate: bool,
+
+    #[clap(long, value_parser, value_name = "FILE", conflicts_with = "statements")]
+    /// Minimize the result using the assignments outside of the care set formula in the given file as don't-cares.
+    care_set: Option<PathBuf>,
+
+    #[clap(long, value_parser, value_name = "FILE")]
+    /// Check whether the input is equivalent to the formula in the given file, exits with 1 if not.
+    equiv: Option<PathBuf>,
+
+    #[clap(long, requires = "input", conflicts_with_all = ["evaluate", "equiv", "is_tautology", "is_contradiction"])]
+    /// Evaluate the input again whenever the input file changes, printing the new results until interrupted.
+    watch: bool,
+
+    #[clap(short, long, conflicts_with_all = ["input", "evaluate", "benchmark"])]
+    /// Start an interactive session, reading a formula or command (:count, :model, :table, :def, :quit) per line.
+    interactive: bool,
 }
 
+impl Args {
+    // whether the progress of the evaluation is hidden, such that only the requested result is printed
+    const fn quiet(&self) -> bool {
+        self.summary_json || ((self.is_tautology || self.is_contradiction) && !self.verbose)
+    }
+}
+
+// the number of variables from which on the size of the decision tree is reported
+const DECISION_TREE_WARNING_VARS: usize = 10;
+
+// the time to wait for further changes to the watched file before evaluating it again
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
 fn main() -> anyhow::Result<()> {
     let wild_args = wild::args_os();
     let args_in = argfile::expand_args_from(wild_args, argfile::parse_fromfile, argfile::PREFIX)?;
     let args = Args::parse_from(args_in);
 
+    if args.max_nodes.is_some() {
+        install_node_limit_hook();
+    }
+
+    if args.interactive {
+        return run_interactive(&args);
+    }
+
+    if let Some(watched) = args.input.as_deref().filter(|_| args.watch) {
+        return run_watch(&args, watched);
+    }
+
+    match run(&args) {
+        // the outcome of a checked property is reported through the exit code
+        Ok(Some(holds)) => std::process::exit(if holds { 0 } else { 1 }),
+        Ok(None) => Ok(()),
+        // errors are distinguished from a property that does not hold
+        Err(e) if args.is_tautology || args.is_contradiction => {
+            eprintln!("Error: {e:?}");
+            std::process::exit(2);
+        }
+        // an empty input is a usage error rather than a failure, so report it without a backtrace
+        Err(e) if e.downcast_ref::<io::Error>().is_some_and(is_empty_input) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// parse and evaluate the input once, and write all requested outputs
+// returns whether the checked property holds if --equiv, --is-tautology or --is-contradiction is given
+fn run(args: &Args) -> anyhow::Result<Option<bool>> {
     let repeat = args.benchmark.unwrap_or(1);
 
-    let inline_eval = args.evaluate;
-    let input_filename = args.input;
+    let inline_eval = &args.evaluate;
+    let input_filename = &args.input;
+
+    // the base name of the files written to the output directory
+    let output_stem = input_filename
+        .as_deref()
+        .and_then(Path::file_stem)
+        .map_or_else(
+            || "formula".to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
 
     let mut reader = if let Some(inline_str) = &inline_eval {
         Box::new(BufReader::new(inline_str.as_bytes())) as Box<dyn BufRead>
@@ -95,36 +342,167 @@ fn main() -> anyhow::Result<()> {
         Box::new(BufReader::new(io::stdin())) as Box<dyn BufRead>
     };
 
-    let pre_variable_ordering = if let Some(ord_filename) = args.ordering {
-        let file = File::open(ord_filename)?;
-        let mut contents = Box::new(BufReader::new(file)) as Box<dyn BufRead>;
-        let tokens = SymbolicBDD::tokenize(&mut contents, None)?;
-        let vars = ParsedFormula::extract_vars(&tokens);
-        Some(vars)
+    let input_format = if args.input_format == InputFormat::Auto {
+        let (detected, prefix) = detect_input_format(&mut reader)?;
+        // the lines read during detection are read again by the parser
+        reader = Box::new(io::Cursor::new(prefix).chain(reader));
+        detected
+    } else {
+        args.input_format
+    };
+
+    let pre_variable_ordering = if let Some(ord_filename) = &args.ordering {
+        Some(read_ordering(ord_filename)?)
+    } else if let Some(ord_filename) = args.warm_ordering.as_ref().filter(|f| f.exists()) {
+        // a missing warm ordering file starts from the default ordering
+        Some(read_ordering(ord_filename)?)
     } else {
         None
     };
 
-    let input_parsed = ParsedFormula::new(&mut reader, pre_variable_ordering)?;
+    let options = ParseOptions {
+        encoding: args.domain_encoding,
+        conjunction: args.conjunction,
+        sort_vars: args.sort_vars,
+    };
+
+    if args.summary_json && repeat == 0 {
+        anyhow::bail!(
+            "--summary-json requires at least one evaluation, i.e. --benchmark 1 or more"
+        );
+    }
+
+    if args.statements && input_format == InputFormat::Dimacs {
+        anyhow::bail!("--statements is not supported for DIMACS input");
+    }
+
+    if args.statements {
+        return run_statements(
+            new_env(args.profile, args.max_nodes),
+            &mut reader,
+            pre_variable_ordering,
+            options,
+            args.explain_unsat,
+            args.show_metadata,
+            args.out_dir
+                .as_deref()
+                .map(|dir| (dir, args.format, output_stem.as_str())),
+        )
+        .map(|()| None);
+    }
+
+    let mut input_parsed = if input_format == InputFormat::Dimacs {
+        let parsed = read_dimacs(new_env(args.profile, args.max_nodes), &mut reader)?;
+
+        if let Some(order) = pre_variable_ordering {
+            let mut reordered = parsed.with_order(&order)?;
+            reordered.env = new_env(args.profile, args.max_nodes);
+            reordered
+        } else {
+            parsed
+        }
+    } else {
+        ParsedFormula::new_with_options(
+            new_env(args.profile, args.max_nodes),
+            &mut reader,
+            pre_variable_ordering,
+            options,
+        )?
+    };
+
+    if let Some(heuristic) = args.ordering_heuristic {
+        let order = input_parsed.heuristic_order(heuristic);
+        input_parsed = input_parsed.with_order(&order)?;
+        input_parsed.env = new_env(args.profile, args.max_nodes);
+    }
 
-    if let Some(parsetree_filename) = args.parsetree {
+    if let Some(parsetree_filename) = &args.parsetree {
         let mut f = File::create(parsetree_filename)?;
 
-        let graph = SymbolicParseTree::new(&input_parsed.bdd);
+        let graph = SymbolicParseTree::new(&input_parsed.bdd).with_flatten(args.parsetree_flat);
 
         graph.render_dot(&mut f)?;
     }
 
+    if let Some(parsetree_filename) = &args.parsetree_json {
+        let mut f = File::create(parsetree_filename)?;
+
+        let graph = SymbolicParseTree::new(&input_parsed.bdd);
+
+        graph.to_json(&mut f)?;
+    }
+
+    if args.show_metadata {
+        for line in &input_parsed.metadata {
+            println!("{line}");
+        }
+    }
+
+    if args.dump_vars {
+        dump_vars(&input_parsed);
+    }
+
+    if args.validate {
+        for warning in input_parsed.validate() {
+            eprintln!("warning: {warning}");
+        }
+    }
+
+    input_parsed.fp_progress = args.fp_progress;
+
+    if let Some(other_filename) = &args.equiv {
+        return check_equivalence(&input_parsed, other_filename, options).map(Some);
+    }
+
     let mut result: Rc<BDD<NamedSymbol>> = Rc::default();
     let mut exec_times = Vec::new();
 
     // Benchmark: repeat n times and log runtime per iteration
     for i in 0..repeat {
+        // drop all previously computed nodes, such that nothing can be reused from earlier iterations
+        if args.cold {
+            input_parsed.env = new_env(args.profile, args.max_nodes);
+        }
+
         let tick = Instant::now();
         result = input_parsed.eval();
         exec_times.push(tick.elapsed());
 
-        eprintln!("finished {}/{} runs", i + 1, repeat);
+        if !args.quiet() {
+            eprintln!("finished {}/{} runs", i + 1, repeat);
+        }
+    }
+
+    if args.profile {
+        print_profile_report(&input_parsed.env.profile_report());
+    }
+
+    // partially evaluate the result, the assigned variables are no longer free
+    if !args.assign.is_empty() {
+        result = input_parsed.assign(result, &args.assign)?;
+    }
+
+    if let Some(care_filename) = &args.care_set {
+        result = apply_care_set(&input_parsed, result, care_filename, options)?;
+    }
+
+    if args.summary_json {
+        print_summary_json(&input_parsed, &result, &exec_times)?;
+        return Ok(None);
+    }
+
+    if args.is_tautology || args.is_contradiction {
+        let (holds, property) = if args.is_tautology {
+            (result.is_true(), "a tautology")
+        } else {
+            (result.is_false(), "a contradiction")
+        };
+
+        if args.verbose {
+            println!("{}{property}", if holds { "" } else { "not " });
+        }
+
+        return Ok(Some(holds));
     }
 
     // Simplify the output when retain_choices is on
@@ -138,11 +516,45 @@ fn main() -> anyhow::Result<()> {
     if args.benchmark.is_some() && repeat > 0 {
         print_performance_results(&exec_times);
 
-        if args.plot {
+        if args.plot_ascii {
+            plot_ascii_performance_results(&exec_times)?;
+        } else if args.plot {
             plot_performance_results(&exec_times)?;
         }
     }
 
+    if args.count {
+        let free_vars = input_parsed.free_vars.len();
+        check_count_limit(free_vars)?;
+
+        println!("{}", input_parsed.env.satcount(&result, free_vars));
+    }
+
+    if args.count_paths {
+        println!("{}", input_parsed.env.path_count(&result));
+    }
+
+    if args.marginals {
+        print_marginals(&input_parsed, &result)?;
+    }
+
+    if args.level_stats {
+        print_level_stats(&input_parsed, &result);
+    }
+
+    if args.check_unique_sat {
+        println!(
+            "{}",
+            input_parsed
+                .env
+                .uniqueness(&result, input_parsed.free_vars.len())
+        );
+    }
+
+    if args.explain_unsat {
+        print_unsat_core(&input_parsed);
+    }
+
     // reduce the bdd to a single path from root to a single 'true' node
     if args.model {
         result = input_parsed.env.model(result);
@@ -150,20 +562,20 @@ fn main() -> anyhow::Result<()> {
 
     // show ordered variable list
 
-    if args.export_ordering {
-        let mut ordered_variables = input_parsed.vars.clone();
-        ordered_variables.sort_by(|a, b| a.id.cmp(&b.id));
-        let ordered_variable_names = ordered_variables
-            .iter()
-            .map(|v| v.name.as_ref())
-            .cloned()
-            .collect::<Vec<String>>();
-
-        for v in &ordered_variable_names {
+    if args.support {
+        for v in input_parsed.env.support(&result) {
             println!("{}", v);
         }
     }
 
+    if args.export_ordering {
+        write_ordering(&mut io::stdout(), &input_parsed.vars)?;
+    }
+
+    if let Some(ord_filename) = &args.warm_ordering {
+        write_ordering(&mut File::create(ord_filename)?, &input_parsed.vars)?;
+    }
+
     // show truth table
 
     let mut headers = input_parsed
@@ -176,49 +588,630 @@ fn main() -> anyhow::Result<()> {
 
     let widths: Vec<usize> = headers.iter().map(|v| max(5, v.len())).collect();
 
+    if args.truthtable || args.truthtable_csv || args.vars {
+        check_var_limit(input_parsed.free_vars.len(), args.limit_vars)?;
+    }
+
     if args.truthtable {
         print_header(&headers, &widths);
-        print_truth_table_recursive(
-            &result,
+        for (row, result) in truth_table_rows(&input_parsed, &result, args.filter, args.expand) {
+            print_sized_line(&row, &widths, result);
+        }
+    }
+
+    if args.truthtable_csv {
+        let mut writer = csv::Writer::from_writer(io::stdout());
+
+        writer.write_record(
             input_parsed
                 .free_vars
                 .iter()
-                .map(|_| TruthTableEntry::Any)
-                .collect(),
-            args.filter,
-            &input_parsed,
-            &widths,
-        );
+                .map(|v| v.name.as_str())
+                .chain(["result"]),
+        )?;
+
+        for (row, result) in truth_table_rows(&input_parsed, &result, args.filter, args.expand) {
+            writer.write_record(
+                row.iter()
+                    .chain([&result])
+                    .map(|v| csv_cell(v.is_true(), v.is_false())),
+            )?;
+        }
+
+        writer.flush()?;
     }
 
     if args.vars {
-        print_true_vars_recursive(
-            &result,
-            input_parsed
-                .free_vars
-                .iter()
-                .map(|_| TruthTableEntry::Any)
-                .collect(),
-            &headers,
-            &input_parsed,
-        );
+        for (row, _) in truth_table_rows(&input_parsed, &result, TruthTableEntry::True, false) {
+            print_true_vars(&row, &headers);
+        }
     }
 
-    if let Some(dot_filename) = args.dot {
+    if let Some(dot_filename) = &args.dot {
         let mut f = File::create(dot_filename)?;
 
-        let graph = BDDGraph::new(&result, args.filter);
+        let root = if args.decision_tree {
+            let num_vars = input_parsed.free_vars.len();
+            if num_vars > DECISION_TREE_WARNING_VARS {
+                eprintln!(
+                    "warning: the decision tree over {num_vars} variables has 2^{num_vars} leaves"
+                );
+            }
+
+            input_parsed
+                .env
+                .to_decision_tree(&result, &input_parsed.free_vars)
+        } else {
+            Rc::clone(&result)
+        };
+
+        let graph = BDDGraph::new(&root, args.filter)
+            .with_compact(args.dot_compact)
+            .with_ranked(args.dot_ranked)
+            .with_tree(args.decision_tree);
 
         graph.render_dot(&mut f)?
     }
 
+    if let Some(out_dir) = &args.out_dir {
+        write_output(out_dir, &output_stem, args.format, &input_parsed, &result)?;
+    }
+
+    if let Some(blif_filename) = &args.blif {
+        let mut f = File::create(blif_filename)?;
+
+        write_blif(&input_parsed, &result, &mut f)?
+    }
+
+    if let Some(rust_filename) = &args.emit_rust {
+        let mut f = File::create(rust_filename)?;
+
+        to_rust_fn(&result, "decide", &mut f)?
+    }
+
+    if let Some(graphml_filename) = &args.graphml {
+        let mut f = File::create(graphml_filename)?;
+
+        let graph = BDDGraph::new(&result, args.filter);
+
+        graph.render_graphml(&mut f)?
+    }
+
+    Ok(None)
+}
+
+// parse the formula in the other file in the environment of the input, using the variables of the input as ordering,
+// such that shared variables get the same ids
+fn parse_other(
+    input_parsed: &ParsedFormula,
+    other_filename: &Path,
+    options: ParseOptions,
+) -> anyhow::Result<ParsedFormula> {
+    let file = File::open(other_filename)?;
+
+    Ok(ParsedFormula::new_with_options(
+        Rc::clone(&input_parsed.env),
+        &mut BufReader::new(file),
+        Some(input_parsed.vars.clone()),
+        options,
+    )?)
+}
+
+// minimize the result against the care set in the given file, and report the reduction in size to stderr
+fn apply_care_set(
+    input_parsed: &ParsedFormula,
+    result: Rc<BDD<NamedSymbol>>,
+    care_filename: &Path,
+    options: ParseOptions,
+) -> anyhow::Result<Rc<BDD<NamedSymbol>>> {
+    let care_parsed = parse_other(input_parsed, care_filename, options)?;
+
+    // the minimized result can depend on any variable of the care set, which must therefore be a variable of the input
+    if let Some(unknown) = care_parsed
+        .free_vars
+        .iter()
+        .find(|v| !input_parsed.free_vars.contains(v))
+    {
+        anyhow::bail!(
+            "the care set refers to variable {}, which does not occur in the input",
+            unknown.name
+        );
+    }
+
+    let env = &input_parsed.env;
+    let simplified = env.simplify_with_dontcare(&result, &care_parsed.eval());
+
+    eprintln!(
+        "the care set reduced the bdd from {} to {} nodes",
+        env.size_reachable(&result),
+        env.size_reachable(&simplified)
+    );
+
+    Ok(simplified)
+}
+
+// evaluate the input every time the file changes, until the process is interrupted
+fn run_watch(args: &Args, input: &Path) -> anyhow::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+
+    // editors often replace the file rather than writing to it, therefore the containing directory is watched
+    let dir = input
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+    loop {
+        // errors do not end the session, such that the formula can be fixed while watching
+        if let Err(e) = run(args) {
+            eprintln!("Error: {e}");
+        }
+        eprintln!("watching {} for changes", input.display());
+
+        wait_for_change(&receiver, input)?;
+
+        // a single save can consist of multiple writes, which are evaluated once
+        while receiver.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+    }
+}
+
+// block until the watched file is created, modified or removed, ignoring the events of other files and reads
+fn wait_for_change(receiver: &Receiver<notify::Result<Event>>, file: &Path) -> anyhow::Result<()> {
+    loop {
+        let event = receiver.recv()??;
+
+        let changed = event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove();
+
+        if changed
+            && event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == file.file_name())
+        {
+            return Ok(());
+        }
+    }
+}
+
+// read a variable ordering, i.e. a file listing the variables in order
+fn read_ordering(filename: &Path) -> anyhow::Result<Vec<NamedSymbol>> {
+    let file = File::open(filename)?;
+    let mut contents = Box::new(BufReader::new(file)) as Box<dyn BufRead>;
+    let tokens = SymbolicBDD::tokenize(&mut contents, None)?;
+    Ok(ParsedFormula::extract_vars(&tokens))
+}
+
+// write the variables ordered by id, one per line, such that the ordering can be read using `read_ordering`
+fn write_ordering(out: &mut dyn Write, vars: &[NamedSymbol]) -> anyhow::Result<()> {
+    let mut ordered_variables = vars.to_vec();
+    ordered_variables.sort_by_key(|a| a.id);
+
+    for v in &ordered_variables {
+        writeln!(out, "{}", v.name)?;
+    }
+
     Ok(())
 }
 
-fn print_sized_line<B, C, D>(labels: &Vec<D>, widths: &B, result: &BDD<C>)
+/// The format of the input formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum InputFormat {
+    /// Detect DIMACS CNF input by its header, and parse any other input as rsbdd
+    #[default]
+    Auto,
+    Rsbdd,
+    Dimacs,
+}
+
+impl Display for InputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Auto => "auto",
+            Self::Rsbdd => "rsbdd",
+            Self::Dimacs => "dimacs",
+        })
+    }
+}
+
+impl FromStr for InputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "rsbdd" => Ok(Self::Rsbdd),
+            "dimacs" | "cnf" => Ok(Self::Dimacs),
+            _ => Err(anyhow::anyhow!("cannot parse {s} as input format")),
+        }
+    }
+}
+
+// determine the format of the input from its first line that is not a DIMACS comment
+// the lines read so far are returned, such that they can be parsed again
+fn detect_input_format(reader: &mut dyn BufRead) -> anyhow::Result<(InputFormat, Vec<u8>)> {
+    let mut prefix = Vec::new();
+
+    loop {
+        let start = prefix.len();
+        if reader.read_until(b'\n', &mut prefix)? == 0 {
+            return Ok((InputFormat::Rsbdd, prefix));
+        }
+
+        let line = String::from_utf8_lossy(&prefix[start..]);
+        let line = line.trim();
+
+        if line.is_empty() || line == "c" || line.starts_with("c ") {
+            continue;
+        }
+
+        if line.starts_with("p cnf") {
+            return Ok((InputFormat::Dimacs, prefix));
+        }
+
+        if matches!(
+            line.split_whitespace().next(),
+            Some("digraph" | "graph" | "strict")
+        ) && line.contains('{')
+        {
+            anyhow::bail!(
+                "the input looks like a graphviz dot file, which cannot be read; pass the formula in rsbdd or DIMACS format instead"
+            );
+        }
+
+        return Ok((InputFormat::Rsbdd, prefix));
+    }
+}
+
+// check whether the parsed input and the formula in the other file are equivalent, and print the outcome
+fn check_equivalence(
+    input_parsed: &ParsedFormula,
+    other_filename: &Path,
+    options: ParseOptions,
+) -> anyhow::Result<bool> {
+    let other_parsed = parse_other(input_parsed, other_filename, options)?;
+
+    let env = &input_parsed.env;
+    let difference = env.xor(input_parsed.eval(), other_parsed.eval());
+
+    if difference.is_false() {
+        println!("equivalent");
+        return Ok(true);
+    }
+
+    println!("not equivalent");
+    println!(
+        "distinguishing assignment: {}",
+        format_model(&env.model(difference))
+    );
+
+    Ok(false)
+}
+
+// format the assignment of a model as a=1,b=0 by following its single path to true
+fn format_model(model: &Rc<BDD<NamedSymbol>>) -> String {
+    let mut assignment = Vec::new();
+    let mut node = Rc::clone(model);
+    while let BDD::Choice(t, v, f, _) = node.as_ref() {
+        let value = !t.is_false();
+        assignment.push(format!("{}={}", v, u8::from(value)));
+        node = Rc::clone(if value { t } else { f });
+    }
+
+    assignment.join(",")
+}
+
+// evaluate every statement independently, printing a block with the satisfiability and a model per statement
+fn run_statements(
+    env: Rc<BDDEnv<NamedSymbol>>,
+    reader: &mut dyn BufRead,
+    variable_ordering: Option<Vec<NamedSymbol>>,
+    options: ParseOptions,
+    explain_unsat: bool,
+    show_metadata: bool,
+    output: Option<(&Path, OutputFormat, &str)>,
+) -> anyhow::Result<()> {
+    let statements = ParsedFormula::new_statements(env, reader, variable_ordering, options)?;
+
+    // the leading comments of the input are shared by all statements
+    if let Some(first) = statements
+        .first()
+        .filter(|first| show_metadata && !first.metadata.is_empty())
+    {
+        for line in &first.metadata {
+            println!("{line}");
+        }
+        println!();
+    }
+
+    for (i, parsed) in statements.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+
+        let result = parsed.eval();
+
+        if result.is_false() {
+            println!("statement {}: unsat", i + 1);
+
+            if explain_unsat {
+                print_unsat_core(parsed);
+            }
+        } else {
+            println!("statement {}: sat", i + 1);
+            println!(
+                "model: {}",
+                format_model(&parsed.env.model(Rc::clone(&result)))
+            );
+        }
+
+        if let Some((out_dir, format, stem)) = output {
+            write_output(
+                out_dir,
+                &format!("{stem}_{}", i + 1),
+                format,
+                parsed,
+                &result,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The format of the files written to the output directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum OutputFormat {
+    /// The bdd as dot graph
+    #[default]
+    Dot,
+    /// The parse tree as json
+    Json,
+}
+
+impl OutputFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.extension())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow::anyhow!("cannot parse {s} as output format")),
+        }
+    }
+}
+
+// write the result or parse tree to '<out_dir>/<name>.<extension>', creating the directory if needed
+fn write_output(
+    out_dir: &Path,
+    name: &str,
+    format: OutputFormat,
+    parsed: &ParsedFormula,
+    result: &Rc<BDD<NamedSymbol>>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    let mut f = File::create(out_dir.join(format!("{name}.{}", format.extension())))?;
+
+    match format {
+        OutputFormat::Dot => BDDGraph::new(result, TruthTableEntry::Any).render_dot(&mut f)?,
+        OutputFormat::Json => SymbolicParseTree::new(&parsed.bdd).to_json(&mut f)?,
+    }
+
+    Ok(())
+}
+
+// print the conjuncts in a minimal unsatisfiable core, numbered by their position in the list of top-level conjuncts
+fn print_unsat_core(parsed: &ParsedFormula) {
+    if let Some(core) = parsed.unsat_core() {
+        let conjuncts = parsed.conjuncts();
+
+        println!("unsat core:");
+        for i in core {
+            println!("constraint {}: {}", i + 1, conjuncts[i]);
+        }
+    }
+}
+
+// print a tab-separated line per variable in id order: the id, the name, and whether it is free or bound
+// followed by its free index for free variables
+fn dump_vars(parsed: &ParsedFormula) {
+    let mut vars = parsed.vars.clone();
+    vars.sort_by_key(|v| v.id);
+
+    for v in vars {
+        match parsed.raw2free.get(v.id).copied().flatten() {
+            Some(index) => eprintln!("{}\t{}\tfree\t{}", v.id, v.name, index),
+            None => eprintln!("{}\t{}\tbound", v.id, v.name),
+        }
+    }
+}
+
+// the state of an interactive session, shared by all lines
+struct Session {
+    env: Rc<BDDEnv<NamedSymbol>>,
+    // all variables seen so far, used as variable ordering to keep the variable ids stable across lines
+    vars: Vec<NamedSymbol>,
+    definitions: FxHashMap<String, ReferenceContents>,
+    encoding: DomainEncoding,
+}
+
+impl Session {
+    fn eval(&mut self, src: &str) -> io::Result<(ParsedFormula, Rc<BDD<NamedSymbol>>)> {
+        let mut parsed = ParsedFormula::new_with_encoding(
+            Rc::clone(&self.env),
+            &mut BufReader::new(src.as_bytes()),
+            Some(self.vars.clone()),
+            self.encoding,
+        )?;
+
+        for (name, contents) in &self.definitions {
+            parsed.define(name, contents.clone());
+        }
+
+        // variables occurring in referenced definitions can be free as well
+        parsed.add_vars(&self.vars);
+        self.vars.clone_from(&parsed.vars);
+
+        let result = parsed.eval();
+
+        Ok((parsed, result))
+    }
+}
+
+// read-eval-print loop: every line is either a formula or a command operating on the last evaluated formula
+fn run_interactive(args: &Args) -> anyhow::Result<()> {
+    let mut session = Session {
+        env: new_env(args.profile, args.max_nodes),
+        vars: Vec::new(),
+        definitions: FxHashMap::default(),
+        encoding: args.domain_encoding,
+    };
+
+    let mut last: Option<(ParsedFormula, Rc<BDD<NamedSymbol>>)> = None;
+
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            break;
+        }
+
+        let line = line.trim();
+        let (command, argument) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(c, a)| (c, a.trim()));
+
+        match (command, &last) {
+            ("", _) => {}
+            (":quit" | ":q", _) => break,
+            (":def", _) => match argument.split_once(char::is_whitespace) {
+                Some((name, src)) => match session.eval(src) {
+                    Ok((parsed, _)) => {
+                        session
+                            .definitions
+                            .insert(name.to_string(), ReferenceContents::Syntax(parsed.bdd));
+                        println!("defined {{{name}}}");
+                    }
+                    Err(e) => eprintln!("error: {e}"),
+                },
+                None => eprintln!("error: expected ':def name formula'"),
+            },
+            (":count" | ":model" | ":table", None) => eprintln!("error: no formula evaluated yet"),
+            (":count", Some((parsed, result))) => match check_count_limit(parsed.free_vars.len()) {
+                Ok(()) => println!("{}", session.env.satcount(result, parsed.free_vars.len())),
+                Err(e) => eprintln!("error: {e}"),
+            },
+            (":model", Some((parsed, result))) => {
+                print_interactive_table(
+                    parsed,
+                    &session.env.model(Rc::clone(result)),
+                    TruthTableEntry::True,
+                )?;
+            }
+            (":table", Some((parsed, result))) => {
+                print_interactive_table(parsed, result, TruthTableEntry::Any)?;
+            }
+            (c, _) if c.starts_with(':') => eprintln!("error: unknown command {c}"),
+            _ => match session.eval(line) {
+                Ok((parsed, result)) => {
+                    println!(
+                        "{}",
+                        match result.as_ref() {
+                            BDD::True => "true",
+                            BDD::False => "false",
+                            BDD::Choice(_, _, _, _) => "satisfiable",
+                        }
+                    );
+                    last = Some((parsed, result));
+                }
+                Err(e) => eprintln!("error: {e}"),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn print_interactive_table(
+    parsed: &ParsedFormula,
+    result: &Rc<BDD<NamedSymbol>>,
+    filter: TruthTableEntry,
+) -> anyhow::Result<()> {
+    let mut headers = parsed
+        .free_vars
+        .iter()
+        .map(|v| v.name.as_ref())
+        .cloned()
+        .collect::<Vec<String>>();
+    headers.push("*".to_string());
+
+    let widths: Vec<usize> = headers.iter().map(|v| max(5, v.len())).collect();
+
+    print_header(&headers, &widths);
+    for (row, result) in truth_table_rows(parsed, result, filter, false) {
+        print_sized_line(&row, &widths, result);
+    }
+
+    Ok(())
+}
+
+// parse a single 'name=value' assignment
+fn parse_assignment(s: &str) -> anyhow::Result<(String, bool)> {
+    let (name, value) = s.split_once('=').ok_or_else(|| {
+        anyhow::anyhow!("expected an assignment of the form name=value, got '{s}'")
+    })?;
+
+    let value = match value.trim() {
+        "1" | "true" => true,
+        "0" | "false" => false,
+        other => return Err(anyhow::anyhow!("cannot parse '{other}' as a truth value")),
+    };
+
+    Ok((name.trim().to_string(), value))
+}
+
+// prevent accidentally printing a truth table with an exponential number of rows
+fn check_var_limit(free_vars: usize, limit: usize) -> anyhow::Result<()> {
+    if limit > 0 && free_vars > limit {
+        Err(anyhow::anyhow!(
+            "the formula has {free_vars} free variables, which exceeds the limit of {limit}; use --model or --count to inspect the result instead, or raise the limit using --limit-vars"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// the number of assignments of 128 or more variables does not fit in the u128 model count
+fn check_count_limit(free_vars: usize) -> anyhow::Result<()> {
+    if free_vars >= u128::BITS as usize {
+        Err(anyhow::anyhow!(
+            "the model count over {free_vars} free variables does not fit in a u128"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn print_sized_line<B, D>(labels: &[D], widths: &B, result: TruthTableEntry)
 where
     B: Index<usize, Output = usize>,
-    C: BDDSymbol,
     D: Display + Sized,
 {
     print!("|");
@@ -226,15 +1219,7 @@ where
     for (i, label) in labels.iter().enumerate() {
         print!(" {:indent$} |", label, indent = widths[i]);
     }
-    println!(
-        " {:indent$} |",
-        match result {
-            BDD::True => "True",
-            BDD::False => "False",
-            _ => unreachable!(),
-        },
-        indent = widths[len]
-    );
+    println!(" {:indent$} |", result, indent = widths[len]);
 }
 
 // print header
@@ -255,6 +1240,49 @@ where
     println!("|");
 }
 
+// create a new environment, optionally collecting node creation statistics and limiting the number of nodes
+fn new_env(profile: bool, max_nodes: Option<usize>) -> Rc<BDDEnv<NamedSymbol>> {
+    let env = if profile {
+        BDDEnv::with_profiling()
+    } else {
+        BDDEnv::new()
+    };
+
+    Rc::new(match max_nodes {
+        Some(limit) => env.with_max_nodes(limit),
+        None => env,
+    })
+}
+
+fn is_empty_input(e: &io::Error) -> bool {
+    e.get_ref()
+        .and_then(|inner| inner.downcast_ref::<ParseError>())
+        .is_some_and(|inner| *inner == ParseError::EmptyInput)
+}
+
+// report an exceeded node limit as an error instead of a panic, other panics use the default hook
+fn install_node_limit_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(exceeded) = info.payload().downcast_ref::<NodeLimitExceeded>() {
+            eprintln!("Error: {exceeded}");
+            std::process::exit(1);
+        }
+
+        default_hook(info);
+    }));
+}
+
+// print node creation statistics to stderr
+fn print_profile_report(report: &ProfileReport) {
+    eprintln!("Profile report:");
+    eprintln!("mk_choice calls: {}", report.mk_choice_calls);
+    eprintln!("Cache hits: {}", report.cache_hits);
+    eprintln!("Cache misses: {}", report.cache_misses);
+    eprintln!("Peak table size: {}", report.peak_size);
+}
+
 // compute run-time statistics: minimum, maximum, median, mean, standard-deviation
 fn stats(results: &[Duration]) -> (f64, f64, f64, f64, f64) {
     let mut sresults = results.to_vec();
@@ -301,11 +1329,20 @@ fn print_performance_results(results: &[Duration]) {
 fn plot_performance_results(results: &[Duration]) -> anyhow::Result<()> {
     let (_, _, _, mean, stddev) = stats(results);
 
-    let mut gnuplot_cmd = Command::new("gnuplot")
+    let spawned = Command::new("gnuplot")
         .arg("-p") // persistent mode
         .arg("-") // piped mode
         .stdin(Stdio::piped())
-        .spawn()?;
+        .spawn();
+
+    // fall back to a textual histogram if gnuplot cannot be started
+    let mut gnuplot_cmd = match spawned {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            eprintln!("cannot start gnuplot ({e}), falling back to a textual histogram");
+            return plot_ascii_performance_results(results);
+        }
+    };
 
     let stdin = gnuplot_cmd.stdin.as_mut().expect("cannot take stdin");
     write_gnuplot_normal_distribution(
@@ -321,68 +1358,168 @@ fn plot_performance_results(results: &[Duration]) -> anyhow::Result<()> {
     Ok(())
 }
 
-// print all variables which can take a 'true' value in the bdd
-fn print_true_vars_recursive(
-    root: &Rc<BDD<NamedSymbol>>,
-    values: Vec<TruthTableEntry>,
-    vars: &[String],
+// print a histogram of the run-time distribution to stderr
+fn plot_ascii_performance_results(results: &[Duration]) -> anyhow::Result<()> {
+    let (min, max, _, _, _) = stats(results);
+    let samples: Vec<f64> = results.iter().map(Duration::as_secs_f64).collect();
+
+    write_ascii_histogram(&mut io::stderr(), &samples, min, max, 10)?;
+
+    Ok(())
+}
+
+// print a single json object summarizing the result, the elapsed time is the mean over all runs
+fn print_summary_json(
     parsed: &ParsedFormula,
-) {
-    match root.as_ref() {
-        BDD::Choice(ref l, s, ref r) => {
-            // first visit the false subtree
-            let mut r_vals = values.clone();
-            r_vals[parsed.to_free_index(s)] = TruthTableEntry::False;
-            print_true_vars_recursive(r, r_vals, vars, parsed);
-
-            // then visit the true subtree
-            let mut l_vals = values;
-            l_vals[parsed.to_free_index(s)] = TruthTableEntry::True;
-            print_true_vars_recursive(l, l_vals, vars, parsed);
-        }
-        BDD::True => {
-            let mut vars_str = Vec::new();
-            for (i, v) in values.iter().enumerate() {
-                if *v == TruthTableEntry::True {
-                    vars_str.push(vars[i].clone());
-                } else if *v == TruthTableEntry::Any {
-                    vars_str.push(vars[i].clone() + "*");
+    result: &Rc<BDD<NamedSymbol>>,
+    exec_times: &[Duration],
+) -> anyhow::Result<()> {
+    check_count_limit(parsed.free_vars.len())?;
+
+    let env = &parsed.env;
+    let free_vars: Vec<String> = parsed
+        .free_vars
+        .iter()
+        .map(|v| json_string(&v.name))
+        .collect();
+    let (_, _, _, mean, _) = stats(exec_times);
+
+    println!(
+        "{{\"sat\": {}, \"model_count\": {}, \"node_count\": {}, \"free_vars\": [{}], \"elapsed_ms\": {:.3}}}",
+        !result.is_false(),
+        env.satcount(result, parsed.free_vars.len()),
+        env.size_reachable(result),
+        free_vars.join(", "),
+        mean * 1000.0
+    );
+
+    Ok(())
+}
+
+// print the number of models in which every free variable is true and false as a table
+fn print_marginals(parsed: &ParsedFormula, result: &Rc<BDD<NamedSymbol>>) -> anyhow::Result<()> {
+    check_count_limit(parsed.free_vars.len())?;
+
+    let counts = parsed.env.marginal_counts(result, &parsed.free_vars);
+
+    let rows = parsed
+        .free_vars
+        .iter()
+        .zip(counts)
+        .map(|(v, (t, f))| vec![v.name.to_string(), t.to_string(), f.to_string()])
+        .collect();
+
+    print_table(&["name", "#true", "#false"], rows);
+
+    Ok(())
+}
+
+// print the number of nodes at every level of the result, i.e. the width of the bdd per free variable
+fn print_level_stats(parsed: &ParsedFormula, result: &Rc<BDD<NamedSymbol>>) {
+    let levels = parsed.env.level_map(result);
+
+    let rows = parsed
+        .free_vars
+        .iter()
+        .map(|v| {
+            let width = levels.get(v).map_or(0, Vec::len);
+            vec![v.name.to_string(), width.to_string()]
+        })
+        .collect();
+
+    print_table(&["name", "nodes"], rows);
+}
+
+// print a table with a header, the first column is aligned to the left and all other columns to the right
+fn print_table(header: &[&str], rows: Vec<Vec<String>>) {
+    let rows: Vec<Vec<String>> = std::iter::once(header.iter().map(|h| h.to_string()).collect())
+        .chain(rows)
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].len())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, &width))| {
+                if column == 0 {
+                    format!("{cell:width$}")
+                } else {
+                    format!("{cell:>width$}")
                 }
-            }
-            println!("{};", vars_str.join(", "));
+            })
+            .collect();
+
+        println!("| {} |", cells.join(" | "));
+    }
+}
+
+// print the variables which are true in a row of the truth table, marking the variables which can take any value
+fn print_true_vars(row: &[TruthTableEntry], vars: &[String]) {
+    let mut vars_str = Vec::new();
+    for (i, v) in row.iter().enumerate() {
+        if *v == TruthTableEntry::True {
+            vars_str.push(vars[i].clone());
+        } else if *v == TruthTableEntry::Any {
+            vars_str.push(vars[i].clone() + "*");
         }
-        _ => {}
+    }
+    println!("{};", vars_str.join(", "));
+}
+
+// a single cell in the csv truth table
+const fn csv_cell(is_true: bool, is_false: bool) -> &'static str {
+    if is_true {
+        "T"
+    } else if is_false {
+        "F"
+    } else {
+        "*"
     }
 }
 
-// recursively walk through the bdd and assign values to the variables until every permutation is assigned a true or false value
-fn print_truth_table_recursive<A>(
-    root: &Rc<BDD<NamedSymbol>>,
-    vars: Vec<TruthTableEntry>,
+// the rows of the truth table of the result over the free variables, only retaining the rows matching the filter
+// when expanding, every row with variables that can take any value is replaced by the rows of all its assignments
+fn truth_table_rows<'a>(
+    parsed: &'a ParsedFormula,
+    result: &Rc<BDD<NamedSymbol>>,
     filter: TruthTableEntry,
-    parsed: &ParsedFormula,
-    sizes: &A,
-) where
-    A: Index<usize, Output = usize>,
-{
-    match root.as_ref() {
-        BDD::Choice(ref l, s, ref r) => {
-            // first visit the false subtree
-            let mut r_vars = vars.clone();
-            r_vars[parsed.to_free_index(s)] = TruthTableEntry::False;
-            print_truth_table_recursive(r, r_vars, filter, parsed, sizes);
-
-            // then visit the true subtree
-            let mut l_vars = vars;
-            l_vars[parsed.to_free_index(s)] = TruthTableEntry::True;
-            print_truth_table_recursive(l, l_vars, filter, parsed, sizes);
-        }
-        c if (filter == TruthTableEntry::Any)
-            || (filter == TruthTableEntry::True && *c == BDD::True)
-            || (filter == TruthTableEntry::False && *c == BDD::False) =>
-        {
-            print_sized_line(&vars, sizes, c);
+    expand: bool,
+) -> impl Iterator<Item = (Vec<TruthTableEntry>, TruthTableEntry)> + 'a {
+    parsed
+        .truth_table_rows(result, &parsed.free_vars)
+        .filter(move |(_, result)| filter.is_any() || *result == filter)
+        .flat_map(move |(row, result)| {
+            let rows = if expand { expand_row(row) } else { vec![row] };
+            rows.into_iter().map(move |row| (row, result))
+        })
+}
+
+// all complete assignments of a row, enumerated in the same order as the truth table
+fn expand_row(row: Vec<TruthTableEntry>) -> Vec<Vec<TruthTableEntry>> {
+    (0..row.len()).fold(vec![row], |rows, i| {
+        // all rows share the entries which are not expanded yet
+        if !rows[0][i].is_any() {
+            return rows;
         }
-        _ => {}
-    }
+
+        rows.into_iter()
+            .flat_map(|row| {
+                [TruthTableEntry::False, TruthTableEntry::True].map(|value| {
+                    let mut row = row.clone();
+                    row[i] = value;
+                    row
+                })
+            })
+            .collect()
+    })
 }
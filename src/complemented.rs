@@ -0,0 +1,405 @@
+//! An alternative bdd representation using complemented edges.
+//!
+//! A function and its negation share the same nodes: an edge can carry a complement flag, which negates the function
+//! represented by the node it points to. To keep the representation canonical, the following rules apply:
+//! - there is only a single terminal node (true), false is represented by a complemented edge to true
+//! - only the false-edge of a choice can be complemented
+//!
+//! As a result, negation is a constant-time operation.
+
+extern crate dot;
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::hash::Hasher;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+
+use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHasher};
+
+use crate::bdd::{BDDEnv, BDD};
+use crate::escape::dot_label;
+use crate::BDDSymbol;
+
+#[derive(Debug)]
+pub enum CNode<Symbol: BDDSymbol> {
+    True,
+    // Choice (true-edge, symbol, false-edge, structural hash)
+    Choice(Edge<Symbol>, Symbol, Edge<Symbol>, u64),
+}
+
+/// A reference to a node, which is negated if the complement flag is set.
+#[derive(Debug)]
+pub struct Edge<Symbol: BDDSymbol> {
+    pub node: Rc<CNode<Symbol>>,
+    pub complement: bool,
+}
+
+impl<S: BDDSymbol> Clone for Edge<S> {
+    fn clone(&self) -> Self {
+        Self {
+            node: Rc::clone(&self.node),
+            complement: self.complement,
+        }
+    }
+}
+
+// nodes are unique within an environment, therefore edges are equal if they point to the same node
+impl<S: BDDSymbol> PartialEq for Edge<S> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.node, &other.node) && self.complement == other.complement
+    }
+}
+
+impl<S: BDDSymbol> Eq for Edge<S> {}
+
+impl<S: BDDSymbol> Edge<S> {
+    pub fn is_const(&self) -> bool {
+        matches!(self.node.as_ref(), CNode::True)
+    }
+
+    pub fn is_true(&self) -> bool {
+        self.is_const() && !self.complement
+    }
+
+    pub fn is_false(&self) -> bool {
+        self.is_const() && self.complement
+    }
+
+    /// Negate the function represented by this edge
+    pub fn negate(&self) -> Self {
+        Self {
+            node: Rc::clone(&self.node),
+            complement: !self.complement,
+        }
+    }
+
+    pub fn get_hash(&self) -> u64 {
+        self.node.get_hash() ^ u64::from(self.complement)
+    }
+
+    // the top symbol of the referenced node, None for the terminal node
+    fn symbol(&self) -> Option<&S> {
+        match self.node.as_ref() {
+            CNode::True => None,
+            CNode::Choice(_, v, _, _) => Some(v),
+        }
+    }
+
+    // the true and false cofactors of this edge with respect to its top symbol, with the complement pushed down
+    fn cofactors(&self) -> (Self, Self) {
+        match self.node.as_ref() {
+            CNode::True => (self.clone(), self.clone()),
+            CNode::Choice(t, _, f, _) if self.complement => (t.negate(), f.negate()),
+            CNode::Choice(t, _, f, _) => (t.clone(), f.clone()),
+        }
+    }
+}
+
+impl<S: BDDSymbol> CNode<S> {
+    /// Obtain the structural hash of this node in constant time.
+    pub const fn get_hash(&self) -> u64 {
+        match self {
+            Self::True => 1,
+            Self::Choice(_, _, _, hash) => *hash,
+        }
+    }
+}
+
+// key of a choice in the lookup table: the true-node, symbol, false-node and false-edge complement flag
+type NodeKey<S> = (*const CNode<S>, S, *const CNode<S>, bool);
+
+#[derive(Debug)]
+pub struct ComplementedEnv<Symbol: BDDSymbol> {
+    pub nodes: RefCell<FxHashMap<NodeKey<Symbol>, Rc<CNode<Symbol>>>>,
+    terminal: Rc<CNode<Symbol>>,
+}
+
+impl<S: BDDSymbol> Default for ComplementedEnv<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: BDDSymbol> ComplementedEnv<S> {
+    /// Create a new BDD graph with complemented edges
+    pub fn new() -> Self {
+        Self {
+            nodes: RefCell::new(FxHashMap::default()),
+            terminal: Rc::new(CNode::True),
+        }
+    }
+
+    /// The number of nodes in the environment, including the terminal node
+    pub fn size(&self) -> usize {
+        self.nodes.borrow().len() + 1
+    }
+
+    pub fn mk_const(&self, v: bool) -> Edge<S> {
+        Edge {
+            node: Rc::clone(&self.terminal),
+            complement: !v,
+        }
+    }
+
+    pub fn var(&self, s: S) -> Edge<S> {
+        self.mk_choice(self.mk_const(true), s, self.mk_const(false))
+    }
+
+    /// Make a new choice based on the given symbol and the true and false edges.
+    /// If the true-edge is complemented, the complement is moved to the resulting edge to keep the choice canonical.
+    pub fn mk_choice(&self, true_edge: Edge<S>, symbol: S, false_edge: Edge<S>) -> Edge<S> {
+        if true_edge == false_edge {
+            return true_edge;
+        }
+
+        if true_edge.complement {
+            return self
+                .mk_choice(true_edge.negate(), symbol, false_edge.negate())
+                .negate();
+        }
+
+        let key = (
+            Rc::as_ptr(&true_edge.node),
+            symbol.clone(),
+            Rc::as_ptr(&false_edge.node),
+            false_edge.complement,
+        );
+
+        let node = Rc::clone(self.nodes.borrow_mut().entry(key).or_insert_with(|| {
+            let mut s = FxHasher::default();
+            s.write_u64(true_edge.get_hash());
+            symbol.hash(&mut s);
+            s.write_u64(false_edge.get_hash());
+            let hash = s.finish();
+
+            Rc::new(CNode::Choice(true_edge, symbol, false_edge, hash))
+        }));
+
+        Edge {
+            node,
+            complement: false,
+        }
+    }
+
+    /// Logic negation, this is a constant-time operation
+    pub fn not(&self, a: &Edge<S>) -> Edge<S> {
+        a.negate()
+    }
+
+    /// Logic conjunction
+    pub fn and(&self, a: &Edge<S>, b: &Edge<S>) -> Edge<S> {
+        if a.is_false() || b.is_false() {
+            return self.mk_const(false);
+        }
+        if a.is_true() || a == b {
+            return b.clone();
+        }
+        if b.is_true() {
+            return a.clone();
+        }
+        if Rc::ptr_eq(&a.node, &b.node) {
+            // a and not a
+            return self.mk_const(false);
+        }
+
+        // both edges point to a choice, split on the smallest symbol
+        let symbol = match (a.symbol(), b.symbol()) {
+            (Some(va), Some(vb)) => va.min(vb).clone(),
+            _ => unreachable!("constants are handled above"),
+        };
+
+        let (at, af) = self.cofactors_at(a, &symbol);
+        let (bt, bf) = self.cofactors_at(b, &symbol);
+
+        self.mk_choice(self.and(&at, &bt), symbol, self.and(&af, &bf))
+    }
+
+    /// Disjunction
+    pub fn or(&self, a: &Edge<S>, b: &Edge<S>) -> Edge<S> {
+        self.and(&a.negate(), &b.negate()).negate()
+    }
+
+    /// Implication
+    pub fn implies(&self, a: &Edge<S>, b: &Edge<S>) -> Edge<S> {
+        self.or(&a.negate(), b)
+    }
+
+    // the cofactors of a with respect to symbol s, where s is not larger than the top symbol of a
+    fn cofactors_at(&self, a: &Edge<S>, s: &S) -> (Edge<S>, Edge<S>) {
+        if a.symbol() == Some(s) {
+            a.cofactors()
+        } else {
+            (a.clone(), a.clone())
+        }
+    }
+
+    /// Convert the function represented by the edge to the standard representation in the given environment.
+    pub fn to_bdd(&self, a: &Edge<S>, env: &BDDEnv<S>) -> Rc<BDD<S>> {
+        let mut cache: FxHashMap<(*const CNode<S>, bool), Rc<BDD<S>>> = FxHashMap::default();
+        Self::to_bdd_recursive(a, env, &mut cache)
+    }
+
+    fn to_bdd_recursive(
+        a: &Edge<S>,
+        env: &BDDEnv<S>,
+        cache: &mut FxHashMap<(*const CNode<S>, bool), Rc<BDD<S>>>,
+    ) -> Rc<BDD<S>> {
+        let key = (Rc::as_ptr(&a.node), a.complement);
+
+        if let Some(result) = cache.get(&key) {
+            return Rc::clone(result);
+        }
+
+        let result = match a.node.as_ref() {
+            CNode::True => env.mk_const(!a.complement),
+            CNode::Choice(_, v, _, _) => {
+                let (t, f) = a.cofactors();
+
+                env.mk_choice(
+                    Self::to_bdd_recursive(&t, env, cache),
+                    v.clone(),
+                    Self::to_bdd_recursive(&f, env, cache),
+                )
+            }
+        };
+
+        cache.insert(key, Rc::clone(&result));
+
+        result
+    }
+
+    /// Convert a bdd in the standard representation to a complemented edge in this environment.
+    pub fn from_bdd(&self, a: &Rc<BDD<S>>) -> Edge<S> {
+        match a.as_ref() {
+            BDD::True => self.mk_const(true),
+            BDD::False => self.mk_const(false),
+            BDD::Choice(t, v, f, _) => {
+                self.mk_choice(self.from_bdd(t), v.clone(), self.from_bdd(f))
+            }
+        }
+    }
+}
+
+// graph nodes are either bdd nodes or the root (None), which points to the node referenced by the root edge
+type GraphEdge<S> = (GraphNode<S>, bool, Edge<S>);
+type GraphNode<S> = Option<Rc<CNode<S>>>;
+
+/// Dot renderer for complemented edges, complemented edges are drawn as dashed lines.
+/// The root edge is rendered from an additional 'root' node.
+pub struct ComplementedGraph<S: BDDSymbol> {
+    root: Edge<S>,
+}
+
+impl<S: BDDSymbol> ComplementedGraph<S> {
+    pub fn render_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        dot::render(self, writer)
+    }
+
+    pub fn new(root: &Edge<S>) -> Self {
+        Self { root: root.clone() }
+    }
+
+    fn collect_nodes(node: &Rc<CNode<S>>, result: &mut Vec<Rc<CNode<S>>>) {
+        if result.iter().any(|n| Rc::ptr_eq(n, node)) {
+            return;
+        }
+
+        result.push(Rc::clone(node));
+
+        if let CNode::Choice(t, _, f, _) = node.as_ref() {
+            Self::collect_nodes(&t.node, result);
+            Self::collect_nodes(&f.node, result);
+        }
+    }
+
+    fn all_nodes(&self) -> Vec<Rc<CNode<S>>> {
+        let mut result = Vec::new();
+        Self::collect_nodes(&self.root.node, &mut result);
+        result
+    }
+}
+
+impl<'a, S: BDDSymbol> dot::Labeller<'a, GraphNode<S>, GraphEdge<S>> for ComplementedGraph<S> {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("bdd_graph").expect("cannot create Id named 'bdd_graph'")
+    }
+
+    fn node_id(&self, n: &GraphNode<S>) -> dot::Id<'a> {
+        match n.as_deref() {
+            None => dot::Id::new("n_root".to_string()).expect("cannot create Id named 'n_root'"),
+            Some(CNode::True) => {
+                dot::Id::new("n_true".to_string()).expect("cannot create Id named 'n_true'")
+            }
+            Some(CNode::Choice(_, _, _, _)) => {
+                let ptr = n.as_ref().map_or(std::ptr::null(), Rc::as_ptr);
+                dot::Id::new(format!("n_{ptr:p}"))
+                    .unwrap_or_else(|_| panic!("cannot create Id named 'n_{ptr:p}'"))
+            }
+        }
+    }
+
+    fn node_label(&self, n: &GraphNode<S>) -> dot::LabelText<'a> {
+        match n.as_deref() {
+            None => dot::LabelText::label("root"),
+            Some(CNode::True) => dot::LabelText::label("true"),
+            Some(CNode::Choice(_, v, _, _)) => dot_label(&v.to_string()),
+        }
+    }
+
+    fn edge_label(&self, (n, e, _): &GraphEdge<S>) -> dot::LabelText<'a> {
+        if n.is_none() {
+            dot::LabelText::LabelStr(Cow::Borrowed(""))
+        } else if *e {
+            dot::LabelText::LabelStr(Cow::Borrowed("T"))
+        } else {
+            dot::LabelText::LabelStr(Cow::Borrowed("F"))
+        }
+    }
+
+    fn edge_style(&self, (_, _, target): &GraphEdge<S>) -> dot::Style {
+        if target.complement {
+            dot::Style::Dashed
+        } else {
+            dot::Style::None
+        }
+    }
+}
+
+impl<'a, S: BDDSymbol> dot::GraphWalk<'a, GraphNode<S>, GraphEdge<S>> for ComplementedGraph<S> {
+    fn nodes(&self) -> dot::Nodes<'a, GraphNode<S>> {
+        std::iter::once(None)
+            .chain(self.all_nodes().into_iter().map(Some))
+            .collect_vec()
+            .into()
+    }
+
+    fn edges(&self) -> dot::Edges<'a, GraphEdge<S>> {
+        let choice_edges = self
+            .all_nodes()
+            .into_iter()
+            .filter_map(|n| match n.as_ref() {
+                CNode::True => None,
+                CNode::Choice(t, _, f, _) => Some([
+                    (Some(Rc::clone(&n)), true, t.clone()),
+                    (Some(Rc::clone(&n)), false, f.clone()),
+                ]),
+            })
+            .flatten();
+
+        std::iter::once((None, true, self.root.clone()))
+            .chain(choice_edges)
+            .collect_vec()
+            .into()
+    }
+
+    fn source(&self, (a, _, _): &GraphEdge<S>) -> GraphNode<S> {
+        a.clone()
+    }
+
+    fn target(&self, (_, _, b): &GraphEdge<S>) -> GraphNode<S> {
+        Some(Rc::clone(&b.node))
+    }
+}
@@ -0,0 +1,119 @@
+use std::io;
+use std::io::BufRead;
+use std::rc::Rc;
+
+use crate::bdd::BDDEnv;
+use crate::parser::*;
+use crate::NamedSymbol;
+
+/// Read a formula in conjunctive normal form from the DIMACS CNF format.
+///
+/// The variables are named after their DIMACS number, with ids in numeric order. Every clause becomes a separate
+/// line of a conjunction, such that e.g. [`ParsedFormula::unsat_core`] reports individual clauses.
+/// Comment lines starting with `c` are ignored, as is everything following a `%` line.
+pub fn read_dimacs(
+    env: Rc<BDDEnv<NamedSymbol>>,
+    contents: &mut dyn BufRead,
+) -> io::Result<ParsedFormula> {
+    let mut vars: Option<Vec<NamedSymbol>> = None;
+    let mut clauses = Vec::new();
+    let mut clause = Vec::new();
+
+    for line in contents.lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line == "c" || line.starts_with("c ") {
+            continue;
+        }
+
+        if line.starts_with('%') {
+            break;
+        }
+
+        let Some(vars) = &vars else {
+            vars = Some(parse_header(line)?);
+            continue;
+        };
+
+        for literal in line.split_whitespace() {
+            let literal: isize = literal
+                .parse()
+                .map_err(|_| invalid_data(format!("cannot parse {literal} as a DIMACS literal")))?;
+
+            if literal == 0 {
+                clauses.push(mk_clause(&clause));
+                clause.clear();
+                continue;
+            }
+
+            let var = vars.get(literal.unsigned_abs() - 1).ok_or_else(|| {
+                invalid_data(format!(
+                    "variable {} exceeds the {} variables declared in the header",
+                    literal.unsigned_abs(),
+                    vars.len()
+                ))
+            })?;
+
+            clause.push((var.clone(), literal > 0));
+        }
+    }
+
+    let vars = vars.ok_or_else(|| invalid_data("missing DIMACS header 'p cnf'".to_string()))?;
+
+    // the final clause does not need to be terminated
+    if !clause.is_empty() {
+        clauses.push(mk_clause(&clause));
+    }
+
+    ParsedFormula::from_parsed(
+        env,
+        vars,
+        Vec::new(),
+        SymbolicBDD::Conjunction(clauses),
+        DomainEncoding::default(),
+    )
+}
+
+// parse the 'p cnf <variables> <clauses>' header into the list of variables
+fn parse_header(line: &str) -> io::Result<Vec<NamedSymbol>> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+
+    let num_vars = match fields[..] {
+        ["p", "cnf", num_vars, num_clauses] if num_clauses.parse::<usize>().is_ok() => {
+            num_vars.parse::<usize>().ok()
+        }
+        _ => None,
+    }
+    .ok_or_else(|| invalid_data(format!("expected DIMACS header 'p cnf', found '{line}'")))?;
+
+    Ok((0..num_vars)
+        .map(|id| NamedSymbol {
+            name: Rc::new((id + 1).to_string()),
+            id,
+        })
+        .collect())
+}
+
+// the disjunction of the literals, the empty clause is false
+fn mk_clause(literals: &[(NamedSymbol, bool)]) -> SymbolicBDD {
+    literals
+        .iter()
+        .rev()
+        .map(|(var, positive)| {
+            let var = SymbolicBDD::Var(var.clone());
+            if *positive {
+                var
+            } else {
+                SymbolicBDD::Not(Box::new(var))
+            }
+        })
+        .reduce(|tail, literal| {
+            SymbolicBDD::BinaryOp(BinaryOperator::Or, Box::new(literal), Box::new(tail))
+        })
+        .unwrap_or(SymbolicBDD::False)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,68 @@
+//! Escaping of variable names for the various output formats.
+//!
+//! Variable names may contain arbitrary characters, e.g. when using quoted identifiers.
+//! All renderers use these functions, such that any name results in valid output.
+
+use std::fmt::Write;
+
+/// Convert a name to an identifier consisting of ASCII letters, digits and underscores, not starting with a digit.
+///
+/// All other characters are replaced by underscores, so distinct names may result in the same identifier.
+pub fn sanitize_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+/// Quote and escape a string for use in json.
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                // writing to a string cannot fail
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Escape the characters which have a special meaning in xml.
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A dot label displaying the text as-is.
+///
+/// The label is written as an html-like label, since regular dot labels escape all non-ASCII characters.
+pub fn dot_label(text: &str) -> dot::LabelText<'static> {
+    dot::LabelText::html(xml_escape(text).replace('\n', "<br/>"))
+}
@@ -3,12 +3,24 @@
 pub use symbols::*;
 pub use truth_table::TruthTableEntry;
 
+pub mod anf;
+#[cfg(feature = "arena")]
+pub mod arena;
 pub mod bdd;
 pub mod bdd_io;
+#[cfg(feature = "complemented_edges")]
+pub mod complemented;
+pub mod dimacs;
+pub mod escape;
+pub mod ordering;
 pub mod parser;
 pub mod parser_io;
 pub mod plot;
+#[cfg(feature = "serde")]
+mod serialize;
 pub mod set;
+pub mod solver;
+pub mod sop;
 
 mod truth_table;
 
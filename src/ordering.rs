@@ -0,0 +1,266 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::parser::*;
+use crate::NamedSymbol;
+
+/// A static variable ordering heuristic, computed from the structure of the parse tree before evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderingHeuristic {
+    /// The order of first appearance in the formula, after any ordering file
+    #[default]
+    Appearance,
+    /// A depth-first traversal of the parse tree, visiting the deepest operand first
+    Dfs,
+    /// The FORCE algorithm, moving variables towards the center of gravity of the subformulas they occur in
+    Force,
+}
+
+impl fmt::Display for OrderingHeuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Appearance => "appearance",
+            Self::Dfs => "dfs",
+            Self::Force => "force",
+        })
+    }
+}
+
+impl FromStr for OrderingHeuristic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "appearance" => Ok(Self::Appearance),
+            "dfs" => Ok(Self::Dfs),
+            "force" => Ok(Self::Force),
+            _ => Err(anyhow::anyhow!("cannot parse {s} as ordering heuristic")),
+        }
+    }
+}
+
+// the maximum number of FORCE iterations, the algorithm usually converges in far fewer
+const FORCE_ITERATIONS: usize = 100;
+
+impl ParsedFormula {
+    /// Compute a variable ordering for this formula using the given heuristic.
+    ///
+    /// The result contains all variables of the formula, and can be applied using [`ParsedFormula::with_order`].
+    pub fn heuristic_order(&self, heuristic: OrderingHeuristic) -> Vec<NamedSymbol> {
+        match heuristic {
+            OrderingHeuristic::Appearance => self.vars.clone(),
+            OrderingHeuristic::Dfs => self.dfs_order(),
+            OrderingHeuristic::Force => self.force_order(),
+        }
+    }
+
+    fn dfs_order(&self) -> Vec<NamedSymbol> {
+        let mut depths = FxHashMap::default();
+        Self::formula_depth(&self.bdd, &mut depths);
+
+        let mut order = Vec::new();
+        let mut seen = FxHashSet::default();
+        self.dfs_recursive(&self.bdd, &depths, &mut order, &mut seen);
+
+        self.complete_order(order)
+    }
+
+    fn dfs_recursive(
+        &self,
+        formula: &SymbolicBDD,
+        depths: &FxHashMap<*const SymbolicBDD, usize>,
+        order: &mut Vec<NamedSymbol>,
+        seen: &mut FxHashSet<usize>,
+    ) {
+        for var in self.own_vars(formula) {
+            if seen.insert(var.id) {
+                order.push(var);
+            }
+        }
+
+        // visit the deepest operand first, the sort is stable so equally deep operands keep their order
+        let mut operands = operands(formula);
+        operands.sort_by_key(|f| std::cmp::Reverse(depths.get(&(*f as *const _)).copied()));
+
+        for operand in operands {
+            self.dfs_recursive(operand, depths, order, seen);
+        }
+    }
+
+    fn formula_depth(
+        formula: &SymbolicBDD,
+        depths: &mut FxHashMap<*const SymbolicBDD, usize>,
+    ) -> usize {
+        let depth = operands(formula)
+            .into_iter()
+            .map(|f| Self::formula_depth(f, depths) + 1)
+            .max()
+            .unwrap_or_default();
+
+        depths.insert(formula as *const _, depth);
+        depth
+    }
+
+    // iteratively place every variable at the average center of gravity of the hyperedges it occurs in
+    fn force_order(&self) -> Vec<NamedSymbol> {
+        let mut edges: Vec<Vec<usize>> = Vec::new();
+        self.hyperedges(&self.bdd, true, &mut edges);
+
+        let vars = &self.vars;
+        let index: FxHashMap<usize, usize> =
+            vars.iter().enumerate().map(|(i, v)| (v.id, i)).collect();
+        let edges: Vec<Vec<usize>> = edges
+            .into_iter()
+            .map(|edge| {
+                edge.iter()
+                    .filter_map(|id| index.get(id).copied())
+                    .collect()
+            })
+            .filter(|edge: &Vec<usize>| edge.len() > 1)
+            .collect();
+
+        // the position of every variable, indexed by its index in vars
+        let mut positions: Vec<usize> = (0..vars.len()).collect();
+        let mut best = (span(&edges, &positions), positions.clone());
+
+        for _ in 0..FORCE_ITERATIONS {
+            let mut sum = vec![0.0; vars.len()];
+            let mut count = vec![0usize; vars.len()];
+
+            for edge in &edges {
+                let cog =
+                    edge.iter().map(|&v| positions[v] as f64).sum::<f64>() / edge.len() as f64;
+
+                for &v in edge {
+                    sum[v] += cog;
+                    count[v] += 1;
+                }
+            }
+
+            let targets: Vec<f64> = (0..vars.len())
+                .map(|v| {
+                    if count[v] == 0 {
+                        positions[v] as f64
+                    } else {
+                        sum[v] / count[v] as f64
+                    }
+                })
+                .collect();
+
+            let mut ranked: Vec<usize> = (0..vars.len()).collect();
+            ranked.sort_by(|&a, &b| {
+                targets[a]
+                    .total_cmp(&targets[b])
+                    .then_with(|| positions[a].cmp(&positions[b]))
+            });
+
+            let mut next = vec![0; vars.len()];
+            for (position, &v) in ranked.iter().enumerate() {
+                next[v] = position;
+            }
+
+            if next == positions {
+                break;
+            }
+
+            positions = next;
+
+            let next_span = span(&edges, &positions);
+            if next_span < best.0 {
+                best = (next_span, positions.clone());
+            }
+        }
+
+        let mut order: Vec<NamedSymbol> = vars.clone();
+        order.sort_by_key(|v| best.1[index[&v.id]]);
+        order
+    }
+
+    // collect the variable ids of every subformula as a hyperedge, except for the conjunctions at the root,
+    // which combine independent constraints; returns the variable ids of the formula
+    fn hyperedges(
+        &self,
+        formula: &SymbolicBDD,
+        root: bool,
+        edges: &mut Vec<Vec<usize>>,
+    ) -> BTreeSet<usize> {
+        let conjunction = matches!(
+            formula,
+            SymbolicBDD::Conjunction(_) | SymbolicBDD::BinaryOp(BinaryOperator::And, _, _)
+        );
+
+        let mut ids: BTreeSet<usize> = self.own_vars(formula).iter().map(|v| v.id).collect();
+        for operand in operands(formula) {
+            ids.extend(self.hyperedges(operand, root && conjunction, edges));
+        }
+
+        if !(root && conjunction) {
+            edges.push(ids.iter().copied().collect());
+        }
+
+        ids
+    }
+
+    // the variables occurring in the formula node itself, excluding those of its operands
+    fn own_vars(&self, formula: &SymbolicBDD) -> Vec<NamedSymbol> {
+        match formula {
+            SymbolicBDD::Var(v) | SymbolicBDD::FixedPoint(v, _, _) => vec![v.clone()],
+            SymbolicBDD::Quantifier(_, vars, _) => vars.clone(),
+            SymbolicBDD::Subtree(bdd) => self.env.support(bdd),
+            _ => Vec::new(),
+        }
+    }
+
+    // append all variables which do not occur in the order, in their current order
+    fn complete_order(&self, mut order: Vec<NamedSymbol>) -> Vec<NamedSymbol> {
+        let present: FxHashSet<usize> = order.iter().map(|v| v.id).collect();
+        order.extend(
+            self.vars
+                .iter()
+                .filter(|v| !present.contains(&v.id))
+                .cloned(),
+        );
+        order
+    }
+}
+
+// the operands of a formula node, in order of appearance
+fn operands(formula: &SymbolicBDD) -> Vec<&SymbolicBDD> {
+    match formula {
+        SymbolicBDD::Not(f)
+        | SymbolicBDD::Quantifier(_, _, f)
+        | SymbolicBDD::FixedPoint(_, _, f) => {
+            vec![f]
+        }
+        SymbolicBDD::CountableConst(_, list, _)
+        | SymbolicBDD::WeightedCountable(_, _, list, _)
+        | SymbolicBDD::Conjunction(list)
+        | SymbolicBDD::NaryOp(_, list) => list.iter().collect(),
+        SymbolicBDD::CountableVariable(_, l, r, _) => l.iter().chain(r.iter()).collect(),
+        SymbolicBDD::Ite(a, b, c) => vec![a, b, c],
+        SymbolicBDD::BinaryOp(_, a, b) => vec![a, b],
+        SymbolicBDD::False
+        | SymbolicBDD::True
+        | SymbolicBDD::Var(_)
+        | SymbolicBDD::Subtree(_)
+        | SymbolicBDD::Reference(_)
+        | SymbolicBDD::DomainEq(_, _, _) => Vec::new(),
+    }
+}
+
+// the total span of all hyperedges, the objective minimized by FORCE
+fn span(edges: &[Vec<usize>], positions: &[usize]) -> usize {
+    edges
+        .iter()
+        .map(|edge| {
+            let (min, max) = edge
+                .iter()
+                .map(|&v| positions[v])
+                .fold((usize::MAX, 0), |(min, max), p| (min.min(p), max.max(p)));
+            max - min
+        })
+        .sum()
+}
@@ -1,22 +1,38 @@
 use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
 use std::io;
 use std::io::BufRead;
-use std::iter::Peekable;
+use std::ops::RangeInclusive;
 use std::rc::Rc;
-use std::slice::Iter;
+use std::str::FromStr;
 use std::string::String;
 use std::vec::Vec;
 
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::bdd::{BDDEnv, BDD};
-use crate::NamedSymbol;
+use crate::truth_table::TruthTableRows;
+use crate::{NamedSymbol, TruthTableEntry};
+
+/// The largest number accepted in a formula, larger numbers result in a parse error.
+pub const MAX_NUMBER: usize = u32::MAX as usize;
+
+/// The maximum nesting depth of parentheses, negations, quantifiers and other operators in a formula.
+///
+/// Deeper formulas result in a parse error rather than exhausting the stack. Chains of the same associative binary
+/// operator, e.g. `a & b & c`, do not count towards the nesting depth, but every change of operator within a chain does.
+pub const MAX_NESTING_DEPTH: usize = 256;
+
+/// The maximum number of variables generated by a single `@range(prefix, lo, hi)`, longer ranges result in a parse
+/// error.
+pub const MAX_RANGE_LENGTH: usize = 1 << 16;
 
 lazy_static! {
-    static ref TOKENIZER: Regex = Regex::new(r#"(?P<symbol>!|&|=>|-|<=>|<=|\||\^|#|\*|\+|>=|=|>|<|\[|\]|,|\(|\))|(?P<countable>\d+)|\{(?P<reference>[\w']+)\}|(?P<identifier>[\w']+)|(?P<eof>$)|(?P<comment>"[^"]*")"#).expect("Error setting-up tokenizer regex");
+    static ref TOKENIZER: Regex = Regex::new(r#"@range\(\s*(?P<range_prefix>[\w']+)\s*,\s*(?P<range_lo>\d+)\s*,\s*(?P<range_hi>\d+)\s*\)|(?P<domain>var)[ \t]+(?P<domain_name>\w[\w']*|`[^`\n]+`|'[^'\n]+')|(?P<builtin>parity|majority|all_eq)[ \t]*\(|(?P<when>when)[ \t]+(?P<when_next>`[^`\n]+`|'[^'\n]+'|\{[\w']+\}|[\w']+|!=|[(!¬\[∀∃-])|(?P<symbol>!=|!|&|=>|∧|∨|¬|→|↔|⊕|∀|∃|-|<=>|<=|\||\^|#|\*|\+|>=|=|>|<|\[|\]|,|\(|\)|\.\.|;)|(?P<countable>\d+)|\{(?P<reference>[\w']+)\}|`(?P<backquoted>[^`\n]+)`|'(?P<quoted>[^'\n]+)'|(?P<identifier>[\w']+)|(?P<newline>\n)|(?P<eof>$)|(?P<comment>"[^"]*")"#).expect("Error setting-up tokenizer regex");
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -34,11 +50,13 @@ pub enum SymbolicBDDToken {
     ImpliesInv,
     Iff,
     If,
+    When,
     Then,
     Else,
     Exists,
     Forall,
     Eq,
+    NotEq,
     Geq,
     Gt,
     Lt,
@@ -52,10 +70,22 @@ pub enum SymbolicBDDToken {
     LFP,
     GFP,
     Hash,
+    Domain,
+    In,
+    Plus,
+    Minus,
+    Star,
+    Range,
+    Semicolon,
+    Parity,
+    Majority,
+    AllEq,
+    Newline,
     Eof,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOperator {
     And,
     Or,
@@ -68,35 +98,362 @@ pub enum BinaryOperator {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CountableOperator {
     AtMost,
     LessThan,
     AtLeast,
     MoreThan,
     Exactly,
+    NotEqual,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NaryOperator {
+    Parity,
+    Majority,
+    AllEq,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QuantifierType {
     Exists,
     Forall,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SymbolicBDD {
     False,
     True,
     Var(NamedSymbol),
-    Not(Box<SymbolicBDD>),
-    Quantifier(QuantifierType, Vec<NamedSymbol>, Box<SymbolicBDD>),
-    CountableConst(CountableOperator, Vec<SymbolicBDD>, usize),
-    CountableVariable(CountableOperator, Vec<SymbolicBDD>, Vec<SymbolicBDD>),
+    Not(Box<Self>),
+    Quantifier(QuantifierType, Vec<NamedSymbol>, Box<Self>),
+    CountableConst(CountableOperator, Vec<Self>, usize),
+    // the comparison of the weighted sum of a list with a constant, with a weight per element of the list
+    WeightedCountable(CountableOperator, Vec<usize>, Vec<Self>, usize),
+    // the countable comparison between two lists, with a constant offset added to the count of the right list
+    CountableVariable(CountableOperator, Vec<Self>, Vec<Self>, i64),
     // the fixed-point operator with a single transformer variable, initial value (as bool), and the transformer function as a symbolic bdd description
-    FixedPoint(NamedSymbol, bool, Box<SymbolicBDD>),
-    Ite(Box<SymbolicBDD>, Box<SymbolicBDD>, Box<SymbolicBDD>),
-    BinaryOp(BinaryOperator, Box<SymbolicBDD>, Box<SymbolicBDD>),
+    FixedPoint(NamedSymbol, bool, Box<Self>),
+    Ite(Box<Self>, Box<Self>, Box<Self>),
+    BinaryOp(BinaryOperator, Box<Self>, Box<Self>),
+    // a builtin function of a list of formulas, e.g. 'parity(a, b, c)'
+    NaryOp(NaryOperator, Vec<Self>),
     Subtree(Rc<BDD<NamedSymbol>>),
     Reference(String),
+    // the comparison of a domain variable (with an optional index) with a constant value, expanded to boolean variables before evaluation
+    DomainEq(NamedSymbol, Option<usize>, usize),
+    // the conjunction of a list of independent formulas, e.g. the lines of a file parsed in conjunction mode
+    Conjunction(Vec<Self>),
+}
+
+/// A multi-valued variable, declared as `var name in lo..hi;`.
+/// Arrays of domain variables (`var name[a..b] in lo..hi;`) result in a declaration per index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DomainDeclaration {
+    pub symbol: NamedSymbol,
+    pub index: Option<usize>,
+    pub values: RangeInclusive<usize>,
+}
+
+impl DomainDeclaration {
+    /// The name of the domain variable as used in the formula, e.g. `cell[0]`
+    pub fn name(&self) -> String {
+        self.index.map_or_else(
+            || self.symbol.to_string(),
+            |index| format!("{}[{}]", self.symbol, index),
+        )
+    }
+
+    fn matches(&self, symbol: &NamedSymbol, index: Option<usize>) -> bool {
+        self.symbol.name == symbol.name && self.index == index
+    }
+}
+
+/// The encoding of multi-valued (domain) variables as boolean variables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DomainEncoding {
+    /// A boolean variable per value, with an implicit exactly-one constraint
+    #[default]
+    OneHot,
+    /// A boolean variable per bit of the (zero-based) index of the value, with an implicit range constraint
+    Log,
+}
+
+impl fmt::Display for DomainEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::OneHot => "one-hot",
+            Self::Log => "log",
+        })
+    }
+}
+
+impl FromStr for DomainEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "one-hot" | "onehot" => Ok(Self::OneHot),
+            "log" | "binary" => Ok(Self::Log),
+            _ => Err(anyhow::anyhow!("cannot parse {s} as domain encoding")),
+        }
+    }
+}
+
+impl fmt::Display for CountableOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::AtMost => "<=",
+            Self::LessThan => "<",
+            Self::AtLeast => ">=",
+            Self::MoreThan => ">",
+            Self::Exactly => "=",
+            Self::NotEqual => "!=",
+        })
+    }
+}
+
+/// A likely modeling mistake found by [`ParsedFormula::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A countable constraint over an empty list of formulas
+    EmptyList {
+        operator: CountableOperator,
+        threshold: usize,
+    },
+    /// A countable constraint with a threshold larger than the number of formulas in the list, or a `>` constraint with
+    /// a threshold equal to it, which reduces to a constant
+    ThresholdExceedsLength {
+        operator: CountableOperator,
+        threshold: usize,
+        length: usize,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyList {
+                operator,
+                threshold,
+            } => write!(
+                f,
+                "countable constraint [] {operator} {threshold} has an empty list"
+            ),
+            Self::ThresholdExceedsLength {
+                operator,
+                threshold,
+                length,
+            } => {
+                // with more than length true formulas being impossible, the constraint reduces to a constant
+                let constant = matches!(
+                    operator,
+                    CountableOperator::AtMost
+                        | CountableOperator::LessThan
+                        | CountableOperator::NotEqual
+                );
+
+                write!(
+                    f,
+                    "countable constraint {operator} {threshold} exceeds the list length {length}, the constraint is always {constant}"
+                )
+            }
+        }
+    }
+}
+
+impl BinaryOperator {
+    /// Whether `(a op b) op c` is equivalent to `a op (b op c)`.
+    pub const fn is_associative(self) -> bool {
+        matches!(self, Self::And | Self::Or | Self::Xor | Self::Iff)
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::And => "&",
+            Self::Or => "|",
+            Self::Xor => "^",
+            Self::Nor => "nor",
+            Self::Nand => "nand",
+            Self::Implies => "=>",
+            Self::ImpliesInv => "<=",
+            Self::Iff => "<=>",
+        })
+    }
+}
+
+impl fmt::Display for NaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Parity => "parity",
+            Self::Majority => "majority",
+            Self::AllEq => "all_eq",
+        })
+    }
+}
+
+impl fmt::Display for QuantifierType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Exists => "exists",
+            Self::Forall => "forall",
+        })
+    }
+}
+
+/// Render the formula in rsbdd syntax, such that parsing the result yields an equivalent parse tree.
+///
+/// Binary operators are right-associative, so only left operands and negated formulas are parenthesized when needed.
+/// Variable names which would not be parsed as an identifier are delimited by backticks or single quotes.
+/// Evaluated subtrees are rendered as nested if-then-else expressions.
+impl fmt::Display for SymbolicBDD {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::False => write!(f, "false"),
+            Self::True => write!(f, "true"),
+            Self::Var(var) => write_name(f, &var.name),
+            Self::Not(sub) => {
+                write!(f, "!")?;
+                sub.fmt_operand(f)
+            }
+            Self::Quantifier(quantifier, vars, sub) => {
+                write!(f, "{quantifier} ")?;
+                for (i, var) in vars.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_name(f, &var.name)?;
+                }
+                write!(f, " # {sub}")
+            }
+            Self::CountableConst(operator, list, count) => {
+                write!(f, "[{}] {operator} {count}", list.iter().format(", "))
+            }
+            Self::WeightedCountable(operator, weights, list, count) => {
+                write!(f, "[")?;
+                for (i, (weight, sub)) in weights.iter().zip(list).enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{weight} * {sub}")?;
+                }
+                write!(f, "] {operator} {count}")
+            }
+            Self::CountableVariable(operator, left, right, offset) => {
+                write!(
+                    f,
+                    "[{}] {operator} [{}]",
+                    left.iter().format(", "),
+                    right.iter().format(", ")
+                )?;
+
+                match offset.cmp(&0) {
+                    std::cmp::Ordering::Greater => write!(f, " + {offset}"),
+                    std::cmp::Ordering::Less => write!(f, " - {}", offset.unsigned_abs()),
+                    std::cmp::Ordering::Equal => Ok(()),
+                }
+            }
+            Self::FixedPoint(var, initial, sub) => {
+                write!(f, "{} ", if *initial { "gfp" } else { "lfp" })?;
+                write_name(f, &var.name)?;
+                write!(f, " # {sub}")
+            }
+            Self::Ite(cond, then, else_) => write!(f, "if {cond} then {then} else {else_}"),
+            Self::BinaryOp(op, left, right) => {
+                left.fmt_operand(f)?;
+                write!(f, " {op} {right}")
+            }
+            Self::NaryOp(op, list) => write!(f, "{op}({})", list.iter().format(", ")),
+            Self::Subtree(bdd) => write_subtree(f, bdd),
+            Self::Reference(name) => write!(f, "{{{name}}}"),
+            Self::DomainEq(var, index, value) => {
+                write_name(f, &var.name)?;
+                if let Some(index) = index {
+                    write!(f, "[{index}]")?;
+                }
+                write!(f, " = {value}")
+            }
+            Self::Conjunction(formulas) => match formulas.split_last() {
+                None => write!(f, "true"),
+                Some((last, init)) => {
+                    for formula in init {
+                        formula.fmt_operand(f)?;
+                        write!(f, " & ")?;
+                    }
+                    write!(f, "{last}")
+                }
+            },
+        }
+    }
+}
+
+// write a variable name, quoted if it would otherwise be read as a keyword, number, or multiple tokens
+fn write_name(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    let plain = name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_numeric())
+        && SymbolicBDD::keyword(name).is_none();
+
+    if plain {
+        write!(f, "{name}")
+    } else if name.contains('`') {
+        write!(f, "'{name}'")
+    } else {
+        write!(f, "`{name}`")
+    }
+}
+
+// write an evaluated subtree as nested if-then-else expressions
+fn write_subtree(f: &mut fmt::Formatter<'_>, bdd: &BDD<NamedSymbol>) -> fmt::Result {
+    match bdd {
+        BDD::False => write!(f, "false"),
+        BDD::True => write!(f, "true"),
+        BDD::Choice(t, v, e, _) => {
+            write!(f, "(if ")?;
+            write_name(f, &v.name)?;
+            write!(f, " then ")?;
+            write_subtree(f, t)?;
+            write!(f, " else ")?;
+            write_subtree(f, e)?;
+            write!(f, ")")
+        }
+    }
+}
+
+/// Specific parse errors, reported as the inner error of an [`io::Error`] of kind [`io::ErrorKind::InvalidData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input contains no formula, e.g. it is empty or only contains whitespace and comments.
+    EmptyInput,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyInput => write!(
+                f,
+                "the input does not contain a formula, use 'true' or 'false' for a constant formula"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Options controlling how an input is tokenized and parsed into a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// The boolean encoding of domain variables.
+    pub encoding: DomainEncoding,
+    /// Parse every line as a separate formula and take the conjunction of all lines.
+    pub conjunction: bool,
+    /// Assign variable ids in order of the sorted variable names instead of the order of first occurrence.
+    pub sort_vars: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -113,6 +470,12 @@ pub struct ParsedFormula {
     pub env: Rc<BDDEnv<NamedSymbol>>,
 
     pub definitions: RefCell<FxHashMap<String, ReferenceContents>>,
+    // all declared domain variables
+    pub domains: Vec<DomainDeclaration>,
+    // report the iteration number and node count of every fixed-point iteration to stderr
+    pub fp_progress: bool,
+    // the comments preceding the formula, e.g. the provenance written by a generator, without their quotes
+    pub metadata: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -121,8 +484,6 @@ pub enum ReferenceContents {
     BDD(Rc<BDD<NamedSymbol>>),
 }
 
-type TokenReader<'a> = Peekable<Iter<'a, SymbolicBDDToken>>;
-
 impl ParsedFormula {
     /// Define a new BDD by name
     pub fn define(&self, name: &str, contents: ReferenceContents) {
@@ -187,7 +548,26 @@ impl ParsedFormula {
                     .collect(),
                 *sz,
             ),
-            SymbolicBDD::CountableVariable(op, l, r) => SymbolicBDD::CountableVariable(
+            SymbolicBDD::WeightedCountable(op, w, n, sz) => SymbolicBDD::WeightedCountable(
+                *op,
+                w.clone(),
+                n.iter()
+                    .map(|v| self.replace_var(v, var, replacement))
+                    .collect(),
+                *sz,
+            ),
+            SymbolicBDD::Conjunction(n) => SymbolicBDD::Conjunction(
+                n.iter()
+                    .map(|v| self.replace_var(v, var, replacement))
+                    .collect(),
+            ),
+            SymbolicBDD::NaryOp(op, n) => SymbolicBDD::NaryOp(
+                *op,
+                n.iter()
+                    .map(|v| self.replace_var(v, var, replacement))
+                    .collect(),
+            ),
+            SymbolicBDD::CountableVariable(op, l, r, k) => SymbolicBDD::CountableVariable(
                 *op,
                 l.iter()
                     .map(|v| self.replace_var(v, var, replacement))
@@ -195,6 +575,7 @@ impl ParsedFormula {
                 r.iter()
                     .map(|v| self.replace_var(v, var, replacement))
                     .collect(),
+                *k,
             ),
             SymbolicBDD::Reference(name) => self.get_definition(name).map_or_else(
                 || formula.clone(),
@@ -210,7 +591,8 @@ impl ParsedFormula {
             SymbolicBDD::True
             | SymbolicBDD::False
             | SymbolicBDD::Subtree(_)
-            | SymbolicBDD::Var(_) => formula.clone(),
+            | SymbolicBDD::Var(_)
+            | SymbolicBDD::DomainEq(_, _, _) => formula.clone(),
         }
     }
 
@@ -218,6 +600,18 @@ impl ParsedFormula {
         self.raw2free[ns.id].unwrap_or_else(|| panic!("{} is not a free variable", ns))
     }
 
+    /// Enumerate the rows of the truth table of root, as the assignment of the free variables and the result of every
+    /// path to a terminal. The entries of the assignment follow the order of free_vars, which must contain all
+    /// variables of root. Variables skipped on a path are [`TruthTableEntry::Any`], and the false branch of every
+    /// choice is visited first.
+    pub fn truth_table_rows(
+        &self,
+        root: &Rc<BDD<NamedSymbol>>,
+        free_vars: &[NamedSymbol],
+    ) -> impl Iterator<Item = (Vec<TruthTableEntry>, TruthTableEntry)> {
+        TruthTableRows::new(root, free_vars)
+    }
+
     pub fn extract_vars(tokens: &[SymbolicBDDToken]) -> Vec<NamedSymbol> {
         tokens
             .iter()
@@ -236,46 +630,835 @@ impl ParsedFormula {
         Self::new_with_env(Rc::new(BDDEnv::new()), contents, variable_ordering)
     }
 
+    /// Parse a formula from a string, reporting malformed input as error.
+    ///
+    /// Parsing does not panic on any input: numbers exceeding [`MAX_NUMBER`] and formulas nested deeper than
+    /// [`MAX_NESTING_DEPTH`] are rejected. Chains of the same associative operator are folded into a balanced tree,
+    /// while every change of operator within a chain counts as a level of nesting.
+    pub fn try_parse(formula: &str) -> io::Result<Self> {
+        Self::new(&mut formula.as_bytes(), None)
+    }
+
     pub fn new_with_env(
         env: Rc<BDDEnv<NamedSymbol>>,
         contents: &mut dyn BufRead,
         variable_ordering: Option<Vec<NamedSymbol>>,
     ) -> io::Result<Self> {
-        let tokens = SymbolicBDD::tokenize(contents, variable_ordering)?;
+        Self::new_with_encoding(env, contents, variable_ordering, DomainEncoding::default())
+    }
+
+    /// Parse a formula, where the declared domain variables are expanded to boolean variables using the given encoding.
+    pub fn new_with_encoding(
+        env: Rc<BDDEnv<NamedSymbol>>,
+        contents: &mut dyn BufRead,
+        variable_ordering: Option<Vec<NamedSymbol>>,
+        encoding: DomainEncoding,
+    ) -> io::Result<Self> {
+        Self::new_with_options(
+            env,
+            contents,
+            variable_ordering,
+            ParseOptions {
+                encoding,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Parse a formula using the given parse options.
+    pub fn new_with_options(
+        env: Rc<BDDEnv<NamedSymbol>>,
+        contents: &mut dyn BufRead,
+        variable_ordering: Option<Vec<NamedSymbol>>,
+        options: ParseOptions,
+    ) -> io::Result<Self> {
+        if options.conjunction {
+            return Self::parse_conjunction(env, contents, variable_ordering, options);
+        }
+
+        // sorting the variables requires all names up front, which rules out streaming
+        if options.sort_vars {
+            let (tokens, metadata) =
+                SymbolicBDD::tokenize_impl(contents, variable_ordering, false, true)?;
+
+            let mut token_reader = TokenReader::new(&tokens);
+            let domains = SymbolicBDD::parse_domain_declarations(&mut token_reader)?;
+            let formula = SymbolicBDD::parse_formula(&mut token_reader)?;
+
+            let mut parsed = Self::from_parsed(
+                env,
+                Self::extract_vars(&tokens),
+                domains,
+                formula,
+                options.encoding,
+            )?;
+            parsed.metadata = metadata;
+
+            return Ok(parsed);
+        }
+
+        let mut stream = TokenStream::new(contents, variable_ordering);
+
+        let mut token_reader = TokenReader::from_source(&mut stream);
+        let parsed = SymbolicBDD::parse_domain_declarations(&mut token_reader)
+            .and_then(|domains| Ok((domains, SymbolicBDD::parse_formula(&mut token_reader)?)));
+        let (domains, formula) = token_reader.finish(parsed)?;
+
+        let mut parsed = Self::from_parsed(
+            env,
+            stream.vars().to_vec(),
+            domains,
+            formula,
+            options.encoding,
+        )?;
+        parsed.metadata = stream.metadata().to_vec();
+
+        Ok(parsed)
+    }
+
+    /// Parse every non-empty line as an independent formula, the result is the conjunction of all lines.
+    /// Domain declarations must precede the formulas, each on its own line(s).
+    /// The whole input is tokenized at once, such that variable ids are consistent across lines.
+    pub fn new_conjunction(
+        env: Rc<BDDEnv<NamedSymbol>>,
+        contents: &mut dyn BufRead,
+        variable_ordering: Option<Vec<NamedSymbol>>,
+        encoding: DomainEncoding,
+    ) -> io::Result<Self> {
+        Self::parse_conjunction(
+            env,
+            contents,
+            variable_ordering,
+            ParseOptions {
+                encoding,
+                conjunction: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Parse a list of independent formulas separated by `;`, after the domain declarations.
+    /// Every statement results in its own parsed formula, which share the environment and variable ids.
+    pub fn new_statements(
+        env: Rc<BDDEnv<NamedSymbol>>,
+        contents: &mut dyn BufRead,
+        variable_ordering: Option<Vec<NamedSymbol>>,
+        options: ParseOptions,
+    ) -> io::Result<Vec<Self>> {
+        let (tokens, metadata) =
+            SymbolicBDD::tokenize_impl(contents, variable_ordering, false, options.sort_vars)?;
+
+        let mut token_reader = TokenReader::new(&tokens);
+        let domains = SymbolicBDD::parse_domain_declarations(&mut token_reader)?;
+        let statements = SymbolicBDD::parse_statements(&mut token_reader)?;
+        let vars = Self::extract_vars(&tokens);
+
+        // the leading comments of the input apply to every statement
+        statements
+            .into_iter()
+            .map(|formula| {
+                let mut parsed = Self::from_parsed(
+                    Rc::clone(&env),
+                    vars.clone(),
+                    domains.clone(),
+                    formula,
+                    options.encoding,
+                )?;
+                parsed.metadata = metadata.clone();
+                Ok(parsed)
+            })
+            .collect()
+    }
 
-        let mut vars: Vec<NamedSymbol> = Self::extract_vars(&tokens);
-        vars.sort_by(|a, b| a.id.cmp(&b.id));
+    fn parse_conjunction(
+        env: Rc<BDDEnv<NamedSymbol>>,
+        contents: &mut dyn BufRead,
+        variable_ordering: Option<Vec<NamedSymbol>>,
+        options: ParseOptions,
+    ) -> io::Result<Self> {
+        let (tokens, metadata) =
+            SymbolicBDD::tokenize_impl(contents, variable_ordering, true, options.sort_vars)?;
+
+        let mut lines = tokens
+            .split(|t| matches!(t, SymbolicBDDToken::Newline | SymbolicBDDToken::Eof))
+            .filter(|line| !line.is_empty())
+            .peekable();
+
+        // the leading lines starting with 'var' contain the domain declarations
+        let mut declarations: Vec<SymbolicBDDToken> = Vec::new();
+        while let Some(line) = lines.next_if(|line| line[0] == SymbolicBDDToken::Domain) {
+            declarations.extend_from_slice(line);
+        }
+        declarations.push(SymbolicBDDToken::Eof);
+
+        let mut token_reader = TokenReader::new(&declarations);
+        let domains = SymbolicBDD::parse_domain_declarations(&mut token_reader)?;
+        expect(SymbolicBDDToken::Eof, &mut token_reader)?;
+
+        let formulas = lines
+            .map(|line| {
+                let line: Vec<SymbolicBDDToken> = line
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(SymbolicBDDToken::Eof))
+                    .collect();
+
+                let mut token_reader = TokenReader::new(&line);
+                SymbolicBDD::parse_formula(&mut token_reader)
+            })
+            .collect::<io::Result<Vec<SymbolicBDD>>>()?;
 
-        let formula = SymbolicBDD::parse_formula(&mut tokens.iter().peekable())?;
+        let mut parsed = Self::from_parsed(
+            env,
+            Self::extract_vars(&tokens),
+            domains,
+            SymbolicBDD::Conjunction(formulas),
+            options.encoding,
+        )?;
+        parsed.metadata = metadata;
+
+        Ok(parsed)
+    }
+
+    // construct the parsed formula from the variables and the parsed domains and formula, expanding the domain variables
+    pub(crate) fn from_parsed(
+        env: Rc<BDDEnv<NamedSymbol>>,
+        mut vars: Vec<NamedSymbol>,
+        domains: Vec<DomainDeclaration>,
+        formula: SymbolicBDD,
+        encoding: DomainEncoding,
+    ) -> io::Result<Self> {
+        vars.sort_by_key(|a| a.id);
 
-        let n = vars.len();
         let mut result = Self {
             vars,
             free_vars: Vec::new(),
-            raw2free: Vec::with_capacity(n),
+            raw2free: Vec::new(),
             bdd: formula,
             env,
             definitions: Default::default(),
+            domains,
+            fp_progress: false,
+            metadata: Vec::new(),
         };
 
-        let mut vi = 0;
-        for v in &result.vars {
-            result.raw2free.push(if result.var_is_free(&result.bdd, v) {
-                result.free_vars.push(v.clone());
-                let v_result = vi;
-                vi += 1;
+        result.expand_domains(encoding)?;
 
-                Some(v_result)
-            } else {
-                None
+        result.update_free_vars();
+
+        Ok(result)
+    }
+
+    // replace all domain variables by their boolean encoding, and add the implicit constraints of every domain
+    // the domain names are removed from the variable list, and all variables are re-numbered to keep the ids contiguous
+    fn expand_domains(&mut self, encoding: DomainEncoding) -> io::Result<()> {
+        // without declarations there is nothing to expand, but every domain reference is undeclared
+        if self.domains.is_empty() {
+            return self.expand_domain_refs(&self.bdd, encoding, &[]).map(drop);
+        }
+
+        let mut next_id = self.vars.iter().map(|v| v.id + 1).max().unwrap_or_default();
+
+        let mut encoded: Vec<Vec<NamedSymbol>> = Vec::with_capacity(self.domains.len());
+        for domain in &self.domains {
+            let n = domain.values.clone().count();
+            let names: Vec<String> = match encoding {
+                DomainEncoding::OneHot => domain
+                    .values
+                    .clone()
+                    .map(|v| format!("{}={}", domain.name(), v))
+                    .collect(),
+                DomainEncoding::Log => (0..(usize::BITS - (n - 1).leading_zeros()))
+                    .map(|bit| format!("{}.{}", domain.name(), bit))
+                    .collect(),
+            };
+
+            encoded.push(
+                names
+                    .into_iter()
+                    .map(|name| {
+                        next_id += 1;
+                        NamedSymbol {
+                            name: Rc::new(name),
+                            id: next_id - 1,
+                        }
+                    })
+                    .collect(),
+            );
+        }
+
+        let expanded = self.expand_domain_refs(&self.bdd, encoding, &encoded)?;
+
+        if let Some(domain) = self
+            .domains
+            .iter()
+            .find(|d| self.var_is_free(&expanded, &d.symbol))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Domain variable {} cannot be used as a boolean variable",
+                    domain.symbol
+                ),
+            ));
+        }
+
+        // the implicit constraints of all domains are added in front of the formula
+        let constraints = self
+            .domains
+            .iter()
+            .zip(&encoded)
+            .map(|(domain, bits)| match encoding {
+                DomainEncoding::OneHot => SymbolicBDD::CountableConst(
+                    CountableOperator::Exactly,
+                    bits.iter().cloned().map(SymbolicBDD::Var).collect(),
+                    1,
+                ),
+                DomainEncoding::Log if domain.values.clone().count().is_power_of_two() => {
+                    SymbolicBDD::True
+                }
+                DomainEncoding::Log => domain
+                    .values
+                    .clone()
+                    .map(|v| Self::encode_value(domain, encoding, bits, v))
+                    .reduce(|a, b| {
+                        SymbolicBDD::BinaryOp(BinaryOperator::Or, Box::new(a), Box::new(b))
+                    })
+                    .unwrap_or(SymbolicBDD::False),
             });
+
+        let formula = constraints.rev().fold(expanded, |acc, c| {
+            SymbolicBDD::BinaryOp(BinaryOperator::And, Box::new(c), Box::new(acc))
+        });
+
+        let domain_names: FxHashSet<&Rc<String>> =
+            self.domains.iter().map(|d| &d.symbol.name).collect();
+
+        let vars: Vec<NamedSymbol> = self
+            .vars
+            .iter()
+            .filter(|v| !domain_names.contains(&v.name))
+            .chain(encoded.iter().flatten())
+            .cloned()
+            .collect();
+
+        let new_ids: FxHashMap<usize, usize> =
+            vars.iter().enumerate().map(|(i, v)| (v.id, i)).collect();
+
+        self.bdd = self.remap_vars(&formula, &new_ids)?;
+        self.vars = vars.iter().map(|v| Self::remap_var(v, &new_ids)).collect();
+
+        Ok(())
+    }
+
+    // the boolean formula representing the domain variable having the given value
+    fn encode_value(
+        domain: &DomainDeclaration,
+        encoding: DomainEncoding,
+        bits: &[NamedSymbol],
+        value: usize,
+    ) -> SymbolicBDD {
+        let offset = value - domain.values.start();
+
+        match encoding {
+            DomainEncoding::OneHot => SymbolicBDD::Var(bits[offset].clone()),
+            DomainEncoding::Log => bits
+                .iter()
+                .enumerate()
+                .map(|(bit, v)| {
+                    if offset & (1 << bit) != 0 {
+                        SymbolicBDD::Var(v.clone())
+                    } else {
+                        SymbolicBDD::Not(Box::new(SymbolicBDD::Var(v.clone())))
+                    }
+                })
+                .reduce(|a, b| SymbolicBDD::BinaryOp(BinaryOperator::And, Box::new(a), Box::new(b)))
+                .unwrap_or(SymbolicBDD::True),
         }
+    }
+
+    fn expand_domain_refs(
+        &self,
+        formula: &SymbolicBDD,
+        encoding: DomainEncoding,
+        encoded: &[Vec<NamedSymbol>],
+    ) -> io::Result<SymbolicBDD> {
+        let expand = |f: &SymbolicBDD| self.expand_domain_refs(f, encoding, encoded);
+        let expand_box = |f: &SymbolicBDD| expand(f).map(Box::new);
+        let expand_list = |list: &[SymbolicBDD]| -> io::Result<Vec<SymbolicBDD>> {
+            list.iter().map(expand).collect()
+        };
+
+        Ok(match formula {
+            SymbolicBDD::DomainEq(symbol, index, value) => {
+                let position = self
+                    .domains
+                    .iter()
+                    .position(|d| d.matches(symbol, *index))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "Undeclared domain variable {}",
+                                DomainDeclaration {
+                                    symbol: symbol.clone(),
+                                    index: *index,
+                                    values: 0..=0,
+                                }
+                                .name()
+                            ),
+                        )
+                    })?;
+                let domain = &self.domains[position];
+
+                if !domain.values.contains(value) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Value {} is outside the domain of {} ({}..{})",
+                            value,
+                            domain.name(),
+                            domain.values.start(),
+                            domain.values.end()
+                        ),
+                    ));
+                }
+
+                Self::encode_value(domain, encoding, &encoded[position], *value)
+            }
+            SymbolicBDD::Not(f) => SymbolicBDD::Not(expand_box(f)?),
+            SymbolicBDD::Quantifier(q, v, f) => {
+                SymbolicBDD::Quantifier(*q, v.clone(), expand_box(f)?)
+            }
+            SymbolicBDD::CountableConst(op, l, n) => {
+                SymbolicBDD::CountableConst(*op, expand_list(l)?, *n)
+            }
+            SymbolicBDD::WeightedCountable(op, w, l, n) => {
+                SymbolicBDD::WeightedCountable(*op, w.clone(), expand_list(l)?, *n)
+            }
+            SymbolicBDD::Conjunction(l) => SymbolicBDD::Conjunction(expand_list(l)?),
+            SymbolicBDD::NaryOp(op, l) => SymbolicBDD::NaryOp(*op, expand_list(l)?),
+            SymbolicBDD::CountableVariable(op, l, r, k) => {
+                SymbolicBDD::CountableVariable(*op, expand_list(l)?, expand_list(r)?, *k)
+            }
+            SymbolicBDD::FixedPoint(v, i, f) => {
+                SymbolicBDD::FixedPoint(v.clone(), *i, expand_box(f)?)
+            }
+            SymbolicBDD::Ite(a, b, c) => {
+                SymbolicBDD::Ite(expand_box(a)?, expand_box(b)?, expand_box(c)?)
+            }
+            SymbolicBDD::BinaryOp(op, l, r) => {
+                SymbolicBDD::BinaryOp(*op, expand_box(l)?, expand_box(r)?)
+            }
+            SymbolicBDD::True
+            | SymbolicBDD::False
+            | SymbolicBDD::Var(_)
+            | SymbolicBDD::Subtree(_)
+            | SymbolicBDD::Reference(_) => formula.clone(),
+        })
+    }
+
+    // (re-)compute the free variables and the raw2free lookup table from the parse tree
+    // raw2free is indexed by variable id, which is not necessarily contiguous (e.g. when a variable ordering is provided)
+    fn update_free_vars(&mut self) {
+        self.free_vars = self.free_vars_of(&self.bdd);
+        self.raw2free = vec![None; self.vars.iter().map(|v| v.id + 1).max().unwrap_or_default()];
+
+        for (vi, v) in self.free_vars.iter().enumerate() {
+            self.raw2free[v.id] = Some(vi);
+        }
+    }
+
+    /// Add variables that do not occur in the formula itself, but can occur through references to definitions.
+    /// The free variables are re-computed afterwards.
+    pub fn add_vars(&mut self, vars: &[NamedSymbol]) {
+        for v in vars {
+            if !self.vars.contains(v) {
+                self.vars.push(v.clone());
+            }
+        }
+        self.vars.sort_by_key(|v| v.id);
+
+        self.update_free_vars();
+    }
+
+    /// Create a copy of this formula in a new environment, where the variable ids are re-assigned according to the
+    /// given ordering. Variables are matched by name; variables not present in the ordering are placed after the
+    /// ordered variables, in their current relative order.
+    ///
+    /// The vars, free_vars and raw2free fields of the resulting formula are consistent with the new ids.
+    ///
+    /// Returns an error if the formula contains bdd subtrees or references to bdd definitions, which cannot be
+    /// reordered.
+    pub fn with_order(&self, order: &[NamedSymbol]) -> io::Result<Self> {
+        let mut new_ids: FxHashMap<usize, usize> = FxHashMap::default();
+
+        for v in order
+            .iter()
+            .filter_map(|o| self.name2var(o.name.as_ref()))
+            .chain(self.vars.iter().cloned())
+        {
+            if !new_ids.contains_key(&v.id) {
+                new_ids.insert(v.id, new_ids.len());
+            }
+        }
+
+        let definitions = self
+            .definitions
+            .borrow()
+            .iter()
+            .map(|(name, contents)| match contents {
+                ReferenceContents::Syntax(syntax) => Ok((
+                    name.clone(),
+                    ReferenceContents::Syntax(self.remap_vars(syntax, &new_ids)?),
+                )),
+                ReferenceContents::BDD(_) => Err(unsupported_reordering()),
+            })
+            .collect::<io::Result<_>>()?;
+
+        let mut vars: Vec<NamedSymbol> = self
+            .vars
+            .iter()
+            .map(|v| Self::remap_var(v, &new_ids))
+            .collect();
+        vars.sort_by_key(|v| v.id);
+
+        let mut result = Self {
+            vars,
+            free_vars: Vec::new(),
+            raw2free: Vec::new(),
+            bdd: self.remap_vars(&self.bdd, &new_ids)?,
+            env: Rc::new(BDDEnv::new()),
+            definitions: RefCell::new(definitions),
+            domains: self.domains.clone(),
+            fp_progress: self.fp_progress,
+            metadata: self.metadata.clone(),
+        };
+
+        result.update_free_vars();
 
         Ok(result)
     }
 
+    /// Evaluate the formula in a new environment using a different variable ordering, see [`ParsedFormula::with_order`].
+    ///
+    /// The symbols in the resulting bdd carry the new ids, therefore the free_vars and raw2free fields of this formula
+    /// do not apply to the result. Use [`ParsedFormula::with_order`] to obtain a formula consistent with the new ids.
+    pub fn reeval_with_order(&self, order: &[NamedSymbol]) -> io::Result<Rc<BDD<NamedSymbol>>> {
+        Ok(self.with_order(order)?.eval())
+    }
+
+    fn remap_var(var: &NamedSymbol, new_ids: &FxHashMap<usize, usize>) -> NamedSymbol {
+        NamedSymbol {
+            name: Rc::clone(&var.name),
+            id: new_ids.get(&var.id).copied().unwrap_or(var.id),
+        }
+    }
+
+    // replace the ids of all variables in the formula according to the new_ids lookup table
+    fn remap_vars(
+        &self,
+        formula: &SymbolicBDD,
+        new_ids: &FxHashMap<usize, usize>,
+    ) -> io::Result<SymbolicBDD> {
+        let remap_list = |list: &[SymbolicBDD]| -> io::Result<Vec<SymbolicBDD>> {
+            list.iter().map(|f| self.remap_vars(f, new_ids)).collect()
+        };
+
+        Ok(match formula {
+            SymbolicBDD::Var(v) => SymbolicBDD::Var(Self::remap_var(v, new_ids)),
+            SymbolicBDD::Not(f) => SymbolicBDD::Not(Box::new(self.remap_vars(f, new_ids)?)),
+            SymbolicBDD::Quantifier(q, v, f) => SymbolicBDD::Quantifier(
+                *q,
+                v.iter().map(|v| Self::remap_var(v, new_ids)).collect(),
+                Box::new(self.remap_vars(f, new_ids)?),
+            ),
+            SymbolicBDD::CountableConst(op, l, n) => {
+                SymbolicBDD::CountableConst(*op, remap_list(l)?, *n)
+            }
+            SymbolicBDD::WeightedCountable(op, w, l, n) => {
+                SymbolicBDD::WeightedCountable(*op, w.clone(), remap_list(l)?, *n)
+            }
+            SymbolicBDD::Conjunction(l) => SymbolicBDD::Conjunction(remap_list(l)?),
+            SymbolicBDD::NaryOp(op, l) => SymbolicBDD::NaryOp(*op, remap_list(l)?),
+            SymbolicBDD::CountableVariable(op, l, r, k) => {
+                SymbolicBDD::CountableVariable(*op, remap_list(l)?, remap_list(r)?, *k)
+            }
+            SymbolicBDD::FixedPoint(v, i, f) => SymbolicBDD::FixedPoint(
+                Self::remap_var(v, new_ids),
+                *i,
+                Box::new(self.remap_vars(f, new_ids)?),
+            ),
+            SymbolicBDD::Ite(a, b, c) => SymbolicBDD::Ite(
+                Box::new(self.remap_vars(a, new_ids)?),
+                Box::new(self.remap_vars(b, new_ids)?),
+                Box::new(self.remap_vars(c, new_ids)?),
+            ),
+            SymbolicBDD::BinaryOp(op, l, r) => SymbolicBDD::BinaryOp(
+                *op,
+                Box::new(self.remap_vars(l, new_ids)?),
+                Box::new(self.remap_vars(r, new_ids)?),
+            ),
+            SymbolicBDD::Subtree(_) => return Err(unsupported_reordering()),
+            SymbolicBDD::True
+            | SymbolicBDD::False
+            | SymbolicBDD::Reference(_)
+            | SymbolicBDD::DomainEq(_, _, _) => formula.clone(),
+        })
+    }
+
+    /// Simplify the constant subformulas of the formula, e.g. `x & true` to `x` and `!false` to `true`.
+    ///
+    /// The identities of the boolean operators are applied bottom-up, such that no bdd nodes are constructed for
+    /// trivial subformulas during evaluation. The result is equivalent to the original formula.
+    pub fn fold_constants(&self, formula: &SymbolicBDD) -> SymbolicBDD {
+        let fold_list = |list: &[SymbolicBDD]| -> Vec<SymbolicBDD> {
+            list.iter().map(|f| self.fold_constants(f)).collect()
+        };
+
+        match formula {
+            SymbolicBDD::Not(f) => negate(self.fold_constants(f)),
+            SymbolicBDD::BinaryOp(op, l, r) => {
+                fold_binary_op(*op, self.fold_constants(l), self.fold_constants(r))
+            }
+            SymbolicBDD::Ite(c, t, e) => match self.fold_constants(c) {
+                SymbolicBDD::True => self.fold_constants(t),
+                SymbolicBDD::False => self.fold_constants(e),
+                c => SymbolicBDD::Ite(
+                    Box::new(c),
+                    Box::new(self.fold_constants(t)),
+                    Box::new(self.fold_constants(e)),
+                ),
+            },
+            SymbolicBDD::Quantifier(q, v, f) => match self.fold_constants(f) {
+                f @ (SymbolicBDD::True | SymbolicBDD::False) => f,
+                f => SymbolicBDD::Quantifier(*q, v.clone(), Box::new(f)),
+            },
+            SymbolicBDD::Conjunction(l) => {
+                let lines = fold_list(l);
+
+                if lines.contains(&SymbolicBDD::False) {
+                    SymbolicBDD::False
+                } else {
+                    SymbolicBDD::Conjunction(
+                        lines
+                            .into_iter()
+                            .filter(|f| *f != SymbolicBDD::True)
+                            .collect(),
+                    )
+                }
+            }
+            SymbolicBDD::CountableConst(op, l, n) => {
+                SymbolicBDD::CountableConst(*op, fold_list(l), *n)
+            }
+            SymbolicBDD::WeightedCountable(op, w, l, n) => {
+                SymbolicBDD::WeightedCountable(*op, w.clone(), fold_list(l), *n)
+            }
+            SymbolicBDD::CountableVariable(op, l, r, k) => {
+                SymbolicBDD::CountableVariable(*op, fold_list(l), fold_list(r), *k)
+            }
+            SymbolicBDD::NaryOp(op, l) => SymbolicBDD::NaryOp(*op, fold_list(l)),
+            SymbolicBDD::FixedPoint(v, i, f) => {
+                SymbolicBDD::FixedPoint(v.clone(), *i, Box::new(self.fold_constants(f)))
+            }
+            SymbolicBDD::True
+            | SymbolicBDD::False
+            | SymbolicBDD::Var(_)
+            | SymbolicBDD::Subtree(_)
+            | SymbolicBDD::Reference(_)
+            | SymbolicBDD::DomainEq(_, _, _) => formula.clone(),
+        }
+    }
+
     pub fn eval(&self) -> Rc<BDD<NamedSymbol>> {
-        self.eval_recursive(&self.bdd)
+        self.eval_recursive(&self.fold_constants(&self.bdd))
+    }
+
+    /// Assign constant values to the named variables in the given (evaluated) bdd.
+    /// The assigned variables are removed from the free variables, such that the free_vars and raw2free fields only
+    /// contain the variables that remain free.
+    pub fn assign(
+        &mut self,
+        bdd: Rc<BDD<NamedSymbol>>,
+        assignments: &[(String, bool)],
+    ) -> io::Result<Rc<BDD<NamedSymbol>>> {
+        // resolve all names before changing the free variables, such that an unknown name leaves the formula unchanged
+        let vars = assignments
+            .iter()
+            .map(|(name, value)| {
+                self.name2var(name).map(|var| (var, *value)).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unknown variable {}", name),
+                    )
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut result = bdd;
+
+        for (var, value) in vars {
+            result = self.env.restrict(result, &var, value);
+
+            if let Some(entry) = self.raw2free.get_mut(var.id) {
+                *entry = None;
+            }
+        }
+
+        // re-number the remaining free variables
+        for (vi, entry) in self.raw2free.iter_mut().flatten().enumerate() {
+            *entry = vi;
+        }
+
+        let assigned: Vec<&String> = assignments.iter().map(|(name, _)| name).collect();
+        self.free_vars
+            .retain(|v| !assigned.contains(&v.name.as_ref()));
+
+        Ok(result)
+    }
+
+    /// Returns the variables occurring free (not bound by a quantifier) in the given (sub)formula, sorted by id.
+    /// References are resolved using the current definitions.
+    pub fn free_vars_of(&self, formula: &SymbolicBDD) -> Vec<NamedSymbol> {
+        let mut free_vars: Vec<NamedSymbol> = self
+            .vars
+            .iter()
+            .filter(|v| self.var_is_free(formula, v))
+            .cloned()
+            .collect();
+        free_vars.sort();
+
+        free_vars
+    }
+
+    /// Check the parse tree for likely modeling mistakes, such as countable constraints which can never be satisfied.
+    /// The warnings do not affect evaluation. References are not followed.
+    pub fn validate(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        Self::validate_recursive(&self.bdd, &mut warnings);
+        warnings
+    }
+
+    fn validate_recursive(formula: &SymbolicBDD, warnings: &mut Vec<Warning>) {
+        match formula {
+            SymbolicBDD::CountableConst(operator, sub, threshold) => {
+                if sub.is_empty() {
+                    warnings.push(Warning::EmptyList {
+                        operator: *operator,
+                        threshold: *threshold,
+                    });
+                } else if *threshold > sub.len()
+                    || (*operator == CountableOperator::MoreThan && *threshold == sub.len())
+                {
+                    warnings.push(Warning::ThresholdExceedsLength {
+                        operator: *operator,
+                        threshold: *threshold,
+                        length: sub.len(),
+                    });
+                }
+
+                for f in sub {
+                    Self::validate_recursive(f, warnings);
+                }
+            }
+            SymbolicBDD::Conjunction(sub)
+            | SymbolicBDD::NaryOp(_, sub)
+            | SymbolicBDD::WeightedCountable(_, _, sub, _) => {
+                for f in sub {
+                    Self::validate_recursive(f, warnings);
+                }
+            }
+            SymbolicBDD::CountableVariable(_, l, r, _) => {
+                for f in l.iter().chain(r.iter()) {
+                    Self::validate_recursive(f, warnings);
+                }
+            }
+            SymbolicBDD::Ite(a, b, c) => {
+                Self::validate_recursive(a, warnings);
+                Self::validate_recursive(b, warnings);
+                Self::validate_recursive(c, warnings);
+            }
+            SymbolicBDD::BinaryOp(_, a, b) => {
+                Self::validate_recursive(a, warnings);
+                Self::validate_recursive(b, warnings);
+            }
+            SymbolicBDD::Not(f)
+            | SymbolicBDD::Quantifier(_, _, f)
+            | SymbolicBDD::FixedPoint(_, _, f) => Self::validate_recursive(f, warnings),
+            SymbolicBDD::True
+            | SymbolicBDD::False
+            | SymbolicBDD::Var(_)
+            | SymbolicBDD::Subtree(_)
+            | SymbolicBDD::Reference(_)
+            | SymbolicBDD::DomainEq(_, _, _) => {}
+        }
+    }
+
+    /// The top-level conjuncts of the formula: the lines in conjunction mode, or the operands of `&` at the root.
+    /// The lines are not split any further, such that every conjunct corresponds to a line of the input.
+    pub fn conjuncts(&self) -> Vec<&SymbolicBDD> {
+        match &self.bdd {
+            SymbolicBDD::Conjunction(lines) => lines.iter().collect(),
+            root => {
+                let mut conjuncts = Vec::new();
+                Self::conjuncts_recursive(root, &mut conjuncts);
+                conjuncts
+            }
+        }
+    }
+
+    fn conjuncts_recursive<'a>(formula: &'a SymbolicBDD, conjuncts: &mut Vec<&'a SymbolicBDD>) {
+        match formula {
+            SymbolicBDD::BinaryOp(BinaryOperator::And, a, b) => {
+                Self::conjuncts_recursive(a, conjuncts);
+                Self::conjuncts_recursive(b, conjuncts);
+            }
+            other => conjuncts.push(other),
+        }
+    }
+
+    /// Find a minimal unsatisfiable subset of the top-level conjuncts, see [`ParsedFormula::conjuncts`].
+    ///
+    /// Returns the indices of the conjuncts in the core, or None if the formula is satisfiable.
+    /// The core is found by deletion: every conjunct is removed in turn, and only restored if the remaining conjuncts
+    /// become satisfiable. The result is minimal, in that removing any conjunct makes it satisfiable,
+    /// but not necessarily the smallest core.
+    pub fn unsat_core(&self) -> Option<Vec<usize>> {
+        let evaluated: Vec<Rc<BDD<NamedSymbol>>> = self
+            .conjuncts()
+            .into_iter()
+            .map(|f| self.eval_recursive(f))
+            .collect();
+
+        let conjunction = |core: &[usize]| {
+            core.iter().fold(self.env.mk_const(true), |acc, &i| {
+                self.env.and(acc, Rc::clone(&evaluated[i]))
+            })
+        };
+
+        let mut core: Vec<usize> = (0..evaluated.len()).collect();
+
+        if !conjunction(&core).is_false() {
+            return None;
+        }
+
+        let mut i = 0;
+        while i < core.len() {
+            let without: Vec<usize> = core
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, &c)| c)
+                .collect();
+
+            if conjunction(&without).is_false() {
+                core = without;
+            } else {
+                i += 1;
+            }
+        }
+
+        Some(core)
     }
 
     // check whether a given variable is bound by a quantifier in the formula
@@ -294,14 +1477,17 @@ impl ParsedFormula {
             }
             SymbolicBDD::Not(f) => self.var_is_free(f, var),
             SymbolicBDD::BinaryOp(_, a, b) => self.var_is_free(a, var) || self.var_is_free(b, var),
-            SymbolicBDD::CountableConst(_, sub, _) => sub.iter().any(|f| self.var_is_free(f, var)),
-            SymbolicBDD::CountableVariable(_, l, r) => {
+            SymbolicBDD::CountableConst(_, sub, _)
+            | SymbolicBDD::WeightedCountable(_, _, sub, _)
+            | SymbolicBDD::Conjunction(sub)
+            | SymbolicBDD::NaryOp(_, sub) => sub.iter().any(|f| self.var_is_free(f, var)),
+            SymbolicBDD::CountableVariable(_, l, r, _) => {
                 l.iter().any(|f| self.var_is_free(f, var))
                     || r.iter().any(|f| self.var_is_free(f, var))
             }
             SymbolicBDD::FixedPoint(v, _, f) => v != var && self.var_is_free(f, var),
             SymbolicBDD::Subtree(_t) => unimplemented!(),
-            SymbolicBDD::True | SymbolicBDD::False => false,
+            SymbolicBDD::True | SymbolicBDD::False | SymbolicBDD::DomainEq(_, _, _) => false,
             SymbolicBDD::Reference(name) => {
                 self.get_definition(name).map_or_else(
                     || true,
@@ -335,22 +1521,80 @@ impl ParsedFormula {
                     CountableOperator::AtMost => self.env.amn(&branches, *n as i64),
                     CountableOperator::AtLeast => self.env.aln(&branches, *n as i64),
                     CountableOperator::Exactly => self.env.exn(&branches, *n as i64),
+                    CountableOperator::NotEqual => self.env.not(self.env.exn(&branches, *n as i64)),
                     CountableOperator::LessThan => self.env.amn(&branches, *n as i64 - 1),
                     CountableOperator::MoreThan => self.env.aln(&branches, *n as i64 + 1),
                 }
             }
-            SymbolicBDD::CountableVariable(op, l, r) => {
+            SymbolicBDD::WeightedCountable(op, ws, bs, n) => {
+                let weighted: Vec<(i64, Rc<BDD<NamedSymbol>>)> = ws
+                    .iter()
+                    .zip(bs)
+                    .map(|(w, b)| (*w as i64, self.eval_recursive(b)))
+                    .collect();
+                let n = *n as i64;
+
+                // every comparison is expressed in terms of 'at most'
+                match op {
+                    CountableOperator::AtMost => self.env.pb_leq(&weighted, n),
+                    CountableOperator::LessThan => self.env.pb_leq(&weighted, n - 1),
+                    CountableOperator::AtLeast => self.env.not(self.env.pb_leq(&weighted, n - 1)),
+                    CountableOperator::MoreThan => self.env.not(self.env.pb_leq(&weighted, n)),
+                    CountableOperator::Exactly => self.env.and(
+                        self.env.pb_leq(&weighted, n),
+                        self.env.not(self.env.pb_leq(&weighted, n - 1)),
+                    ),
+                    CountableOperator::NotEqual => self.env.or(
+                        self.env.pb_leq(&weighted, n - 1),
+                        self.env.not(self.env.pb_leq(&weighted, n)),
+                    ),
+                }
+            }
+            SymbolicBDD::Conjunction(bs) => self
+                .env
+                .and_all(bs.iter().map(|b| self.eval_recursive(b)).collect()),
+            SymbolicBDD::NaryOp(NaryOperator::Parity, bs) => {
+                bs.iter().fold(self.env.mk_const(false), |acc, b| {
+                    self.env.xor(acc, self.eval_recursive(b))
+                })
+            }
+            SymbolicBDD::NaryOp(NaryOperator::Majority, bs) => {
+                let branches: Vec<Rc<BDD<NamedSymbol>>> =
+                    bs.iter().map(|b| self.eval_recursive(b)).collect();
+
+                // strictly more than half of the formulas are true
+                self.env.aln(&branches, (branches.len() / 2 + 1) as i64)
+            }
+            SymbolicBDD::NaryOp(NaryOperator::AllEq, bs) => self.env.all_equal(
+                &bs.iter()
+                    .map(|b| self.eval_recursive(b))
+                    .collect::<Vec<_>>(),
+            ),
+            SymbolicBDD::CountableVariable(op, l, r, k) => {
                 let l_branches: Vec<Rc<BDD<NamedSymbol>>> =
                     l.iter().map(|b| self.eval_recursive(b)).collect();
                 let r_branches: Vec<Rc<BDD<NamedSymbol>>> =
                     r.iter().map(|b| self.eval_recursive(b)).collect();
 
                 match op {
-                    CountableOperator::AtMost => self.env.count_leq(&l_branches, &r_branches),
-                    CountableOperator::AtLeast => self.env.count_geq(&l_branches, &r_branches),
-                    CountableOperator::Exactly => self.env.count_eq(&l_branches, &r_branches),
-                    CountableOperator::LessThan => self.env.count_lt(&l_branches, &r_branches),
-                    CountableOperator::MoreThan => self.env.count_gt(&l_branches, &r_branches),
+                    CountableOperator::AtMost => {
+                        self.env.count_leq_offset(&l_branches, &r_branches, *k)
+                    }
+                    CountableOperator::AtLeast => {
+                        self.env.count_geq_offset(&l_branches, &r_branches, *k)
+                    }
+                    CountableOperator::Exactly => {
+                        self.env.count_eq_offset(&l_branches, &r_branches, *k)
+                    }
+                    CountableOperator::NotEqual => {
+                        self.env.count_ne_offset(&l_branches, &r_branches, *k)
+                    }
+                    CountableOperator::LessThan => {
+                        self.env.count_lt_offset(&l_branches, &r_branches, *k)
+                    }
+                    CountableOperator::MoreThan => {
+                        self.env.count_gt_offset(&l_branches, &r_branches, *k)
+                    }
                 }
             }
             SymbolicBDD::Ite(c, t, e) => self.env.ite(
@@ -375,14 +1619,21 @@ impl ParsedFormula {
             }
             SymbolicBDD::FixedPoint(var, initial, transformer) => {
                 let env = &self.env;
-
-                env.fp(env.mk_const(*initial), |x| {
+                let transform = |x| {
                     self.eval_recursive(&self.replace_var(
                         transformer,
                         var,
                         &SymbolicBDD::Subtree(x),
                     ))
-                })
+                };
+
+                if self.fp_progress {
+                    env.fp_with_progress(env.mk_const(*initial), transform, |i, nodes| {
+                        eprintln!("fixed point {var}: iteration {i}, {nodes} nodes");
+                    })
+                } else {
+                    env.fp(env.mk_const(*initial), transform)
+                }
             }
             SymbolicBDD::Subtree(t) => Rc::clone(t),
             SymbolicBDD::Reference(name) => self.get_definition(name).map_or_else(
@@ -392,6 +1643,9 @@ impl ParsedFormula {
                     ReferenceContents::BDD(bdd) => bdd,
                 },
             ),
+            SymbolicBDD::DomainEq(_, _, _) => {
+                unreachable!("domain variables are expanded when the formula is parsed")
+            }
         }
     }
 
@@ -410,7 +1664,35 @@ impl ParsedFormula {
 }
 
 impl SymbolicBDD {
+    // formulas which are parsed as a whole when followed by a binary operator
+    const fn is_operand(&self) -> bool {
+        !matches!(
+            self,
+            Self::Quantifier(..)
+                | Self::FixedPoint(..)
+                | Self::Ite(..)
+                | Self::BinaryOp(..)
+                | Self::Conjunction(..)
+        )
+    }
+
+    // write the formula as operand of a binary operator or negation, parenthesized if needed
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_operand() {
+            write!(f, "{self}")
+        } else {
+            write!(f, "({self})")
+        }
+    }
+
     fn parse_formula(tokens: &mut TokenReader) -> io::Result<Self> {
+        if let None | Some(SymbolicBDDToken::Eof) = tokens.peek() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                ParseError::EmptyInput,
+            ));
+        }
+
         let result = Self::parse_sub_formula(tokens)?;
 
         expect(SymbolicBDDToken::Eof, tokens)?;
@@ -418,7 +1700,33 @@ impl SymbolicBDD {
         Ok(result)
     }
 
+    // parse a list of formulas separated by semicolons, a trailing semicolon is allowed
+    fn parse_statements(tokens: &mut TokenReader) -> io::Result<Vec<Self>> {
+        let mut statements = vec![Self::parse_sub_formula(tokens)?];
+
+        while check(SymbolicBDDToken::Semicolon, tokens).is_ok() {
+            expect(SymbolicBDDToken::Semicolon, tokens)?;
+
+            if check(SymbolicBDDToken::Eof, tokens).is_ok() {
+                break;
+            }
+
+            statements.push(Self::parse_sub_formula(tokens)?);
+        }
+
+        expect(SymbolicBDDToken::Eof, tokens)?;
+
+        Ok(statements)
+    }
+
     fn parse_simple_sub_formula(tokens: &mut TokenReader) -> io::Result<Self> {
+        tokens.descend()?;
+        let result = Self::parse_simple_sub_formula_impl(tokens);
+        tokens.ascend();
+        result
+    }
+
+    fn parse_simple_sub_formula_impl(tokens: &mut TokenReader) -> io::Result<Self> {
         match tokens.peek() {
             Some(SymbolicBDDToken::OpenParen) => Self::parse_parentized_formula(tokens),
             Some(SymbolicBDDToken::OpenSquare) => Self::parse_countable_formula(tokens),
@@ -433,42 +1741,96 @@ impl SymbolicBDD {
             Some(SymbolicBDDToken::Reference(_)) => {
                 Ok(Self::Reference(Self::parse_reference_name(tokens)?))
             }
-            Some(SymbolicBDDToken::Var(_)) => Ok(Self::Var(Self::parse_variable_name(tokens)?)),
-            Some(SymbolicBDDToken::Not) => Self::parse_negation(tokens),
+            Some(SymbolicBDDToken::Var(_)) => Self::parse_variable(tokens),
+            Some(SymbolicBDDToken::Not | SymbolicBDDToken::Minus) => Self::parse_negation(tokens),
             Some(SymbolicBDDToken::Exists) => Self::parse_existence_quantifier(tokens),
             Some(SymbolicBDDToken::Forall) => Self::parse_universal_quantifier(tokens),
             Some(SymbolicBDDToken::GFP) => Self::parse_fixed_point(tokens, true),
             Some(SymbolicBDDToken::LFP) => Self::parse_fixed_point(tokens, false),
             Some(SymbolicBDDToken::If) => Self::parse_ite(tokens),
+            Some(SymbolicBDDToken::When) => Self::parse_when(tokens),
+            Some(SymbolicBDDToken::Parity) => Self::parse_nary(tokens, NaryOperator::Parity),
+            Some(SymbolicBDDToken::Majority) => Self::parse_nary(tokens, NaryOperator::Majority),
+            Some(SymbolicBDDToken::AllEq) => Self::parse_nary(tokens, NaryOperator::AllEq),
             None | Some(SymbolicBDDToken::Eof) => {
                 Err(io::Error::new(io::ErrorKind::InvalidData, "Unexpected EOF"))
             }
-            Some(other) => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Unexpected token {:?}", other),
-            )),
+            Some(other) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected token {:?}", other),
+            )),
+        }
+    }
+
+    fn parse_sub_formula(tokens: &mut TokenReader) -> io::Result<Self> {
+        // the operands and operators are collected iteratively, such that long chains do not exhaust the stack
+        let mut operands = vec![Self::parse_simple_sub_formula(tokens)?];
+        let mut operators = Vec::new();
+
+        // either a binary operator or end of sub-formula
+        while let Some(
+            SymbolicBDDToken::And
+            | SymbolicBDDToken::Star
+            | SymbolicBDDToken::Or
+            | SymbolicBDDToken::Plus
+            | SymbolicBDDToken::Xor
+            | SymbolicBDDToken::Nor
+            | SymbolicBDDToken::Nand
+            | SymbolicBDDToken::Implies
+            | SymbolicBDDToken::In
+            | SymbolicBDDToken::ImpliesInv
+            | SymbolicBDDToken::Iff,
+        ) = tokens.peek()
+        {
+            operators.push(Self::parse_binary_operator(tokens)?);
+            operands.push(Self::parse_simple_sub_formula(tokens)?);
+        }
+
+        // binary operators are right-associative
+        let expected_operand = || io::Error::new(io::ErrorKind::InvalidData, "Expected an operand");
+        let mut result = operands.pop().ok_or_else(expected_operand)?;
+        let mut depth = 0;
+
+        while let Some(op) = operators.pop() {
+            let mut run = vec![result];
+
+            // a run of the same associative operator is folded into a balanced tree, such that long chains do not
+            // result in a deeply nested formula
+            loop {
+                run.push(operands.pop().ok_or_else(expected_operand)?);
+                if !op.is_associative() || operators.last() != Some(&op) {
+                    break;
+                }
+                operators.pop();
+            }
+
+            // every other operator in the chain nests the formula one level deeper
+            tokens.descend()?;
+            depth += 1;
+
+            run.reverse();
+            result = Self::balanced(op, run);
         }
-    }
 
-    fn parse_sub_formula(tokens: &mut TokenReader) -> io::Result<Self> {
-        let left = Self::parse_simple_sub_formula(tokens)?;
+        for _ in 0..depth {
+            tokens.ascend();
+        }
 
-        // either a binary operator or end of sub-formula
-        match tokens.peek() {
-            Some(SymbolicBDDToken::And)
-            | Some(SymbolicBDDToken::Or)
-            | Some(SymbolicBDDToken::Xor)
-            | Some(SymbolicBDDToken::Nor)
-            | Some(SymbolicBDDToken::Nand)
-            | Some(SymbolicBDDToken::Implies)
-            | Some(SymbolicBDDToken::ImpliesInv)
-            | Some(SymbolicBDDToken::Iff) => {
-                let op = Self::parse_binary_operator(tokens)?;
-                let right = Self::parse_sub_formula(tokens)?;
-                Ok(Self::BinaryOp(op, Box::new(left), Box::new(right)))
-            }
-            _ => Ok(left),
+        Ok(result)
+    }
+
+    // combine a non-empty list of operands of an associative operator into a tree of logarithmic depth
+    fn balanced(op: BinaryOperator, mut operands: Vec<Self>) -> Self {
+        if operands.len() == 1 {
+            return operands.swap_remove(0);
         }
+
+        let right = operands.split_off(operands.len() / 2);
+        Self::BinaryOp(
+            op,
+            Box::new(Self::balanced(op, operands)),
+            Box::new(Self::balanced(op, right)),
+        )
     }
 
     fn parse_ite(tokens: &mut TokenReader) -> io::Result<Self> {
@@ -476,19 +1838,82 @@ impl SymbolicBDD {
         let cond = Self::parse_sub_formula(tokens)?;
         expect(SymbolicBDDToken::Then, tokens)?;
         let then = Self::parse_sub_formula(tokens)?;
-        expect(SymbolicBDDToken::Else, tokens)?;
-        let else_ = Self::parse_sub_formula(tokens)?;
+
+        // without an else branch, the else branch defaults to true: `if a then b` is equivalent to `a => b`
+        let else_ = if check(SymbolicBDDToken::Else, tokens).is_ok() {
+            expect(SymbolicBDDToken::Else, tokens)?;
+            Self::parse_sub_formula(tokens)?
+        } else {
+            Self::True
+        };
 
         Ok(Self::Ite(Box::new(cond), Box::new(then), Box::new(else_)))
     }
 
+    // parse 'when a then b', which is equivalent to 'a => b'
+    fn parse_when(tokens: &mut TokenReader) -> io::Result<Self> {
+        expect(SymbolicBDDToken::When, tokens)?;
+        let cond = Self::parse_sub_formula(tokens)?;
+        expect(SymbolicBDDToken::Then, tokens)?;
+        let then = Self::parse_sub_formula(tokens)?;
+
+        Ok(Self::BinaryOp(
+            BinaryOperator::Implies,
+            Box::new(cond),
+            Box::new(then),
+        ))
+    }
+
+    // parse a builtin function applied to a parenthesized list of formulas, e.g. 'majority(a, b, c)'
+    fn parse_nary(tokens: &mut TokenReader, op: NaryOperator) -> io::Result<Self> {
+        expect(
+            match op {
+                NaryOperator::Parity => SymbolicBDDToken::Parity,
+                NaryOperator::Majority => SymbolicBDDToken::Majority,
+                NaryOperator::AllEq => SymbolicBDDToken::AllEq,
+            },
+            tokens,
+        )?;
+
+        let args = Self::parse_delimited_list(
+            tokens,
+            SymbolicBDDToken::OpenParen,
+            SymbolicBDDToken::CloseParen,
+        )?;
+
+        Ok(Self::NaryOp(op, args))
+    }
+
     fn parse_formula_list(tokens: &mut TokenReader) -> io::Result<Vec<Self>> {
-        expect(SymbolicBDDToken::OpenSquare, tokens)?;
+        Self::parse_delimited_list(
+            tokens,
+            SymbolicBDDToken::OpenSquare,
+            SymbolicBDDToken::CloseSquare,
+        )
+    }
+
+    // parse a comma-separated list of formulas between the open and close tokens
+    fn parse_delimited_list(
+        tokens: &mut TokenReader,
+        open: SymbolicBDDToken,
+        close: SymbolicBDDToken,
+    ) -> io::Result<Vec<Self>> {
+        Self::parse_delimited_list_with(tokens, open, close, Self::parse_sub_formula)
+    }
+
+    // parse a comma-separated list of elements between the open and close tokens
+    fn parse_delimited_list_with<T>(
+        tokens: &mut TokenReader,
+        open: SymbolicBDDToken,
+        close: SymbolicBDDToken,
+        parse_element: impl Fn(&mut TokenReader) -> io::Result<T>,
+    ) -> io::Result<Vec<T>> {
+        expect(open, tokens)?;
         let mut subforms = Vec::new();
 
         loop {
-            if check(SymbolicBDDToken::CloseSquare, tokens).is_err() {
-                subforms.push(Self::parse_sub_formula(tokens)?);
+            if check(close.clone(), tokens).is_err() {
+                subforms.push(parse_element(tokens)?);
 
                 // if no comma is found after the sub-formula, the formula should end with a closing square bracket
                 if check(SymbolicBDDToken::Comma, tokens).is_err() {
@@ -502,7 +1927,7 @@ impl SymbolicBDD {
             }
         }
 
-        expect(SymbolicBDDToken::CloseSquare, tokens)?;
+        expect(close, tokens)?;
 
         Ok(subforms)
     }
@@ -517,11 +1942,38 @@ impl SymbolicBDD {
         }
     }
 
+    // parse an element of a countable list, optionally preceded by a weight, e.g. '2 * a'
+    fn parse_weighted_element(tokens: &mut TokenReader) -> io::Result<(Option<usize>, Self)> {
+        if let (Some(SymbolicBDDToken::Countable(_)), Some(SymbolicBDDToken::Star)) =
+            (tokens.peek().cloned(), tokens.peek_nth(1))
+        {
+            let weight = Self::parse_countable(tokens)?;
+            expect(SymbolicBDDToken::Star, tokens)?;
+
+            Ok((Some(weight), Self::parse_sub_formula(tokens)?))
+        } else {
+            Ok((None, Self::parse_sub_formula(tokens)?))
+        }
+    }
+
     fn parse_countable_formula(tokens: &mut TokenReader) -> io::Result<Self> {
-        let leftlist = Self::parse_formula_list(tokens)?;
+        let elements = Self::parse_delimited_list_with(
+            tokens,
+            SymbolicBDDToken::OpenSquare,
+            SymbolicBDDToken::CloseSquare,
+            Self::parse_weighted_element,
+        )?;
+
+        // elements without a weight count once
+        let weighted = elements.iter().any(|(weight, _)| weight.is_some());
+        let (weights, leftlist): (Vec<usize>, Vec<Self>) = elements
+            .into_iter()
+            .map(|(weight, f)| (weight.unwrap_or(1), f))
+            .unzip();
 
         let operator = match tokens.next() {
             Some(SymbolicBDDToken::Eq) => CountableOperator::Exactly,
+            Some(SymbolicBDDToken::NotEq) => CountableOperator::NotEqual,
             Some(SymbolicBDDToken::ImpliesInv) => CountableOperator::AtMost,
             Some(SymbolicBDDToken::Geq) => CountableOperator::AtLeast,
             Some(SymbolicBDDToken::Lt) => CountableOperator::LessThan,
@@ -535,14 +1987,131 @@ impl SymbolicBDD {
         };
 
         if check(SymbolicBDDToken::OpenSquare, tokens).is_ok() {
+            if weighted {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Weighted lists can only be compared with a number",
+                ));
+            }
+
             let rightlist = Self::parse_formula_list(tokens)?;
+            let offset = Self::parse_countable_offset(tokens)?;
 
-            Ok(Self::CountableVariable(operator, leftlist, rightlist))
+            Ok(Self::CountableVariable(
+                operator, leftlist, rightlist, offset,
+            ))
         } else {
             let count = Self::parse_countable(tokens)?;
 
-            Ok(Self::CountableConst(operator, leftlist, count))
+            if weighted {
+                Ok(Self::WeightedCountable(operator, weights, leftlist, count))
+            } else {
+                Ok(Self::CountableConst(operator, leftlist, count))
+            }
+        }
+    }
+
+    // parse an optional '+ n' or '- n' suffix, only if the sign is directly followed by a number
+    fn parse_countable_offset(tokens: &mut TokenReader) -> io::Result<i64> {
+        // only the '+' and '-' signs, not the other spellings of disjunction and negation such as '|' or '!'
+        let sign = match tokens.peek() {
+            Some(SymbolicBDDToken::Plus) => 1,
+            Some(SymbolicBDDToken::Minus) => -1,
+            _ => return Ok(0),
+        };
+
+        // look ahead without consuming the sign, such that '[a] = [b] + c' is still parsed as a disjunction
+        if let Some(SymbolicBDDToken::Countable(_)) = tokens.peek_nth(1) {
+            tokens.next();
+            let offset = i64::try_from(Self::parse_countable(tokens)?).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "Countable offset out of range")
+            })?;
+            Ok(sign * offset)
+        } else {
+            Ok(0)
+        }
+    }
+
+    // a variable, or a comparison of a (possibly indexed) domain variable with a value, e.g. 'cell[0] = 5'
+    fn parse_variable(tokens: &mut TokenReader) -> io::Result<Self> {
+        let var = Self::parse_variable_name(tokens)?;
+
+        let index = if check(SymbolicBDDToken::OpenSquare, tokens).is_ok() {
+            expect(SymbolicBDDToken::OpenSquare, tokens)?;
+            let index = Self::parse_countable(tokens)?;
+            expect(SymbolicBDDToken::CloseSquare, tokens)?;
+
+            Some(index)
+        } else {
+            None
+        };
+
+        if index.is_some() || check(SymbolicBDDToken::Eq, tokens).is_ok() {
+            expect(SymbolicBDDToken::Eq, tokens)?;
+            let value = Self::parse_countable(tokens)?;
+
+            Ok(Self::DomainEq(var, index, value))
+        } else {
+            Ok(Self::Var(var))
+        }
+    }
+
+    // parse an inclusive range 'lo..hi'
+    fn parse_range(tokens: &mut TokenReader) -> io::Result<RangeInclusive<usize>> {
+        let lo = Self::parse_countable(tokens)?;
+        expect(SymbolicBDDToken::Range, tokens)?;
+        let hi = Self::parse_countable(tokens)?;
+
+        if lo > hi {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Empty range {}..{}", lo, hi),
+            ));
+        }
+
+        Ok(lo..=hi)
+    }
+
+    // parse all leading 'var name in lo..hi;' and 'var name[a..b] in lo..hi;' declarations
+    fn parse_domain_declarations(tokens: &mut TokenReader) -> io::Result<Vec<DomainDeclaration>> {
+        let mut domains: Vec<DomainDeclaration> = Vec::new();
+
+        while check(SymbolicBDDToken::Domain, tokens).is_ok() {
+            expect(SymbolicBDDToken::Domain, tokens)?;
+            let symbol = Self::parse_variable_name(tokens)?;
+
+            let indices: Vec<Option<usize>> = if check(SymbolicBDDToken::OpenSquare, tokens).is_ok()
+            {
+                expect(SymbolicBDDToken::OpenSquare, tokens)?;
+                let range = Self::parse_range(tokens)?;
+                expect(SymbolicBDDToken::CloseSquare, tokens)?;
+
+                range.map(Some).collect()
+            } else {
+                vec![None]
+            };
+
+            expect(SymbolicBDDToken::In, tokens)?;
+            let values = Self::parse_range(tokens)?;
+            expect(SymbolicBDDToken::Semicolon, tokens)?;
+
+            for index in indices {
+                if domains.iter().any(|d| d.matches(&symbol, index)) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Duplicate declaration of domain variable {}", symbol),
+                    ));
+                }
+
+                domains.push(DomainDeclaration {
+                    symbol: symbol.clone(),
+                    index,
+                    values: values.clone(),
+                });
+            }
         }
+
+        Ok(domains)
     }
 
     fn parse_variable_name(tokens: &mut TokenReader) -> io::Result<NamedSymbol> {
@@ -644,10 +2213,20 @@ impl SymbolicBDD {
                 expect(SymbolicBDDToken::And, tokens)?;
                 Ok(BinaryOperator::And)
             }
+            // outside of a weighted list element, '*' is a conjunction
+            Some(SymbolicBDDToken::Star) => {
+                expect(SymbolicBDDToken::Star, tokens)?;
+                Ok(BinaryOperator::And)
+            }
             Some(SymbolicBDDToken::Or) => {
                 expect(SymbolicBDDToken::Or, tokens)?;
                 Ok(BinaryOperator::Or)
             }
+            // outside of a countable offset, '+' is a disjunction
+            Some(SymbolicBDDToken::Plus) => {
+                expect(SymbolicBDDToken::Plus, tokens)?;
+                Ok(BinaryOperator::Or)
+            }
             Some(SymbolicBDDToken::Xor) => {
                 expect(SymbolicBDDToken::Xor, tokens)?;
                 Ok(BinaryOperator::Xor)
@@ -664,6 +2243,11 @@ impl SymbolicBDD {
                 expect(SymbolicBDDToken::Implies, tokens)?;
                 Ok(BinaryOperator::Implies)
             }
+            // outside of a domain declaration, 'in' is an implication
+            Some(SymbolicBDDToken::In) => {
+                expect(SymbolicBDDToken::In, tokens)?;
+                Ok(BinaryOperator::Implies)
+            }
             Some(SymbolicBDDToken::ImpliesInv) => {
                 expect(SymbolicBDDToken::ImpliesInv, tokens)?;
                 Ok(BinaryOperator::ImpliesInv)
@@ -680,7 +2264,12 @@ impl SymbolicBDD {
     }
 
     fn parse_negation(tokens: &mut TokenReader) -> io::Result<Self> {
-        expect(SymbolicBDDToken::Not, tokens)?;
+        // outside of a countable offset, '-' is a negation
+        if check(SymbolicBDDToken::Minus, tokens).is_ok() {
+            expect(SymbolicBDDToken::Minus, tokens)?;
+        } else {
+            expect(SymbolicBDDToken::Not, tokens)?;
+        }
 
         let sf = Self::parse_simple_sub_formula(tokens);
 
@@ -700,39 +2289,276 @@ impl SymbolicBDD {
         Ok(subform)
     }
 
+    // the token corresponding to a keyword, or none if the identifier is not a keyword
+    fn keyword(identifier: &str) -> Option<SymbolicBDDToken> {
+        match identifier {
+            "false" => Some(SymbolicBDDToken::False),
+            "true" => Some(SymbolicBDDToken::True),
+            "not" => Some(SymbolicBDDToken::Not),
+            "and" => Some(SymbolicBDDToken::And),
+            "or" => Some(SymbolicBDDToken::Or),
+            "xor" => Some(SymbolicBDDToken::Xor),
+            "nor" => Some(SymbolicBDDToken::Nor),
+            "nand" => Some(SymbolicBDDToken::Nand),
+            "implies" => Some(SymbolicBDDToken::Implies),
+            "in" => Some(SymbolicBDDToken::In),
+            "iff" | "eq" => Some(SymbolicBDDToken::Iff),
+            "exists" | "any" => Some(SymbolicBDDToken::Exists),
+            "forall" | "all" => Some(SymbolicBDDToken::Forall),
+            "if" => Some(SymbolicBDDToken::If),
+            "then" => Some(SymbolicBDDToken::Then),
+            "else" => Some(SymbolicBDDToken::Else),
+            "gfp" | "nu" => Some(SymbolicBDDToken::GFP),
+            "lfp" | "mu" => Some(SymbolicBDDToken::LFP),
+            _ => None,
+        }
+    }
+
+    // the name of the variable declared by 'var name', which is only a declaration if the name is quoted or not a
+    // keyword or number; 'var' is not a keyword, such that it can still be used as a variable name, e.g. in 'var & b'
+    fn domain_name<'t>(c: &regex::Captures<'t>) -> Option<&'t str> {
+        let name = c.name("domain_name")?.as_str();
+
+        // quoted names are never keywords
+        let quoted = name
+            .strip_prefix('`')
+            .and_then(|n| n.strip_suffix('`'))
+            .or_else(|| name.strip_prefix('\'').and_then(|n| n.strip_suffix('\'')));
+
+        quoted.or_else(|| {
+            (Self::keyword(name).is_none() && !name.starts_with(|c: char| c.is_numeric()))
+                .then_some(name)
+        })
+    }
+
+    // whether 'when' starts a 'when a then b' formula, which is only the case if it is followed by the start of a
+    // formula; 'when' is not a keyword, such that it can still be used as a variable name, e.g. in 'when & b'
+    fn is_when_formula(c: &regex::Captures) -> bool {
+        let Some(next) = c.name("when_next").map(|m| m.as_str()) else {
+            return false;
+        };
+
+        Self::keyword(next).map_or_else(
+            || next != "!=" && !next.starts_with(|c: char| c.is_numeric()),
+            |keyword| {
+                matches!(
+                    keyword,
+                    SymbolicBDDToken::False
+                        | SymbolicBDDToken::True
+                        | SymbolicBDDToken::Not
+                        | SymbolicBDDToken::Exists
+                        | SymbolicBDDToken::Forall
+                        | SymbolicBDDToken::If
+                        | SymbolicBDDToken::GFP
+                        | SymbolicBDDToken::LFP
+                )
+            },
+        )
+    }
+
+    // the name of a quoted variable, without the delimiters
+    fn quoted_identifier<'t>(c: &regex::Captures<'t>) -> Option<regex::Match<'t>> {
+        c.name("backquoted").or_else(|| c.name("quoted"))
+    }
+
+    // parse a number in the input, which may not exceed MAX_NUMBER
+    fn parse_number(number: &str) -> io::Result<usize> {
+        number
+            .parse()
+            .ok()
+            .filter(|n| *n <= MAX_NUMBER)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Number {} exceeds the maximum of {}", number, MAX_NUMBER),
+                )
+            })
+    }
+
+    // the names of the variables generated by '@range(prefix, lo, hi)', i.e. prefix_lo up to and including prefix_hi
+    // returns None if the captured token is not a range
+    fn range_names(c: &regex::Captures) -> io::Result<Option<Vec<String>>> {
+        let Some(prefix) = c.name("range_prefix") else {
+            return Ok(None);
+        };
+
+        let bound = |group: &str| Self::parse_number(c.name(group).map_or("", |m| m.as_str()));
+        let (lo, hi) = (bound("range_lo")?, bound("range_hi")?);
+
+        if lo > hi {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Empty range @range({}, {}, {})", prefix.as_str(), lo, hi),
+            ));
+        }
+
+        if hi - lo >= MAX_RANGE_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Range @range({}, {}, {}) exceeds the maximum length of {}",
+                    prefix.as_str(),
+                    lo,
+                    hi,
+                    MAX_RANGE_LENGTH
+                ),
+            ));
+        }
+
+        Ok(Some(
+            (lo..=hi)
+                .map(|i| format!("{}_{}", prefix.as_str(), i))
+                .collect(),
+        ))
+    }
+
+    /// Split the input into tokens, assigning an id to every new variable.
+    ///
+    /// Variable names containing spaces or symbols can be delimited by backticks or single quotes, e.g. `` `queen at (2,3)` ``.
+    /// Quoted names are never interpreted as keywords.
+    ///
+    /// The meta-construct `@range(prefix, lo, hi)` is expanded to the comma-separated variables `prefix_lo` up to and
+    /// including `prefix_hi`, which are assigned ids in that order, e.g. `[@range(x, 0, 2)] = 1` is read as
+    /// `[x_0, x_1, x_2] = 1`.
+    ///
+    /// Besides the ASCII operators and keywords, the Unicode logic symbols `∧`, `∨`, `¬`, `→`, `↔`, `⊕`, `∀` and `∃`
+    /// are accepted as synonyms for `&`, `|`, `!`, `=>`, `<=>`, `^`, `forall` and `exists` respectively.
+    ///
+    /// All tokens are collected at once, see [`TokenStream`] for reading the tokens of a large input lazily.
     pub fn tokenize(
         contents: &mut dyn BufRead,
         variable_ordering: Option<Vec<NamedSymbol>>,
     ) -> io::Result<Vec<SymbolicBDDToken>> {
+        Self::tokenize_impl(contents, variable_ordering, false, false).map(|(tokens, _)| tokens)
+    }
+
+    /// Split the input into tokens like `tokenize`, but emit a newline token at the end of every line.
+    /// Newlines inside comments are ignored.
+    pub fn tokenize_lines(
+        contents: &mut dyn BufRead,
+        variable_ordering: Option<Vec<NamedSymbol>>,
+    ) -> io::Result<Vec<SymbolicBDDToken>> {
+        Self::tokenize_impl(contents, variable_ordering, true, false).map(|(tokens, _)| tokens)
+    }
+
+    // split the input into tokens, and collect the comments preceding the first token
+    fn tokenize_impl(
+        contents: &mut dyn BufRead,
+        variable_ordering: Option<Vec<NamedSymbol>>,
+        newlines: bool,
+        sort_vars: bool,
+    ) -> io::Result<(Vec<SymbolicBDDToken>, Vec<String>)> {
         let mut src: String = String::new();
         let mut result = Vec::new();
 
-        let mut variable_indexes: FxHashMap<String, usize> = FxHashMap::default();
-        let mut var_id_counter: usize = 0;
+        let mut variable_ids = VariableIds::new(variable_ordering);
+
+        contents.read_to_string(&mut src)?;
+
+        // pre-assign the ids of all new variables in sorted order, such that the ids do not depend on the order of occurrence
+        if sort_vars {
+            let mut new_vars: Vec<String> = Vec::new();
+
+            Self::variable_names(&src, &mut new_vars)?;
+
+            for var in new_vars.iter().sorted().dedup() {
+                variable_ids.named_var(var);
+            }
+        }
+
+        Self::tokenize_str(&src, &mut variable_ids, newlines, &mut result)?;
 
-        if let Some(variables) = variable_ordering {
-            for var in variables {
-                variable_indexes.insert(var.name.as_ref().clone(), var.id);
-                if var.id >= var_id_counter {
-                    var_id_counter = var.id + 1;
+        result.push(SymbolicBDDToken::Eof);
+
+        let mut metadata = Vec::new();
+        leading_comments(&src, &mut metadata);
+
+        Ok((result, metadata))
+    }
+
+    // append the tokens of the source to the result, the end of the source is not marked by an eof token
+    // collect the names of all variables occurring in the input, in order of occurrence
+    fn variable_names(src: &str, names: &mut Vec<String>) -> io::Result<()> {
+        for c in TOKENIZER.captures_iter(src) {
+            if let Some(range) = Self::range_names(&c)? {
+                names.extend(range);
+            } else if c.name("domain").is_some() {
+                // either the declared variable, or 'var' itself if it is not followed by a name
+                names.push(Self::domain_name(&c).unwrap_or("var").to_string());
+            } else if let Some(when) = c.name("when") {
+                if !Self::is_when_formula(&c) {
+                    names.push(when.as_str().to_string());
                 }
+                Self::variable_names(c.name("when_next").map_or("", |m| m.as_str()), names)?;
+            } else if let Some(identifier) = c
+                .name("identifier")
+                .filter(|identifier| Self::keyword(identifier.as_str()).is_none())
+                .or_else(|| Self::quoted_identifier(&c))
+            {
+                names.push(identifier.as_str().to_string());
             }
         }
 
-        contents.read_to_string(&mut src)?;
+        Ok(())
+    }
 
-        for c in TOKENIZER.captures_iter(src.as_str()) {
-            if let Some(symbol) = c.name("symbol") {
+    fn tokenize_str(
+        src: &str,
+        variable_ids: &mut VariableIds,
+        newlines: bool,
+        result: &mut Vec<SymbolicBDDToken>,
+    ) -> io::Result<()> {
+        for c in TOKENIZER.captures_iter(src) {
+            if let Some(names) = Self::range_names(&c)? {
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        result.push(SymbolicBDDToken::Comma);
+                    }
+                    result.push(variable_ids.named_var(name));
+                }
+            } else if let Some(domain) = c.name("domain") {
+                if let Some(name) = Self::domain_name(&c) {
+                    result.push(SymbolicBDDToken::Domain);
+                    result.push(variable_ids.named_var(name));
+                } else {
+                    // 'var' followed by a keyword or number is a variable named 'var'
+                    result.push(variable_ids.named_var(domain.as_str()));
+                    let rest = c.name("domain_name").map_or("", |m| m.as_str());
+                    Self::tokenize_str(rest, variable_ids, newlines, result)?;
+                }
+            } else if let Some(builtin) = c.name("builtin") {
+                result.push(match builtin.as_str() {
+                    "parity" => SymbolicBDDToken::Parity,
+                    "majority" => SymbolicBDDToken::Majority,
+                    _ => SymbolicBDDToken::AllEq,
+                });
+                result.push(SymbolicBDDToken::OpenParen);
+            } else if let Some(when) = c.name("when") {
+                // 'when' followed by anything but the start of a formula is a variable named 'when'
+                result.push(if Self::is_when_formula(&c) {
+                    SymbolicBDDToken::When
+                } else {
+                    variable_ids.named_var(when.as_str())
+                });
+                let rest = c.name("when_next").map_or("", |m| m.as_str());
+                Self::tokenize_str(rest, variable_ids, newlines, result)?;
+            } else if let Some(symbol) = c.name("symbol") {
                 match symbol.as_str() {
-                    "&" | "*" => result.push(SymbolicBDDToken::And),
-                    "|" | "+" => result.push(SymbolicBDDToken::Or),
-                    "^" => result.push(SymbolicBDDToken::Xor),
-                    "-" | "!" => result.push(SymbolicBDDToken::Not),
-                    "=>" => result.push(SymbolicBDDToken::Implies),
+                    "&" | "∧" => result.push(SymbolicBDDToken::And),
+                    "*" => result.push(SymbolicBDDToken::Star),
+                    "|" | "∨" => result.push(SymbolicBDDToken::Or),
+                    "+" => result.push(SymbolicBDDToken::Plus),
+                    "^" | "⊕" => result.push(SymbolicBDDToken::Xor),
+                    "!" | "¬" => result.push(SymbolicBDDToken::Not),
+                    "-" => result.push(SymbolicBDDToken::Minus),
+                    "=>" | "→" => result.push(SymbolicBDDToken::Implies),
                     "<=" => result.push(SymbolicBDDToken::ImpliesInv),
-                    "<=>" => result.push(SymbolicBDDToken::Iff),
+                    "<=>" | "↔" => result.push(SymbolicBDDToken::Iff),
+                    "∀" => result.push(SymbolicBDDToken::Forall),
+                    "∃" => result.push(SymbolicBDDToken::Exists),
                     "#" => result.push(SymbolicBDDToken::Hash),
                     "=" => result.push(SymbolicBDDToken::Eq),
+                    "!=" => result.push(SymbolicBDDToken::NotEq),
                     "<" => result.push(SymbolicBDDToken::Lt),
                     ">" => result.push(SymbolicBDDToken::Gt),
                     ">=" => result.push(SymbolicBDDToken::Geq),
@@ -741,6 +2567,8 @@ impl SymbolicBDD {
                     "[" => result.push(SymbolicBDDToken::OpenSquare),
                     "]" => result.push(SymbolicBDDToken::CloseSquare),
                     "," => result.push(SymbolicBDDToken::Comma),
+                    ".." => result.push(SymbolicBDDToken::Range),
+                    ";" => result.push(SymbolicBDDToken::Semicolon),
                     _ => {
                         return Err(io::Error::new(
                             io::ErrorKind::InvalidData,
@@ -751,67 +2579,330 @@ impl SymbolicBDD {
             } else if let Some(reference) = c.name("reference") {
                 result.push(SymbolicBDDToken::Reference(reference.as_str().to_string()));
             } else if let Some(identifier) = c.name("identifier") {
-                match identifier.as_str() {
-                    "false" => result.push(SymbolicBDDToken::False),
-                    "true" => result.push(SymbolicBDDToken::True),
-                    "not" => result.push(SymbolicBDDToken::Not),
-                    "and" => result.push(SymbolicBDDToken::And),
-                    "or" => result.push(SymbolicBDDToken::Or),
-                    "xor" => result.push(SymbolicBDDToken::Xor),
-                    "nor" => result.push(SymbolicBDDToken::Nor),
-                    "nand" => result.push(SymbolicBDDToken::Nand),
-                    "implies" | "in" => result.push(SymbolicBDDToken::Implies),
-                    "iff" | "eq" => result.push(SymbolicBDDToken::Iff),
-                    "exists" | "any" => result.push(SymbolicBDDToken::Exists),
-                    "forall" | "all" => result.push(SymbolicBDDToken::Forall),
-                    "if" => result.push(SymbolicBDDToken::If),
-                    "then" => result.push(SymbolicBDDToken::Then),
-                    "else" => result.push(SymbolicBDDToken::Else),
-                    "gfp" | "nu" => result.push(SymbolicBDDToken::GFP),
-                    "lfp" | "mu" => result.push(SymbolicBDDToken::LFP),
-                    var => {
-                        let var_str = var.to_string();
-                        let var_id: usize;
-
-                        if let Some(id) = variable_indexes.get(&var_str) {
-                            var_id = *id;
-                        } else {
-                            var_id = var_id_counter;
-                            var_id_counter += 1;
-
-                            variable_indexes.insert(var_str.clone(), var_id);
-                        }
-
-                        result.push(SymbolicBDDToken::Var(NamedSymbol {
-                            name: Rc::new(var_str),
-                            id: var_id,
-                        }))
-                    }
+                match (Self::keyword(identifier.as_str()), identifier.as_str()) {
+                    (Some(keyword), _) => result.push(keyword),
+                    (None, var) => result.push(variable_ids.named_var(var)),
                 }
+            } else if let Some(quoted) = Self::quoted_identifier(&c) {
+                result.push(variable_ids.named_var(quoted.as_str()));
             } else if let Some(number) = c.name("countable") {
-                let parsed_number = number.as_str().parse().expect("Failed to parse number");
-                result.push(SymbolicBDDToken::Countable(parsed_number));
-            } else if c.name("eof").is_some() {
-                result.push(SymbolicBDDToken::Eof);
-            } else if c.name("comment").is_some() {
-                // ignore comments
+                result.push(SymbolicBDDToken::Countable(Self::parse_number(
+                    number.as_str(),
+                )?));
+            } else if c.name("newline").is_some() {
+                if newlines {
+                    result.push(SymbolicBDDToken::Newline);
+                }
+            } else if c.name("eof").is_some() || c.name("comment").is_some() {
+                // the eof token is added by the caller, comments are ignored
             } else {
                 return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown token"));
             }
         }
 
-        // force-insert EOF if not exists
-        if result.last() != Some(&SymbolicBDDToken::Eof) {
-            result.push(SymbolicBDDToken::Eof);
+        Ok(())
+    }
+}
+
+// the ids assigned to the variable names seen so far
+struct VariableIds {
+    indexes: FxHashMap<String, usize>,
+    next_id: usize,
+}
+
+impl VariableIds {
+    fn new(variable_ordering: Option<Vec<NamedSymbol>>) -> Self {
+        let mut ids = Self {
+            indexes: FxHashMap::default(),
+            next_id: 0,
+        };
+
+        for var in variable_ordering.into_iter().flatten() {
+            ids.indexes.insert(var.name.as_ref().clone(), var.id);
+            ids.next_id = ids.next_id.max(var.id + 1);
+        }
+
+        ids
+    }
+
+    // the variable token with the given name, assigning a new id if the name has not been seen before
+    fn named_var(&mut self, var: &str) -> SymbolicBDDToken {
+        let var_id = if let Some(id) = self.indexes.get(var) {
+            *id
+        } else {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.indexes.insert(var.to_string(), id);
+            id
+        };
+
+        SymbolicBDDToken::Var(NamedSymbol {
+            name: Rc::new(var.to_string()),
+            id: var_id,
+        })
+    }
+}
+
+/// A tokenizer which reads the input line by line, such that only the tokens of a single line are held in memory.
+///
+/// The tokens are identical to those of [`SymbolicBDD::tokenize`], ending with a single eof token.
+/// Comments spanning multiple lines are read as a whole.
+pub struct TokenStream<'r> {
+    contents: &'r mut dyn BufRead,
+    variable_ids: VariableIds,
+    pending: VecDeque<SymbolicBDDToken>,
+    // all distinct variables emitted so far, in order of first occurrence
+    vars: Vec<NamedSymbol>,
+    seen: FxHashSet<usize>,
+    // the comments read before the first token
+    metadata: Vec<String>,
+    leading: bool,
+    finished: bool,
+}
+
+impl<'r> TokenStream<'r> {
+    pub fn new(contents: &'r mut dyn BufRead, variable_ordering: Option<Vec<NamedSymbol>>) -> Self {
+        Self {
+            contents,
+            variable_ids: VariableIds::new(variable_ordering),
+            pending: VecDeque::new(),
+            vars: Vec::new(),
+            seen: FxHashSet::default(),
+            metadata: Vec::new(),
+            leading: true,
+            finished: false,
+        }
+    }
+
+    /// The comments preceding the first token, see [`ParsedFormula::metadata`].
+    pub fn metadata(&self) -> &[String] {
+        &self.metadata
+    }
+
+    /// All distinct variables read so far, in order of first occurrence, like [`ParsedFormula::extract_vars`].
+    pub fn vars(&self) -> &[NamedSymbol] {
+        &self.vars
+    }
+
+    // tokenize the next line, extended with the following lines while it contains an unterminated comment
+    // returns false if the input is exhausted
+    fn read_chunk(&mut self) -> io::Result<bool> {
+        let mut chunk = String::new();
+
+        if self.contents.read_line(&mut chunk)? == 0 {
+            return Ok(false);
+        }
+
+        while has_open_comment(&chunk) {
+            if self.contents.read_line(&mut chunk)? == 0 {
+                break;
+            }
         }
 
-        Ok(result)
+        if self.leading {
+            self.leading = leading_comments(&chunk, &mut self.metadata);
+        }
+
+        let mut tokens = Vec::new();
+        SymbolicBDD::tokenize_str(&chunk, &mut self.variable_ids, false, &mut tokens)?;
+
+        for token in &tokens {
+            if let SymbolicBDDToken::Var(var) = token {
+                if self.seen.insert(var.id) {
+                    self.vars.push(var.clone());
+                }
+            }
+        }
+
+        self.pending.extend(tokens);
+
+        Ok(true)
+    }
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = io::Result<SymbolicBDDToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !self.finished {
+            match self.read_chunk() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.finished = true;
+                    self.pending.push_back(SymbolicBDDToken::Eof);
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+// the variables in bdd subtrees and bdd definitions carry their ids, which cannot be reassigned
+fn unsupported_reordering() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "reordering a formula with BDD references/subtrees is not supported",
+    )
+}
+
+// the negation of a folded formula, removing double negations
+fn negate(formula: SymbolicBDD) -> SymbolicBDD {
+    match formula {
+        SymbolicBDD::True => SymbolicBDD::False,
+        SymbolicBDD::False => SymbolicBDD::True,
+        SymbolicBDD::Not(f) => *f,
+        f => SymbolicBDD::Not(Box::new(f)),
+    }
+}
+
+// apply the identities of the binary operator when either of the folded operands is constant
+fn fold_binary_op(op: BinaryOperator, l: SymbolicBDD, r: SymbolicBDD) -> SymbolicBDD {
+    use SymbolicBDD::{False, True};
+
+    match (op, l, r) {
+        (BinaryOperator::And, False, _) | (BinaryOperator::And, _, False) => False,
+        (BinaryOperator::And, True, f) | (BinaryOperator::And, f, True) => f,
+        (BinaryOperator::Or, True, _) | (BinaryOperator::Or, _, True) => True,
+        (BinaryOperator::Or, False, f) | (BinaryOperator::Or, f, False) => f,
+        (BinaryOperator::Nand, False, _) | (BinaryOperator::Nand, _, False) => True,
+        (BinaryOperator::Nand, True, f) | (BinaryOperator::Nand, f, True) => negate(f),
+        (BinaryOperator::Nor, True, _) | (BinaryOperator::Nor, _, True) => False,
+        (BinaryOperator::Nor, False, f) | (BinaryOperator::Nor, f, False) => negate(f),
+        (BinaryOperator::Xor, False, f) | (BinaryOperator::Xor, f, False) => f,
+        (BinaryOperator::Xor, True, f) | (BinaryOperator::Xor, f, True) => negate(f),
+        (BinaryOperator::Iff, True, f) | (BinaryOperator::Iff, f, True) => f,
+        (BinaryOperator::Iff, False, f) | (BinaryOperator::Iff, f, False) => negate(f),
+        (BinaryOperator::Implies, False, _)
+        | (BinaryOperator::Implies, _, True)
+        | (BinaryOperator::ImpliesInv, _, False)
+        | (BinaryOperator::ImpliesInv, True, _) => True,
+        (BinaryOperator::Implies, True, f) | (BinaryOperator::ImpliesInv, f, True) => f,
+        (BinaryOperator::Implies, f, False) | (BinaryOperator::ImpliesInv, False, f) => negate(f),
+        (op, l, r) => SymbolicBDD::BinaryOp(op, Box::new(l), Box::new(r)),
+    }
+}
+
+// append the comments preceding the first token of the source to the metadata, without their quotes and surrounding
+// whitespace; returns true if the source contains no other tokens, such that the comments may continue after it
+fn leading_comments(src: &str, metadata: &mut Vec<String>) -> bool {
+    for c in TOKENIZER.captures_iter(src) {
+        if let Some(comment) = c.name("comment") {
+            metadata.push(comment.as_str().trim_matches('"').trim().to_string());
+        } else if c.name("newline").is_none() && c.name("eof").is_none() {
+            return false;
+        }
+    }
+
+    true
+}
+
+// check whether the source contains a quote which is not part of any token, i.e. a comment that is not closed yet
+fn has_open_comment(src: &str) -> bool {
+    if !src.contains('"') {
+        return false;
+    }
+
+    let mut end = 0;
+    for m in TOKENIZER.find_iter(src) {
+        if src[end..m.start()].contains('"') {
+            return true;
+        }
+        end = m.end();
+    }
+
+    src[end..].contains('"')
+}
+
+// a token source for the parser with arbitrary lookahead, reading either a list of tokens or a token stream
+// the first error of the source is kept aside and ends the tokens, see TokenReader::finish
+struct TokenReader<'a> {
+    source: Box<dyn Iterator<Item = io::Result<SymbolicBDDToken>> + 'a>,
+    buffer: VecDeque<SymbolicBDDToken>,
+    last: Option<SymbolicBDDToken>,
+    error: Option<io::Error>,
+    depth: usize,
+    too_deep: bool,
+}
+
+impl<'a> TokenReader<'a> {
+    fn new(tokens: &'a [SymbolicBDDToken]) -> Self {
+        Self::from_source(tokens.iter().cloned().map(Ok))
+    }
+
+    fn from_source(source: impl Iterator<Item = io::Result<SymbolicBDDToken>> + 'a) -> Self {
+        Self {
+            source: Box::new(source),
+            buffer: VecDeque::new(),
+            last: None,
+            error: None,
+            depth: 0,
+            too_deep: false,
+        }
+    }
+
+    // enter a nested sub-formula, failing when exceeding the maximum nesting depth
+    // once exceeded, every further attempt fails as well, such that failover parsing cannot recover from it
+    fn descend(&mut self) -> io::Result<()> {
+        self.too_deep |= self.depth >= MAX_NESTING_DEPTH;
+
+        if self.too_deep {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Formula exceeds the maximum nesting depth of {}",
+                    MAX_NESTING_DEPTH
+                ),
+            ));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    const fn ascend(&mut self) {
+        self.depth -= 1;
+    }
+
+    // make sure the buffer holds at least n tokens, unless the source is exhausted
+    fn fill(&mut self, n: usize) {
+        while self.buffer.len() < n && self.error.is_none() {
+            match self.source.next() {
+                Some(Ok(token)) => self.buffer.push_back(token),
+                Some(Err(e)) => self.error = Some(e),
+                None => break,
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<&SymbolicBDDToken> {
+        self.peek_nth(0)
+    }
+
+    fn peek_nth(&mut self, n: usize) -> Option<&SymbolicBDDToken> {
+        self.fill(n + 1);
+        self.buffer.get(n)
+    }
+
+    fn next(&mut self) -> Option<&SymbolicBDDToken> {
+        self.fill(1);
+        self.last = self.buffer.pop_front();
+        self.last.as_ref()
+    }
+
+    // the result of parsing, unless reading the tokens failed, in which case the read error takes precedence
+    fn finish<T>(self, result: io::Result<T>) -> io::Result<T> {
+        self.error.map_or(result, Err)
     }
 }
 
 fn expect(token: SymbolicBDDToken, tokens: &mut TokenReader) -> io::Result<()> {
-    match &tokens.next() {
-        &Some(t) if *t == token => Ok(()),
+    match tokens.next() {
+        Some(t) if *t == token => Ok(()),
         t => Err(io::Error::new(
             io::ErrorKind::InvalidData,
             format!("Expected {:?}, got {:?}", token, t),
@@ -821,7 +2912,7 @@ fn expect(token: SymbolicBDDToken, tokens: &mut TokenReader) -> io::Result<()> {
 
 fn check(token: SymbolicBDDToken, tokens: &mut TokenReader) -> io::Result<()> {
     match tokens.peek() {
-        Some(&t) if *t == token => Ok(()),
+        Some(t) if *t == token => Ok(()),
         t => Err(io::Error::new(
             io::ErrorKind::InvalidData,
             format!(
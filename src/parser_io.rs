@@ -2,31 +2,209 @@ extern crate dot;
 
 use std::io;
 use std::io::Write;
+use std::rc::Rc;
 
 use itertools::Itertools;
+use rustc_hash::FxHashSet;
 
+use crate::bdd::BDD;
+use crate::escape::{dot_label, json_string, sanitize_name};
 use crate::parser::*;
+use crate::NamedSymbol;
 
 pub struct SymbolicParseTree {
     pub internal_tree: SymbolicBDD,
     pub nodes: Vec<SymbolicBDD>,
+    flatten: bool,
 }
 
 type GraphNode = usize;
 type GraphEdge = (usize, String, usize);
 
+// the operands of a chain of an associative binary operator, which is rendered as a single node with an edge to
+// every operand
+struct FlatOp<'a>(Vec<&'a SymbolicBDD>);
+
+impl<'a> FlatOp<'a> {
+    // the flattened chain rooted at the node, if the node applies an associative operator
+    fn new(node: &'a SymbolicBDD) -> Option<Self> {
+        match node {
+            SymbolicBDD::BinaryOp(
+                op @ (BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor),
+                _,
+                _,
+            ) => {
+                let mut operands = Vec::new();
+                Self::collect_operands(*op, node, &mut operands);
+                Some(Self(operands))
+            }
+            _ => None,
+        }
+    }
+
+    fn collect_operands(
+        op: BinaryOperator,
+        node: &'a SymbolicBDD,
+        operands: &mut Vec<&'a SymbolicBDD>,
+    ) {
+        match node {
+            SymbolicBDD::BinaryOp(node_op, l, r) if *node_op == op => {
+                Self::collect_operands(op, l, operands);
+                Self::collect_operands(op, r, operands);
+            }
+            _ => operands.push(node),
+        }
+    }
+}
+
 impl SymbolicParseTree {
     pub fn render_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
         dot::render(self, writer)
     }
 
-    fn nodes_recursive(root: &SymbolicBDD) -> Vec<SymbolicBDD> {
+    /// Render chains of the associative operators `&`, `|` and `^` as a single node with an edge to every operand,
+    /// instead of a nested binary node per operator, e.g. `a & b & c` results in one `And` node with three children.
+    /// The evaluation of the formula is not affected.
+    pub fn with_flatten(mut self, flatten: bool) -> Self {
+        self.flatten = flatten;
+        self.nodes = Self::nodes_recursive(&self.internal_tree, flatten)
+            .into_iter()
+            .unique()
+            .collect();
+        self
+    }
+
+    // the flattened chain rooted at the node, if flattening is enabled
+    fn flat_op<'a>(&self, node: &'a SymbolicBDD) -> Option<FlatOp<'a>> {
+        FlatOp::new(node).filter(|_| self.flatten)
+    }
+
+    /// Write the parse tree as JSON: a flat list of nodes, where every node refers to its children by id.
+    /// Each node contains its kind, operator, variables and thresholds, such that the formula can be reconstructed.
+    pub fn to_json<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{{")?;
+        writeln!(
+            writer,
+            "  \"root\": {},",
+            self.index_of(&self.internal_tree)
+        )?;
+        writeln!(writer, "  \"nodes\": [")?;
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let separator = if i + 1 < self.nodes.len() { "," } else { "" };
+            writeln!(
+                writer,
+                "    {{\"id\": {i}, {}}}{separator}",
+                self.node_json(node)
+            )?;
+        }
+
+        writeln!(writer, "  ]")?;
+        writeln!(writer, "}}")
+    }
+
+    // the json fields of a single node (excluding its id)
+    fn node_json(&self, node: &SymbolicBDD) -> String {
+        let children = |c: &[&SymbolicBDD]| -> String {
+            c.iter().map(|f| self.index_of(f).to_string()).join(", ")
+        };
+        let list = |l: &[SymbolicBDD]| -> String { l.iter().map(|f| self.index_of(f)).join(", ") };
+
+        match node {
+            SymbolicBDD::False => "\"kind\": \"False\"".to_string(),
+            SymbolicBDD::True => "\"kind\": \"True\"".to_string(),
+            SymbolicBDD::Var(v) => format!(
+                "\"kind\": \"Var\", \"name\": {}, \"var_id\": {}",
+                json_string(&v.name),
+                v.id
+            ),
+            SymbolicBDD::Not(f) => format!("\"kind\": \"Not\", \"children\": [{}]", children(&[f])),
+            SymbolicBDD::Quantifier(q, vars, f) => format!(
+                "\"kind\": \"Quantifier\", \"operator\": \"{:?}\", \"variables\": [{}], \"children\": [{}]",
+                q,
+                vars.iter().map(|v| json_string(&v.name)).join(", "),
+                children(&[f])
+            ),
+            SymbolicBDD::CountableConst(op, l, n) => format!(
+                "\"kind\": \"CountableConst\", \"operator\": \"{:?}\", \"threshold\": {}, \"children\": [{}]",
+                op,
+                n,
+                list(l)
+            ),
+            SymbolicBDD::WeightedCountable(op, w, l, n) => format!(
+                "\"kind\": \"WeightedCountable\", \"operator\": \"{:?}\", \"threshold\": {}, \"weights\": [{}], \"children\": [{}]",
+                op,
+                n,
+                w.iter().join(", "),
+                list(l)
+            ),
+            SymbolicBDD::CountableVariable(op, l, r, k) => format!(
+                "\"kind\": \"CountableVariable\", \"operator\": \"{:?}\", \"offset\": {}, \"left\": [{}], \"right\": [{}]",
+                op,
+                k,
+                list(l),
+                list(r)
+            ),
+            SymbolicBDD::FixedPoint(v, init, f) => format!(
+                "\"kind\": \"FixedPoint\", \"operator\": \"{}\", \"variables\": [{}], \"children\": [{}]",
+                if *init { "GFP" } else { "LFP" },
+                json_string(&v.name),
+                children(&[f])
+            ),
+            SymbolicBDD::Ite(c, t, e) => format!(
+                "\"kind\": \"Ite\", \"children\": [{}]",
+                children(&[c, t, e])
+            ),
+            SymbolicBDD::BinaryOp(op, l, r) => format!(
+                "\"kind\": \"BinaryOp\", \"operator\": \"{:?}\", \"children\": [{}]",
+                op,
+                self.flat_op(node)
+                    .map_or_else(|| children(&[l, r]), |FlatOp(operands)| children(&operands))
+            ),
+            SymbolicBDD::Subtree(_) => "\"kind\": \"Subtree\"".to_string(),
+            SymbolicBDD::Reference(name) => {
+                format!("\"kind\": \"Reference\", \"name\": {}", json_string(name))
+            }
+            SymbolicBDD::DomainEq(v, index, value) => format!(
+                "\"kind\": \"DomainEq\", \"name\": {}, \"index\": {}, \"value\": {}",
+                json_string(&v.name),
+                index.map_or_else(|| "null".to_string(), |i| i.to_string()),
+                value
+            ),
+            SymbolicBDD::Conjunction(l) => {
+                format!("\"kind\": \"Conjunction\", \"children\": [{}]", list(l))
+            }
+            SymbolicBDD::NaryOp(op, l) => format!(
+                "\"kind\": \"NaryOp\", \"operator\": \"{:?}\", \"children\": [{}]",
+                op,
+                list(l)
+            ),
+        }
+    }
+
+    fn index_of(&self, node: &SymbolicBDD) -> usize {
+        self.nodes
+            .iter()
+            .position(|n| n == node)
+            .expect("cannot find position")
+    }
+
+    fn nodes_recursive(root: &SymbolicBDD, flatten: bool) -> Vec<SymbolicBDD> {
         let this_node = vec![root.clone()];
 
+        // the intermediate nodes of a flattened chain are not rendered
+        if let Some(FlatOp(operands)) = FlatOp::new(root).filter(|_| flatten) {
+            return operands
+                .into_iter()
+                .flat_map(|operand| Self::nodes_recursive(operand, flatten))
+                .chain(this_node)
+                .collect();
+        }
+
         match root {
             SymbolicBDD::BinaryOp(_, l, r) => {
-                let left_nodes = Self::nodes_recursive(l);
-                let right_nodes = Self::nodes_recursive(r);
+                let left_nodes = Self::nodes_recursive(l, flatten);
+                let right_nodes = Self::nodes_recursive(r, flatten);
 
                 left_nodes
                     .into_iter()
@@ -37,28 +215,31 @@ impl SymbolicParseTree {
             SymbolicBDD::Quantifier(_, _, f)
             | SymbolicBDD::Not(f)
             | SymbolicBDD::FixedPoint(_, _, f) => {
-                let new_nodes = Self::nodes_recursive(f);
+                let new_nodes = Self::nodes_recursive(f, flatten);
 
                 new_nodes.into_iter().chain(this_node).collect()
             }
-            SymbolicBDD::CountableConst(_, f, _) => {
+            SymbolicBDD::CountableConst(_, f, _)
+            | SymbolicBDD::WeightedCountable(_, _, f, _)
+            | SymbolicBDD::Conjunction(f)
+            | SymbolicBDD::NaryOp(_, f) => {
                 let mut new_nodes: Vec<SymbolicBDD> = this_node;
 
                 for subtree in f {
-                    new_nodes.extend(Self::nodes_recursive(subtree));
+                    new_nodes.extend(Self::nodes_recursive(subtree, flatten));
                 }
 
                 new_nodes
             }
-            SymbolicBDD::CountableVariable(_, a, b) => {
+            SymbolicBDD::CountableVariable(_, a, b, _) => {
                 let mut new_nodes: Vec<SymbolicBDD> = this_node;
 
                 for subtree in a {
-                    new_nodes.extend(Self::nodes_recursive(subtree));
+                    new_nodes.extend(Self::nodes_recursive(subtree, flatten));
                 }
 
                 for subtree in b {
-                    new_nodes.extend(Self::nodes_recursive(subtree));
+                    new_nodes.extend(Self::nodes_recursive(subtree, flatten));
                 }
 
                 new_nodes
@@ -66,9 +247,9 @@ impl SymbolicParseTree {
             SymbolicBDD::Ite(c, t, e) => {
                 let mut new_nodes: Vec<SymbolicBDD> = this_node;
 
-                new_nodes.extend(Self::nodes_recursive(c));
-                new_nodes.extend(Self::nodes_recursive(t));
-                new_nodes.extend(Self::nodes_recursive(e));
+                new_nodes.extend(Self::nodes_recursive(c, flatten));
+                new_nodes.extend(Self::nodes_recursive(t, flatten));
+                new_nodes.extend(Self::nodes_recursive(e, flatten));
 
                 new_nodes
             }
@@ -76,14 +257,19 @@ impl SymbolicParseTree {
             | SymbolicBDD::False
             | SymbolicBDD::Var(_)
             | SymbolicBDD::Subtree(_)
-            | SymbolicBDD::Reference(_) => this_node,
+            | SymbolicBDD::Reference(_)
+            | SymbolicBDD::DomainEq(_, _, _) => this_node,
         }
     }
 
     pub fn new(src: &SymbolicBDD) -> Self {
         Self {
             internal_tree: src.clone(),
-            nodes: Self::nodes_recursive(src).into_iter().unique().collect(),
+            nodes: Self::nodes_recursive(src, false)
+                .into_iter()
+                .unique()
+                .collect(),
+            flatten: false,
         }
     }
 }
@@ -99,34 +285,50 @@ impl<'a> dot::Labeller<'a, GraphNode, GraphEdge> for SymbolicParseTree {
     }
 
     fn node_label(&self, n: &GraphNode) -> dot::LabelText<'a> {
-        match &self.nodes[*n] {
-            SymbolicBDD::BinaryOp(ref op, _, _) => dot::LabelText::label(format!("{:?}", op)),
-            SymbolicBDD::Quantifier(op, ref v, _) => dot::LabelText::label(format!(
+        let label = match &self.nodes[*n] {
+            SymbolicBDD::BinaryOp(ref op, _, _) => format!("{:?}", op),
+            SymbolicBDD::Quantifier(op, ref v, _) => format!(
                 "{:?} [{}]",
                 op,
                 v.iter().map(|s| s.name.as_ref()).cloned().join(", ")
-            )),
-            SymbolicBDD::Not(_) => dot::LabelText::label("Not".to_string()),
+            ),
+            SymbolicBDD::Not(_) => "Not".to_string(),
             SymbolicBDD::CountableConst(ref v, _, n) => {
-                dot::LabelText::label(format!("{:?} {}", v, n))
+                format!("{:?} {}", v, n)
+            }
+            SymbolicBDD::WeightedCountable(ref v, _, _, n) => {
+                format!("Weighted {:?} {}", v, n)
+            }
+            SymbolicBDD::CountableVariable(ref v, _, _, 0) => {
+                format!("{:?}", v)
             }
-            SymbolicBDD::CountableVariable(ref v, _, _) => {
-                dot::LabelText::label(format!("{:?}", v))
+            SymbolicBDD::CountableVariable(ref v, _, _, k) => {
+                format!("{:?} {:+}", v, k)
             }
             SymbolicBDD::FixedPoint(ref v, init, _) => {
                 if *init {
-                    dot::LabelText::label(format!("GFP {}", v))
+                    format!("GFP {}", v)
                 } else {
-                    dot::LabelText::label(format!("LFP {}", v))
+                    format!("LFP {}", v)
                 }
             }
-            SymbolicBDD::Ite(_, _, _) => dot::LabelText::label("Ite".to_string()),
-            SymbolicBDD::False => dot::LabelText::label("False".to_string()),
-            SymbolicBDD::True => dot::LabelText::label("True".to_string()),
-            SymbolicBDD::Var(v) => dot::LabelText::label(format!("Var {}", v)),
-            SymbolicBDD::Subtree(_) => dot::LabelText::label("BDD".to_string()),
-            SymbolicBDD::Reference(name) => dot::LabelText::label(format!("Ref {name}")),
-        }
+            SymbolicBDD::Ite(_, _, _) => "Ite".to_string(),
+            SymbolicBDD::Conjunction(_) => "Conjunction".to_string(),
+            SymbolicBDD::NaryOp(op, _) => format!("{:?}", op),
+            SymbolicBDD::False => "False".to_string(),
+            SymbolicBDD::True => "True".to_string(),
+            SymbolicBDD::Var(v) => format!("Var {}", v),
+            SymbolicBDD::Subtree(_) => "BDD".to_string(),
+            SymbolicBDD::Reference(name) => format!("Ref {name}"),
+            SymbolicBDD::DomainEq(v, Some(index), value) => {
+                format!("{v}[{index}] = {value}")
+            }
+            SymbolicBDD::DomainEq(v, None, value) => {
+                format!("{v} = {value}")
+            }
+        };
+
+        dot_label(&label)
     }
 
     fn edge_label(&self, e: &GraphEdge) -> dot::LabelText<'a> {
@@ -143,6 +345,13 @@ impl<'a> dot::GraphWalk<'a, GraphNode, GraphEdge> for SymbolicParseTree {
         let mut edges: Vec<GraphEdge> = Vec::new();
 
         for (i, node) in self.nodes.iter().enumerate() {
+            if let Some(FlatOp(operands)) = self.flat_op(node) {
+                for (j, operand) in operands.into_iter().enumerate() {
+                    edges.push((i, format!("{{{}}}", j), self.index_of(operand)));
+                }
+                continue;
+            }
+
             match node {
                 SymbolicBDD::BinaryOp(_, l, r) => {
                     edges.push((
@@ -174,7 +383,9 @@ impl<'a> dot::GraphWalk<'a, GraphNode, GraphEdge> for SymbolicParseTree {
                             .expect("cannot find position"),
                     ));
                 }
-                SymbolicBDD::CountableConst(_, f, _) => {
+                SymbolicBDD::CountableConst(_, f, _)
+                | SymbolicBDD::Conjunction(f)
+                | SymbolicBDD::NaryOp(_, f) => {
                     for (j, subtree) in f.iter().enumerate() {
                         edges.push((
                             i,
@@ -186,7 +397,19 @@ impl<'a> dot::GraphWalk<'a, GraphNode, GraphEdge> for SymbolicParseTree {
                         ));
                     }
                 }
-                SymbolicBDD::CountableVariable(_, a, b) => {
+                SymbolicBDD::WeightedCountable(_, w, f, _) => {
+                    for (j, (weight, subtree)) in w.iter().zip(f).enumerate() {
+                        edges.push((
+                            i,
+                            format!("{weight} * {{{j}}}"),
+                            self.nodes
+                                .iter()
+                                .position(|n| n == subtree)
+                                .expect("cannot find position"),
+                        ));
+                    }
+                }
+                SymbolicBDD::CountableVariable(_, a, b, _) => {
                     for (j, subtree) in a.iter().enumerate() {
                         edges.push((
                             i,
@@ -238,7 +461,8 @@ impl<'a> dot::GraphWalk<'a, GraphNode, GraphEdge> for SymbolicParseTree {
                 | SymbolicBDD::True
                 | SymbolicBDD::Var(_)
                 | SymbolicBDD::Subtree(_)
-                | SymbolicBDD::Reference(_) => {}
+                | SymbolicBDD::Reference(_)
+                | SymbolicBDD::DomainEq(_, _, _) => {}
             }
         }
 
@@ -253,3 +477,62 @@ impl<'a> dot::GraphWalk<'a, GraphNode, GraphEdge> for SymbolicParseTree {
         e.2
     }
 }
+
+/// Write the bdd as a single-output BLIF model, e.g. for logic synthesis tools such as ABC or SIS.
+///
+/// The inputs are the free variables of the parsed formula and the output is named `out`. The cover of the output is the
+/// sum of products of the bdd, see [`crate::bdd::BDDEnv::to_sop`]. Variable names are sanitized to BLIF identifiers by
+/// replacing all characters other than letters, digits and underscores.
+pub fn write_blif<W: Write>(
+    parsed: &ParsedFormula,
+    bdd: &Rc<BDD<NamedSymbol>>,
+    w: &mut W,
+) -> io::Result<()> {
+    let names = blif_names(&parsed.free_vars);
+
+    writeln!(w, ".model formula")?;
+    writeln!(w, ".inputs {}", names.iter().join(" "))?;
+    writeln!(w, ".outputs out")?;
+    writeln!(w, ".names {} out", names.iter().join(" "))?;
+
+    for cube in parsed.env.to_sop(bdd).cubes {
+        let row: String = parsed
+            .free_vars
+            .iter()
+            .map(|v| match cube.get(v) {
+                Some(true) => '1',
+                Some(false) => '0',
+                None => '-',
+            })
+            .collect();
+
+        if row.is_empty() {
+            writeln!(w, "1")?;
+        } else {
+            writeln!(w, "{row} 1")?;
+        }
+    }
+
+    writeln!(w, ".end")
+}
+
+// unique BLIF identifiers for the variables, which do not collide with the output
+fn blif_names(vars: &[NamedSymbol]) -> Vec<String> {
+    let mut used: FxHashSet<String> = FxHashSet::default();
+    used.insert("out".to_string());
+
+    vars.iter()
+        .map(|v| {
+            let base = sanitize_name(&v.name);
+
+            let mut name = base.clone();
+            let mut suffix = 1;
+            while !used.insert(name.clone()) {
+                name = format!("{base}_{suffix}");
+                suffix += 1;
+            }
+
+            name
+        })
+        .collect()
+}
@@ -26,3 +26,43 @@ pub fn write_gnuplot_normal_distribution<S: Write>(
 
     Ok(())
 }
+
+/// Write a textual histogram of the samples.
+///
+/// The samples are divided into the given number of equally sized bins between min and max.
+/// Every bin is rendered as a bar of '#' characters, scaled such that the largest bin has a width of 50.
+pub fn write_ascii_histogram<S: Write>(
+    writer: &mut S,
+    samples: &[f64],
+    min: f64,
+    max: f64,
+    bins: usize,
+) -> io::Result<()> {
+    const WIDTH: usize = 50;
+
+    // all samples are equal (or there is a single sample), use a single bin
+    let bins = if max > min { bins.max(1) } else { 1 };
+    let bin_width = (max - min) / bins as f64;
+
+    let mut counts = vec![0usize; bins];
+    for &sample in samples {
+        let bin = if bin_width > 0.0 {
+            (((sample - min) / bin_width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+
+    let largest = counts.iter().copied().max().unwrap_or_default().max(1);
+
+    for (i, count) in counts.iter().enumerate() {
+        let lower = (i as f64).mul_add(bin_width, min);
+        let upper = lower + bin_width;
+        let bar = "#".repeat((count * WIDTH).div_ceil(largest));
+
+        writeln!(writer, "{lower:.4}s - {upper:.4}s | {bar:<WIDTH$} {count}")?;
+    }
+
+    Ok(())
+}
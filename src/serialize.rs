@@ -0,0 +1,155 @@
+//! Serialization of bdds using serde.
+//!
+//! A bdd is serialized as a table of its unique nodes rather than as a recursive structure, such that shared subtrees
+//! are written only once. The terminals have the fixed indices 0 (false) and 1 (true), every choice refers to its
+//! subtrees by index, and only refers to nodes preceding it in the table. A deserialized table must describe a reduced
+//! and ordered bdd: the nodes are unique, have distinct subtrees, and their symbols precede the symbols of their subtrees.
+
+use std::rc::Rc;
+
+use rustc_hash::FxHashMap;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::bdd::BDD;
+use crate::BDDSymbol;
+
+// the index of the first choice in the node table
+const FIRST_CHOICE: usize = 2;
+
+// a choice in the node table: the index of the true-subtree, the symbol, and the index of the false-subtree
+type TableNode<S> = (usize, S, usize);
+
+#[derive(Serialize)]
+struct NodeTableRef<'a, S> {
+    nodes: Vec<TableNode<&'a S>>,
+    root: usize,
+    // the indices of the nodes in the table, such that structurally identical nodes are written once
+    #[serde(skip)]
+    unique: FxHashMap<TableNode<&'a S>, usize>,
+}
+
+impl<'a, S: BDDSymbol> NodeTableRef<'a, S> {
+    // add a choice to the table, unless it is already part of it, returning the index of the choice
+    fn push(&mut self, t: usize, v: &'a S, f: usize) -> usize {
+        let next = FIRST_CHOICE + self.nodes.len();
+        let index = *self.unique.entry((t, v, f)).or_insert(next);
+
+        if index == next {
+            self.nodes.push((t, v, f));
+        }
+
+        index
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeTable<S> {
+    nodes: Vec<TableNode<S>>,
+    root: usize,
+}
+
+impl<S: BDDSymbol + Serialize> Serialize for BDD<S> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        let mut table = NodeTableRef {
+            nodes: Vec::new(),
+            root: 0,
+            unique: FxHashMap::default(),
+        };
+        let mut indices: FxHashMap<*const Self, usize> = FxHashMap::default();
+
+        // the root is not behind an Rc, therefore it is added separately
+        table.root = match self {
+            Self::False => 0,
+            Self::True => 1,
+            Self::Choice(t, v, f, _) => {
+                let t = add_node(t, &mut table, &mut indices);
+                let f = add_node(f, &mut table, &mut indices);
+                table.push(t, v, f)
+            }
+        };
+
+        table.serialize(serializer)
+    }
+}
+
+// add the node and its subtrees to the table in post-order, returning the index of the node
+fn add_node<'a, S: BDDSymbol>(
+    node: &'a Rc<BDD<S>>,
+    table: &mut NodeTableRef<'a, S>,
+    indices: &mut FxHashMap<*const BDD<S>, usize>,
+) -> usize {
+    match node.as_ref() {
+        BDD::False => 0,
+        BDD::True => 1,
+        BDD::Choice(t, v, f, _) => {
+            if let Some(&index) = indices.get(&Rc::as_ptr(node)) {
+                return index;
+            }
+
+            let t = add_node(t, table, indices);
+            let f = add_node(f, table, indices);
+            let index = table.push(t, v, f);
+            indices.insert(Rc::as_ptr(node), index);
+            index
+        }
+    }
+}
+
+impl<'de, S: BDDSymbol + Deserialize<'de>> Deserialize<'de> for BDD<S> {
+    /// Rebuild the bdd from its node table, the nodes are shared as in the serialized bdd.
+    ///
+    /// The nodes are not added to the lookup table of an environment, but compare equal to the nodes of the original bdd.
+    /// Tables which do not describe a reduced and ordered bdd are rejected.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = NodeTable::<S>::deserialize(deserializer)?;
+
+        let mut nodes: Vec<Rc<Self>> = vec![Rc::new(Self::False), Rc::new(Self::True)];
+        let mut unique: FxHashMap<(usize, S, usize), usize> = FxHashMap::default();
+
+        for (index, (t, v, f)) in table.nodes.into_iter().enumerate() {
+            let index = FIRST_CHOICE + index;
+            let subtree = |i: usize| {
+                nodes.get(i).filter(|_| i < index).cloned().ok_or_else(|| {
+                    D::Error::custom(format!("node {index} refers to undefined node {i}"))
+                })
+            };
+
+            let (t_node, f_node) = (subtree(t)?, subtree(f)?);
+
+            // since the nodes are unique, identical subtrees have the same index
+            if t == f {
+                return Err(D::Error::custom(format!(
+                    "node {index} has identical subtrees {t}"
+                )));
+            }
+
+            for (i, child) in [(t, &t_node), (f, &f_node)] {
+                match child.as_ref() {
+                    Self::Choice(_, w, _, _) if *w <= v => {
+                        return Err(D::Error::custom(format!(
+                            "node {index} is not ordered before its subtree {i}"
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(other) = unique.insert((t, v.clone(), f), index) {
+                return Err(D::Error::custom(format!(
+                    "node {index} is a duplicate of node {other}"
+                )));
+            }
+
+            nodes.push(Rc::new(Self::choice(t_node, v, f_node)));
+        }
+
+        // cloning the root only clones the references to its subtrees
+        nodes
+            .get(table.root)
+            .map(|root| root.as_ref().clone())
+            .ok_or_else(|| {
+                D::Error::custom(format!("the root refers to undefined node {}", table.root))
+            })
+    }
+}
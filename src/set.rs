@@ -60,15 +60,11 @@ impl BDDSet {
     }
 
     pub fn insert<T: BDDCategorizable>(&self, e: T) -> &Self {
-        let new_item = (0..self.bits)
-            .map(|i| {
-                if e.categorize(i) {
-                    self.env.var(i)
-                } else {
-                    self.env.not(self.env.var(i))
-                }
-            })
-            .fold(self.env.mk_const(true), |a, e| self.env.and(a, e));
+        let new_item = self.env.cube(
+            &(0..self.bits)
+                .map(|i| (i, e.categorize(i)))
+                .collect::<Vec<_>>(),
+        );
 
         let _self = self.bdd.borrow().clone();
 
@@ -102,6 +98,80 @@ impl BDDSet {
 
     pub fn contains<T: BDDCategorizable>(&self, e: T) -> bool {
         let singleton = Self::from_element(e, self.bits, &self.env);
-        self.intersect(&singleton) == &singleton
+        let singleton_bdd = singleton.bdd.borrow().clone();
+
+        // intersect without modifying self
+        self.env
+            .and(self.bdd.borrow().clone(), Rc::clone(&singleton_bdd))
+            == singleton_bdd
+    }
+
+    /// The number of elements in this set
+    pub fn cardinality(&self) -> u128 {
+        self.env.satcount(&self.bdd.borrow(), self.bits)
+    }
+
+    /// Iterate over all elements in this set, in ascending order.
+    ///
+    /// Variables of the bdd outside of the bits of this set, e.g. of a set created using [`BDDSet::from_bdd`], are
+    /// existentially quantified: an element is a member if it is a member for any value of these variables.
+    pub fn iter_members(&self) -> impl Iterator<Item = usize> {
+        let mut members = Vec::new();
+        self.members_recursive(&self.bdd.borrow(), 0, 0, &mut members);
+
+        members.sort_unstable();
+        members.into_iter()
+    }
+
+    // collect all elements below the node, where value holds the bits decided above the given bit
+    fn members_recursive(
+        &self,
+        node: &Rc<BDD<usize>>,
+        bit: usize,
+        value: usize,
+        members: &mut Vec<usize>,
+    ) {
+        match node.as_ref() {
+            BDD::False => {}
+            // the remaining variables are outside of the bits of the set, and any node other than false is satisfiable
+            _ if bit >= self.bits => members.push(value),
+            // a variable is true iff the corresponding bit is zero, see BDDCategorizable
+            BDD::Choice(t, v, e, _) if *v == bit => {
+                self.members_recursive(t, bit + 1, value, members);
+                self.members_recursive(e, bit + 1, value | (1 << bit), members);
+            }
+            // the bit does not occur in the node, so the subset contains both values
+            _ => {
+                self.members_recursive(node, bit + 1, value, members);
+                self.members_recursive(node, bit + 1, value | (1 << bit), members);
+            }
+        }
+    }
+
+    /// The subset of elements satisfying the predicate, in the same environment.
+    ///
+    /// The elements are enumerated using [`BDDSet::iter_members`], so variables outside of the bits of this set are
+    /// existentially quantified.
+    pub fn filter<F: Fn(usize) -> bool>(&self, pred: F) -> Self {
+        let result = Self::with_env(self.bits, &self.env);
+        for e in self.iter_members().filter(|&e| pred(e)) {
+            result.insert(e);
+        }
+
+        result
+    }
+
+    /// The image of this set under the function, in the same environment.
+    ///
+    /// Elements of the image which do not fit in the number of bits of this set are truncated.
+    /// The elements are enumerated using [`BDDSet::iter_members`], so variables outside of the bits of this set are
+    /// existentially quantified.
+    pub fn map<F: Fn(usize) -> usize>(&self, f: F) -> Self {
+        let result = Self::with_env(self.bits, &self.env);
+        for e in self.iter_members() {
+            result.insert(f(e));
+        }
+
+        result
     }
 }
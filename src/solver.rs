@@ -0,0 +1,69 @@
+use std::rc::Rc;
+
+use crate::bdd::*;
+use crate::BDDSymbol;
+
+/// An incremental solver maintaining the conjunction of all asserted constraints.
+///
+/// Scopes can be opened with [`Solver::push`] and closed with [`Solver::pop`], which discards all constraints asserted
+/// since the matching push. Since bdds are immutable and shared, a scope only stores the root of the conjunction.
+#[derive(Debug, Clone)]
+pub struct Solver<S: BDDSymbol> {
+    pub env: Rc<BDDEnv<S>>,
+    root: Rc<BDD<S>>,
+    scopes: Vec<Rc<BDD<S>>>,
+}
+
+impl<S: BDDSymbol> Solver<S> {
+    /// Create a new solver without constraints in the given environment.
+    pub fn new(env: Rc<BDDEnv<S>>) -> Self {
+        let root = env.mk_const(true);
+
+        Self {
+            env,
+            root,
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Add a constraint to the current conjunction.
+    pub fn assert(&mut self, b: Rc<BDD<S>>) {
+        self.root = self.env.and(Rc::clone(&self.root), b);
+    }
+
+    /// Check whether the conjunction of all asserted constraints is satisfiable.
+    pub fn is_sat(&self) -> bool {
+        !self.root.is_false()
+    }
+
+    /// A single satisfying model of the current conjunction, see [`BDDEnv::model`].
+    pub fn model(&self) -> Rc<BDD<S>> {
+        self.env.model(Rc::clone(&self.root))
+    }
+
+    /// The conjunction of all asserted constraints.
+    pub const fn root(&self) -> &Rc<BDD<S>> {
+        &self.root
+    }
+
+    /// Open a new scope, saving the current conjunction.
+    pub fn push(&mut self) {
+        self.scopes.push(Rc::clone(&self.root));
+    }
+
+    /// Close the innermost scope, restoring the conjunction to its state at the matching push.
+    /// Returns false if there is no open scope, in which case the conjunction is left unchanged.
+    pub fn pop(&mut self) -> bool {
+        if let Some(root) = self.scopes.pop() {
+            self.root = root;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The number of open scopes.
+    pub const fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+}
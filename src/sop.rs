@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+use itertools::Itertools;
+
+use crate::bdd::*;
+use crate::BDDSymbol;
+
+/// A conjunction of literals, mapping every variable in the cube to its polarity.
+pub type Cube<S> = BTreeMap<S, bool>;
+
+/// A disjunction of cubes, e.g. `a&!b | c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumOfProducts<S: BDDSymbol> {
+    pub cubes: Vec<Cube<S>>,
+}
+
+impl<S: BDDSymbol> SumOfProducts<S> {
+    /// Construct the bdd representing this sum of products in the given environment.
+    pub fn to_bdd(&self, env: &BDDEnv<S>) -> Rc<BDD<S>> {
+        self.cubes.iter().fold(env.mk_const(false), |acc, cube| {
+            env.or(acc, Self::cube_to_bdd(cube, env))
+        })
+    }
+
+    fn cube_to_bdd(cube: &Cube<S>, env: &BDDEnv<S>) -> Rc<BDD<S>> {
+        env.cube(
+            &cube
+                .iter()
+                .map(|(v, polarity)| (v.clone(), *polarity))
+                .collect_vec(),
+        )
+    }
+}
+
+impl<S: BDDSymbol> fmt::Display for SumOfProducts<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cubes.is_empty() {
+            return write!(f, "false");
+        }
+
+        let cubes = self.cubes.iter().map(|cube| {
+            if cube.is_empty() {
+                "true".to_string()
+            } else {
+                cube.iter()
+                    .map(|(v, polarity)| {
+                        if *polarity {
+                            format!("{v}")
+                        } else {
+                            format!("!{v}")
+                        }
+                    })
+                    .join("&")
+            }
+        });
+
+        write!(f, "{}", cubes.format(" | "))
+    }
+}
+
+impl<S: BDDSymbol> BDDEnv<S> {
+    /// Extract a sum of products from the bdd.
+    ///
+    /// The cubes are derived from the paths to true, after which all prime implicants are computed by iterated
+    /// consensus and absorption. Finally, every cube covered by the remaining cubes is removed.
+    /// The result is an irredundant cover of prime implicants, which is not necessarily of minimal size.
+    pub fn to_sop(&self, root: &Rc<BDD<S>>) -> SumOfProducts<S> {
+        let mut cubes: Vec<Cube<S>> = Vec::new();
+        Self::collect_cubes(root, &mut Cube::new(), &mut cubes);
+
+        let primes = Self::prime_implicants(cubes);
+
+        // remove cubes which are covered by the other cubes, larger cubes first
+        let mut cover: Vec<Cube<S>> = primes;
+        cover.sort_by_key(|cube| std::cmp::Reverse(cube.len()));
+
+        let mut i = 0;
+        while i < cover.len() {
+            let others = SumOfProducts {
+                cubes: cover
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, c)| c.clone())
+                    .collect(),
+            };
+
+            let cube = SumOfProducts::cube_to_bdd(&cover[i], self);
+            if self.implies(cube, others.to_bdd(self)).is_true() {
+                cover.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        cover.sort();
+
+        SumOfProducts { cubes: cover }
+    }
+
+    // collect the cubes corresponding to all paths leading to true
+    fn collect_cubes(node: &Rc<BDD<S>>, path: &mut Cube<S>, cubes: &mut Vec<Cube<S>>) {
+        match node.as_ref() {
+            BDD::False => {}
+            BDD::True => cubes.push(path.clone()),
+            BDD::Choice(t, v, f, _) => {
+                path.insert(v.clone(), true);
+                Self::collect_cubes(t, path, cubes);
+                path.insert(v.clone(), false);
+                Self::collect_cubes(f, path, cubes);
+                path.remove(v);
+            }
+        }
+    }
+
+    // compute all prime implicants by repeatedly adding consensus terms and removing absorbed cubes
+    fn prime_implicants(mut cubes: Vec<Cube<S>>) -> Vec<Cube<S>> {
+        cubes = Self::absorb(cubes);
+
+        loop {
+            let mut new_cubes: Vec<Cube<S>> = Vec::new();
+
+            for (a, b) in cubes.iter().tuple_combinations() {
+                if let Some(consensus) = Self::consensus(a, b) {
+                    let absorbed = cubes
+                        .iter()
+                        .chain(new_cubes.iter())
+                        .any(|c| Self::subsumes(c, &consensus));
+
+                    if !absorbed {
+                        new_cubes.push(consensus);
+                    }
+                }
+            }
+
+            if new_cubes.is_empty() {
+                return cubes;
+            }
+
+            cubes.extend(new_cubes);
+            cubes = Self::absorb(cubes);
+        }
+    }
+
+    // the consensus of two cubes which conflict in exactly one variable
+    fn consensus(a: &Cube<S>, b: &Cube<S>) -> Option<Cube<S>> {
+        let conflicts: Vec<&S> = a
+            .iter()
+            .filter(|(v, polarity)| b.get(v).is_some_and(|p| p != *polarity))
+            .map(|(v, _)| v)
+            .collect();
+
+        match conflicts.as_slice() {
+            [v] => Some(
+                a.iter()
+                    .chain(b.iter())
+                    .filter(|(w, _)| w != v)
+                    .map(|(w, p)| (w.clone(), *p))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    // a subsumes b if every literal of a occurs in b, i.e. b implies a
+    fn subsumes(a: &Cube<S>, b: &Cube<S>) -> bool {
+        a.iter().all(|(v, polarity)| b.get(v) == Some(polarity))
+    }
+
+    // remove duplicate cubes and all cubes subsumed by another cube
+    fn absorb(cubes: Vec<Cube<S>>) -> Vec<Cube<S>> {
+        let cubes: Vec<Cube<S>> = cubes.into_iter().unique().collect();
+
+        cubes
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| {
+                !cubes
+                    .iter()
+                    .enumerate()
+                    .any(|(j, d)| *i != j && Self::subsumes(d, c))
+            })
+            .map(|(_, c)| c.clone())
+            .collect()
+    }
+}
@@ -9,6 +9,7 @@ pub trait BDDSymbol: Ord + Display + Debug + Clone + Hash {}
 impl<T> BDDSymbol for T where T: Ord + Display + Debug + Clone + Hash {}
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedSymbol {
     pub name: Rc<String>,
     pub id: usize,
@@ -1,9 +1,15 @@
 use std::{
     fmt::{self, Display},
+    rc::Rc,
     str::FromStr,
 };
 
+use rustc_hash::FxHashMap;
+
+use crate::{bdd::BDD, BDDSymbol};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Single variable assignment in a truth table.
 ///
 /// The variable assignments in a truth table can be one of True, False or Any.
@@ -71,3 +77,52 @@ impl FromStr for TruthTableEntry {
             .copied()
     }
 }
+
+/// The rows of the truth table of a bdd, i.e. the assignment and result of every path to a terminal.
+///
+/// Variables skipped on a path are assigned [`TruthTableEntry::Any`]. The paths are visited depth-first,
+/// following the false branch of every choice first.
+pub struct TruthTableRows<S: BDDSymbol> {
+    // the position of every variable in the rows
+    index: FxHashMap<S, usize>,
+    stack: Vec<(Rc<BDD<S>>, Vec<TruthTableEntry>)>,
+}
+
+impl<S: BDDSymbol> TruthTableRows<S> {
+    pub fn new(root: &Rc<BDD<S>>, vars: &[S]) -> Self {
+        Self {
+            index: vars.iter().cloned().zip(0..).collect(),
+            stack: vec![(Rc::clone(root), vec![TruthTableEntry::Any; vars.len()])],
+        }
+    }
+}
+
+impl<S: BDDSymbol> Iterator for TruthTableRows<S> {
+    type Item = (Vec<TruthTableEntry>, TruthTableEntry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, row)) = self.stack.pop() {
+            match node.as_ref() {
+                BDD::True => return Some((row, TruthTableEntry::True)),
+                BDD::False => return Some((row, TruthTableEntry::False)),
+                BDD::Choice(t, v, f, _) => {
+                    let i = *self
+                        .index
+                        .get(v)
+                        .unwrap_or_else(|| panic!("{v} is not a free variable"));
+
+                    // the false branch is pushed last, such that it is visited first
+                    let mut t_row = row.clone();
+                    t_row[i] = TruthTableEntry::True;
+                    self.stack.push((Rc::clone(t), t_row));
+
+                    let mut f_row = row;
+                    f_row[i] = TruthTableEntry::False;
+                    self.stack.push((Rc::clone(f), f_row));
+                }
+            }
+        }
+
+        None
+    }
+}
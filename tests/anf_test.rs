@@ -0,0 +1,36 @@
+use std::io;
+use std::io::BufReader;
+
+use pretty_assertions::assert_eq;
+
+use rsbdd::anf::*;
+use rsbdd::bdd::*;
+use rsbdd::parser::*;
+
+fn anf_of(formula: &str) -> io::Result<String> {
+    let parsed = ParsedFormula::new(&mut BufReader::new(formula.as_bytes()), None)?;
+    Ok(format_anf(&parsed.env.to_anf(&parsed.eval())))
+}
+
+#[test]
+fn test_anf_constants() {
+    let e: BDDEnv<usize> = BDDEnv::new();
+
+    assert!(e.to_anf(&e.mk_const(false)).is_empty());
+    assert_eq!(e.to_anf(&e.mk_const(true)), vec![Vec::<usize>::new()]);
+    assert_eq!(e.to_anf(&e.var(3)), vec![vec![3]]);
+    assert_eq!(e.to_anf(&e.not(e.var(3))), vec![vec![], vec![3]]);
+}
+
+#[test]
+fn test_anf_formulas() -> io::Result<()> {
+    assert_eq!(anf_of("false")?, "0");
+    assert_eq!(anf_of("true")?, "1");
+    assert_eq!(anf_of("a ^ b")?, "a ^ b");
+    assert_eq!(anf_of("a | b")?, "a ^ b ^ a&b");
+    assert_eq!(anf_of("!a | b")?, "1 ^ a ^ a&b");
+    assert_eq!(anf_of("a & b & c")?, "a&b&c");
+    assert_eq!(anf_of("a <=> b")?, "1 ^ a ^ b");
+
+    Ok(())
+}
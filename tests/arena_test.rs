@@ -0,0 +1,86 @@
+#![cfg(feature = "arena")]
+
+use std::rc::Rc;
+
+use pretty_assertions::assert_eq;
+
+use rsbdd::arena::*;
+use rsbdd::bdd::{BDDEnv, BDD};
+
+// all pairs of cells on an n by n board which cannot both contain a queen
+fn queen_conflicts(n: usize) -> Vec<(usize, usize)> {
+    let attacks = |a: usize, b: usize| {
+        let (ra, ca, rb, cb) = (a / n, a % n, b / n, b % n);
+        ra == rb || ca == cb || ra + cb == rb + ca || ra + ca == rb + cb
+    };
+
+    (0..n * n)
+        .flat_map(|a| (a + 1..n * n).map(move |b| (a, b)))
+        .filter(|&(a, b)| attacks(a, b))
+        .collect()
+}
+
+fn queens_standard(e: &BDDEnv<usize>, n: usize) -> Rc<BDD<usize>> {
+    let conflicts = queen_conflicts(n)
+        .into_iter()
+        .fold(e.mk_const(true), |acc, (a, b)| {
+            e.and(acc, e.not(e.and(e.var(a), e.var(b))))
+        });
+
+    (0..n).fold(conflicts, |acc, r| {
+        let row = (0..n).fold(e.mk_const(false), |row, c| e.or(row, e.var(r * n + c)));
+        e.and(acc, row)
+    })
+}
+
+fn queens_arena(e: &ArenaBDD<usize>, n: usize) -> NodeId {
+    let conflicts = queen_conflicts(n)
+        .into_iter()
+        .fold(e.mk_const(true), |acc, (a, b)| {
+            e.and(acc, e.not(e.and(e.var(a), e.var(b))))
+        });
+
+    (0..n).fold(conflicts, |acc, r| {
+        let row = (0..n).fold(e.mk_const(false), |row, c| e.or(row, e.var(r * n + c)));
+        e.and(acc, row)
+    })
+}
+
+#[test]
+fn test_arena_basic() {
+    let e = ArenaBDD::new();
+    let s = BDDEnv::new();
+
+    let a = e.var(0);
+    let b = e.var(1);
+
+    assert_eq!(e.and(a, e.not(a)), FALSE);
+    assert_eq!(e.or(a, e.not(a)), TRUE);
+    assert_eq!(e.not(e.not(a)), a);
+    assert_eq!(e.implies(a, a), TRUE);
+    assert_eq!(e.and(a, b), e.and(b, a));
+
+    let nab = e.not(e.and(a, b));
+    assert_eq!(e.to_bdd(nab, &s), s.not(s.and(s.var(0), s.var(1))));
+    assert_eq!(e.from_bdd(&e.to_bdd(nab, &s)), nab);
+
+    // converting an existing function does not create new nodes
+    let size = e.size();
+    assert_eq!(e.from_bdd(&s.and(s.var(0), s.var(1))), e.and(a, b));
+    assert_eq!(e.size(), size);
+}
+
+#[test]
+fn test_arena_queens() {
+    for n in 4..=5 {
+        let s = BDDEnv::new();
+        let e = ArenaBDD::new();
+
+        let standard = queens_standard(&s, n);
+        let arena = queens_arena(&e, n);
+
+        assert_eq!(e.to_bdd(arena, &s), standard);
+        assert_eq!(e.from_bdd(&standard), arena);
+        assert_eq!(e.to_bdd(e.not(arena), &s), s.not(standard));
+    }
+}
@@ -3,6 +3,9 @@ use std::fs::File;
 use std::rc::Rc;
 
 use pretty_assertions::{assert_eq, assert_ne};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rustc_hash::FxHashMap;
 
 use rsbdd::bdd::*;
 use rsbdd::bdd_io::*;
@@ -52,6 +55,142 @@ fn test_simplifications() {
     assert_eq!(e.size(), 4);
 }
 
+#[test]
+fn test_mk_choice_unchecked() {
+    let e = BDDEnv::new();
+
+    let ab = e.and(e.var(0), e.var(1));
+    let size = e.size();
+
+    // building an existing canonical node returns the shared node
+    let unchecked = e.mk_choice_unchecked(e.var(1), 0, e.mk_const(false));
+    assert_eq!(unchecked, ab);
+    assert!(Rc::ptr_eq(&unchecked, &ab));
+    assert_eq!(e.size(), size);
+
+    // new nodes are added to the lookup table
+    let xor = e.mk_choice_unchecked(e.not(e.var(1)), 0, e.var(1));
+    assert_eq!(e.size(), size + 2);
+    assert_eq!(xor, e.xor(e.var(0), e.var(1)));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "both subtrees of 0 are equal")]
+fn test_mk_choice_unchecked_redundant() {
+    let e = BDDEnv::new();
+    e.mk_choice_unchecked(e.var(1), 0, e.var(1));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "is not ordered before")]
+fn test_mk_choice_unchecked_order() {
+    let e = BDDEnv::new();
+    e.mk_choice_unchecked(e.var(0), 1, e.mk_const(false));
+}
+
+#[test]
+fn test_and_all() {
+    let e = BDDEnv::new();
+
+    let vars: Vec<Rc<BDD>> = (0..5).map(|i| e.var(i)).collect();
+    let expected = vars
+        .iter()
+        .fold(e.mk_const(true), |acc, v| e.and(acc, Rc::clone(v)));
+
+    assert_eq!(e.and_all(vars), expected);
+    assert_eq!(e.and_all(vec![]), e.mk_const(true));
+    assert_eq!(
+        e.and_all(vec![e.var(0), e.not(e.var(0))]),
+        e.mk_const(false)
+    );
+}
+
+#[test]
+fn test_all_equal() {
+    let e = BDDEnv::new();
+
+    assert_eq!(e.all_equal(&[e.var(0), e.var(1)]), e.eq(e.var(0), e.var(1)));
+    assert_eq!(
+        e.all_equal(&[e.var(0), e.var(0), e.var(0)]),
+        e.mk_const(true)
+    );
+    assert_eq!(e.all_equal(&[e.var(0)]), e.mk_const(true));
+    assert_eq!(e.all_equal(&[]), e.mk_const(true));
+    assert_eq!(
+        e.all_equal(&[e.var(0), e.var(1), e.var(2)]),
+        e.and(e.eq(e.var(0), e.var(1)), e.eq(e.var(1), e.var(2)))
+    );
+}
+
+#[test]
+fn test_size_reachable() {
+    let e = BDDEnv::new();
+
+    // v0, v1, true, false
+    assert_eq!(e.size_reachable(&e.and(e.var(0), e.var(1))), 4);
+
+    // intermediate nodes are not reachable from the root
+    let _v2 = e.and(e.var(2), e.var(3));
+    assert_eq!(e.size_reachable(&e.var(0)), 3);
+
+    // a tautology only contains the 'true' terminal
+    assert_eq!(e.size_reachable(&e.or(e.var(0), e.not(e.var(0)))), 1);
+}
+
+#[test]
+fn test_gc() {
+    let e = BDDEnv::new();
+
+    let root = e.and(e.var(0), e.var(1));
+    let _tmp = e.amn(&[e.var(2), e.var(3), e.var(4)], 1);
+
+    let size_before = e.size();
+
+    e.gc(std::slice::from_ref(&root));
+
+    assert!(e.size() < size_before);
+    assert_eq!(e.size(), e.size_reachable(&root));
+
+    // the retained nodes are still shared with new computations
+    assert!(Rc::ptr_eq(&e.and(e.var(1), e.var(0)), &root));
+
+    // terminals are always retained
+    e.gc(&[]);
+    assert_eq!(e.size(), 2);
+    assert_eq!(e.var(0), e.not(e.not(e.var(0))));
+}
+
+#[test]
+fn test_profile_report() {
+    let e = BDDEnv::with_profiling();
+
+    assert_eq!(e.profile_report().peak_size, 2);
+
+    let _v0 = e.var(0);
+    let _v0 = e.var(0);
+
+    let report = e.profile_report();
+    assert_eq!(report.mk_choice_calls, 2);
+    assert_eq!(report.cache_hits, 1);
+    assert_eq!(report.cache_misses, 1);
+    assert_eq!(report.peak_size, 3);
+
+    e.gc(&[]);
+    assert_eq!(e.profile_report().peak_size, 3);
+
+    // profiling is disabled by default
+    let e: BDDEnv<usize> = BDDEnv::new();
+    let _v0 = e.var(0);
+    assert_eq!(e.profile_report(), ProfileReport::default());
+
+    // profiling does not affect equivalence of environments
+    let p = BDDEnv::with_profiling();
+    let _v0 = p.var(0);
+    assert_eq!(e, p);
+}
+
 #[test]
 fn test_simple_duplicates() {
     let e = BDDEnv::new();
@@ -152,6 +291,130 @@ fn test_quantifier_lists() {
     );
 }
 
+#[test]
+fn test_restrict() {
+    let e = BDDEnv::new();
+
+    let f = e.and(e.var(0), e.or(e.var(1), e.var(2)));
+
+    assert_eq!(e.restrict(f.clone(), &0, true), e.or(e.var(1), e.var(2)));
+    assert_eq!(e.restrict(f.clone(), &0, false), e.mk_const(false));
+    assert_eq!(e.restrict(f.clone(), &1, true), e.var(0));
+    assert_eq!(e.restrict(f.clone(), &3, true), f);
+}
+
+#[test]
+fn test_constrain() {
+    let e = BDDEnv::new();
+
+    // a | b is true wherever a holds
+    let f = e.or(e.var(0), e.var(1));
+    assert_eq!(e.constrain(&f, &e.var(0)), e.mk_const(true));
+
+    // with care set !a, only b remains
+    assert_eq!(e.constrain(&f, &e.not(e.var(0))), e.var(1));
+
+    // trivial care sets
+    assert_eq!(e.constrain(&f, &e.mk_const(true)), f);
+    assert_eq!(e.constrain(&f, &e.mk_const(false)), e.mk_const(false));
+    assert_eq!(e.constrain(&f, &f), e.mk_const(true));
+
+    let g = e.or(
+        e.and(e.var(0), e.var(2)),
+        e.and(e.var(1), e.xor(e.var(2), e.var(3))),
+    );
+    let care = e.or(e.and(e.var(1), e.var(3)), e.not(e.var(2)));
+    let constrained = e.constrain(&g, &care);
+
+    // the result agrees with g on the care set, and is not larger
+    assert_eq!(
+        e.and(Rc::clone(&constrained), Rc::clone(&care)),
+        e.and(Rc::clone(&g), care)
+    );
+    assert!(constrained.node_list().len() <= g.node_list().len());
+}
+
+#[test]
+fn test_simplify_with_dontcare() {
+    let e = BDDEnv::new();
+
+    // (a & b) | (!a & c) reduces to b if a always holds
+    let f = e.or(e.and(e.var(0), e.var(1)), e.and(e.not(e.var(0)), e.var(2)));
+    assert_eq!(e.simplify_with_dontcare(&f, &e.var(0)), e.var(1));
+
+    let mut rng = StdRng::seed_from_u64(2380);
+    let mut random_formula = || {
+        (0..3).fold(e.mk_const(false), |acc, _| {
+            let clause = (0..5).fold(e.mk_const(true), |c, v| match rng.gen_range(0..3) {
+                0 => c,
+                1 => e.and(c, e.var(v)),
+                _ => e.and(c, e.not(e.var(v))),
+            });
+            e.or(acc, clause)
+        })
+    };
+
+    for _ in 0..50 {
+        let f = random_formula();
+        let care = random_formula();
+        let simplified = e.simplify_with_dontcare(&f, &care);
+
+        // the result agrees with f on the care set, and is never larger
+        assert_eq!(
+            e.and(Rc::clone(&simplified), Rc::clone(&care)),
+            e.and(Rc::clone(&f), care)
+        );
+        assert!(e.size_reachable(&simplified) <= e.size_reachable(&f));
+    }
+}
+
+#[test]
+fn test_cofactors() {
+    let e = BDDEnv::new();
+
+    let f = e.or(
+        e.and(e.var(0), e.var(2)),
+        e.and(e.var(1), e.xor(e.var(2), e.var(3))),
+    );
+
+    for v in 0..5 {
+        let (high, low) = e.cofactors(&f, &v);
+
+        assert_eq!(high, e.restrict(f.clone(), &v, true));
+        assert_eq!(low, e.restrict(f.clone(), &v, false));
+
+        // shannon expansion reconstructs the original
+        assert_eq!(e.ite(e.var(v), high, low), f);
+    }
+}
+
+#[test]
+fn test_exists_with_witness() {
+    let e = BDDEnv::new();
+
+    let f = e.and(
+        e.eq(e.var(0), e.xor(e.var(1), e.var(2))),
+        e.or(e.var(0), e.var(3)),
+    );
+
+    let (result, witness) = e.exists_with_witness(&0, f.clone());
+
+    assert_eq!(result, e.exists(vec![0], f.clone()));
+
+    // substituting the witness for the eliminated variable must satisfy the original formula
+    let substituted = e.ite(
+        witness.clone(),
+        e.restrict(f.clone(), &0, true),
+        e.restrict(f.clone(), &0, false),
+    );
+    assert_eq!(substituted, result);
+
+    assert_eq!(
+        e.implies(e.and(result, e.eq(e.var(0), witness)), f),
+        e.mk_const(true)
+    );
+}
+
 #[test]
 fn test_fixedpoint() {
     let e = BDDEnv::new();
@@ -162,6 +425,42 @@ fn test_fixedpoint() {
     );
 }
 
+#[test]
+fn test_fixedpoint_progress() {
+    let e = BDDEnv::new();
+    let n = 4;
+
+    // reachability of bit vectors, starting from all zeros and setting a single bit per transition
+    let init = (0..n).fold(e.mk_const(true), |acc, i| e.and(acc, e.not(e.var(i))));
+    let post = |x: Rc<BDD>| {
+        (0..n).fold(e.mk_const(false), |acc, i| {
+            e.or(acc, e.and(e.var(i), e.restrict(Rc::clone(&x), &i, false)))
+        })
+    };
+
+    let mut reports = Vec::new();
+    let reachable = e.fp_with_progress(
+        e.mk_const(false),
+        |x: Rc<BDD>| e.or(Rc::clone(&init), e.or(Rc::clone(&x), post(x))),
+        |i, nodes| reports.push((i, nodes)),
+    );
+
+    assert_eq!(reachable, e.mk_const(true));
+    assert_eq!(
+        reachable,
+        e.fp(e.mk_const(false), |x: Rc<BDD>| e
+            .or(Rc::clone(&init), e.or(Rc::clone(&x), post(x))))
+    );
+
+    // every vector has at most n bits set, the last iteration confirms the fixed point
+    let iterations: Vec<usize> = reports.iter().map(|(i, _)| *i).collect();
+    assert_eq!(iterations, (1..=n + 2).collect::<Vec<_>>());
+    // the shared terminals are counted once
+    assert_eq!(reports[0].1, e.size_reachable(&init));
+    assert_eq!(reports[0].1, n + 2);
+    assert_eq!(reports[n + 1].1, 1);
+}
+
 #[test]
 fn test_implication_biimplication() {
     let e = BDDEnv::new();
@@ -195,6 +494,43 @@ fn test_ite() {
         e.ite(e.var(0), e.mk_const(false), e.mk_const(true)),
         e.not(e.var(0))
     );
+
+    // equal branches do not depend on the condition
+    let f = e.or(e.var(1), e.var(2));
+    assert_eq!(e.ite(e.and(e.var(0), e.var(3)), f.clone(), f.clone()), f);
+}
+
+#[test]
+fn test_ite_var() {
+    let e = BDDEnv::new();
+
+    let reference = |s: usize, b: Rc<BDD>, c: Rc<BDD>| {
+        e.and(e.implies(e.var(s), b), e.implies(e.not(e.var(s)), c))
+    };
+
+    let cases = [
+        (0, e.var(1), e.var(2)),
+        (0, e.mk_const(true), e.mk_const(false)),
+        (1, e.var(1), e.not(e.var(1))),
+        (2, e.and(e.var(0), e.var(3)), e.var(1)),
+        (3, e.var(1), e.var(1)),
+        (1, e.xor(e.var(2), e.var(4)), e.mk_const(true)),
+    ];
+
+    for (s, b, c) in cases {
+        assert_eq!(
+            e.ite_var(s, b.clone(), c.clone()),
+            reference(s, b.clone(), c.clone())
+        );
+        assert_eq!(e.ite(e.var(s), b.clone(), c.clone()), reference(s, b, c));
+    }
+
+    // an ordered choice is built directly, with a single call to mk_choice on top of the two variables
+    let d = BDDEnv::with_profiling();
+    let f = d.ite_var(0, d.var(1), d.var(2));
+    assert_eq!(d.profile_report().mk_choice_calls, 3);
+    assert_eq!(d.size(), 5);
+    assert_eq!(f, e.ite_var(0, e.var(1), e.var(2)));
 }
 
 #[test]
@@ -394,6 +730,453 @@ fn test_model() {
     assert_ne!(e.implies(model, e.var(2)), e.mk_const(true));
 }
 
+#[test]
+fn test_largest_implicant() {
+    let e = BDDEnv::new();
+
+    let cube = |literals: &[(usize, bool)]| {
+        literals.iter().fold(e.mk_const(true), |acc, &(v, value)| {
+            if value {
+                e.and(acc, e.var(v))
+            } else {
+                e.and(acc, e.not(e.var(v)))
+            }
+        })
+    };
+
+    // a | (b & c) has the single-literal implicant a
+    let f = e.or(e.var(0), e.and(e.var(1), e.var(2)));
+    assert_eq!(e.largest_implicant(&f), Some(vec![(0, true)]));
+
+    // the shortest path of (!a & b) | (a & b & c) | d is !a & b, since d occurs below a and b
+    // the greedy result is prime, but larger than the implicant d
+    let g = e.or(
+        e.or(
+            e.and(e.not(e.var(0)), e.var(1)),
+            cube(&[(0, true), (1, true), (2, true)]),
+        ),
+        e.var(3),
+    );
+    assert_eq!(e.largest_implicant(&g), Some(vec![(0, false), (1, true)]));
+
+    // the path a & b & c of (a & b & c) | (!a & b) is reduced to the prime implicant b & c
+    let h = e.or(
+        cube(&[(0, true), (1, true), (2, true)]),
+        cube(&[(0, false), (1, true)]),
+    );
+    let implicant = e.largest_implicant(&h).expect("h is satisfiable");
+    assert!(e.implies(cube(&implicant), h.clone()).is_true());
+    assert_eq!(implicant.len(), 2);
+
+    // every literal of the result is needed
+    for i in 0..implicant.len() {
+        let mut reduced = implicant.clone();
+        reduced.remove(i);
+        assert!(!e.implies(cube(&reduced), h.clone()).is_true());
+    }
+
+    // true is implied by the empty cube, while false has no implicants
+    assert_eq!(e.largest_implicant(&e.mk_const(true)), Some(vec![]));
+    assert_eq!(e.largest_implicant(&e.mk_const(false)), None);
+    assert_eq!(
+        e.largest_implicant(&e.not(e.var(4))),
+        Some(vec![(4, false)])
+    );
+}
+
+#[test]
+fn test_path_count() {
+    let e = BDDEnv::new();
+
+    assert_eq!(e.path_count(&e.mk_const(false)), 0);
+    assert_eq!(e.path_count(&e.mk_const(true)), 1);
+    assert_eq!(e.path_count(&e.var(1)), 1);
+
+    // a | b has the paths a and !a & b, which skip b and the unused variable c
+    let a_or_b = e.or(e.var(0), e.var(1));
+    assert_eq!(e.path_count(&a_or_b), 2);
+    assert_eq!(e.satcount(&a_or_b, 3), 6);
+
+    // xor does not skip any variable, so both counts are equal
+    let xor = e.xor(e.var(0), e.xor(e.var(1), e.var(2)));
+    assert_eq!(e.path_count(&xor), 4);
+    assert_eq!(e.satcount(&xor, 3), 4);
+
+    // the paths of a bdd form a disjoint cover
+    let vars: Vec<Rc<BDD>> = (0..6).map(|i| e.var(i)).collect();
+    let f = e.aln(&vars, 5);
+    assert_eq!(e.path_count(&f), 6);
+    assert_eq!(e.satcount(&f, 6), 7);
+}
+
+#[test]
+fn test_model_min_max() {
+    let e = BDDEnv::new();
+
+    let a_or_b = e.or(e.var(0), e.var(1));
+    assert_eq!(e.model_min(&a_or_b), e.and(e.not(e.var(0)), e.var(1)));
+    assert_eq!(e.model_max(&a_or_b), e.var(0));
+
+    let xor = e.xor(e.var(0), e.xor(e.var(1), e.var(2)));
+    assert_eq!(
+        e.model_min(&xor),
+        e.and(e.not(e.var(0)), e.and(e.not(e.var(1)), e.var(2)))
+    );
+    assert_eq!(
+        e.model_max(&xor),
+        e.and(e.var(0), e.and(e.var(1), e.var(2)))
+    );
+
+    // the smallest model of exactly two out of four variables sets the last two
+    let vars: Vec<Rc<BDD>> = (0..4).map(|i| e.var(i)).collect();
+    let two = e.exn(&vars, 2);
+    assert_eq!(
+        e.model_min(&two),
+        e.and(
+            e.and(e.not(e.var(0)), e.not(e.var(1))),
+            e.and(e.var(2), e.var(3))
+        )
+    );
+    assert_eq!(
+        e.model_max(&two),
+        e.and(
+            e.and(e.var(0), e.var(1)),
+            e.and(e.not(e.var(2)), e.not(e.var(3)))
+        )
+    );
+
+    for f in [a_or_b, xor, two] {
+        assert!(e.implies(e.model_min(&f), f.clone()).is_true());
+        assert!(e.implies(e.model_max(&f), f.clone()).is_true());
+    }
+
+    assert_eq!(e.model_min(&e.mk_const(false)), e.mk_const(false));
+    assert_eq!(e.model_max(&e.mk_const(true)), e.mk_const(true));
+}
+
+#[test]
+fn test_satcount() {
+    let e = BDDEnv::new();
+
+    assert_eq!(e.satcount(&e.mk_const(false), 3), 0);
+    assert_eq!(e.satcount(&e.mk_const(true), 3), 8);
+    assert_eq!(e.satcount(&e.var(1), 3), 4);
+    assert_eq!(e.satcount(&e.or(e.var(0), e.var(2)), 3), 6);
+    assert_eq!(e.satcount(&e.or(e.var(0), e.var(2)), 4), 12);
+
+    let vars: Vec<Rc<BDD>> = (0..10).map(|i| e.var(i)).collect();
+    assert_eq!(e.satcount(&e.exn(&vars, 3), 10), 120);
+
+    assert_eq!(e.satcount(&e.var(0), 127), 1 << 126);
+}
+
+#[test]
+#[should_panic(expected = "does not fit in a u128")]
+fn test_satcount_overflow() {
+    let e = BDDEnv::new();
+
+    e.satcount(&e.var(0), 128);
+}
+
+#[test]
+fn test_hamming_leq() {
+    let e = BDDEnv::new();
+    let n = 5;
+    let vars: Vec<usize> = (0..n).collect();
+    let reference = [true, false, false, true, true];
+
+    for d in 0..=(n as i64 + 1) {
+        let ball = e.hamming_leq(&vars, &reference, d);
+
+        // compare against the distance of every assignment
+        for bits in 0..(1 << n) {
+            let assignment: FxHashMap<usize, bool> =
+                vars.iter().map(|&v| (v, bits & (1 << v) != 0)).collect();
+            let distance = vars
+                .iter()
+                .filter(|&v| assignment[v] != reference[*v])
+                .count() as i64;
+
+            assert_eq!(e.eval_assignment(&ball, &assignment), distance <= d);
+        }
+    }
+
+    // distance 0 only matches the reference, a distance of at least the length always holds
+    assert_eq!(e.satcount(&e.hamming_leq(&vars, &reference, 0), n), 1);
+    assert_eq!(e.hamming_leq(&vars, &reference, n as i64), e.mk_const(true));
+    assert_eq!(e.hamming_leq(&vars, &reference, -1), e.mk_const(false));
+}
+
+#[test]
+fn test_project() {
+    let e = BDDEnv::new();
+
+    // the relation {(x, y, z) | x => y, y ^ z}
+    let relation = e.and(e.implies(e.var(0), e.var(1)), e.xor(e.var(1), e.var(2)));
+
+    let projected = e.project(&[0, 2], Rc::clone(&relation), &[0, 1, 2]);
+    assert_eq!(projected, e.exists(vec![1], Rc::clone(&relation)));
+    // only x = 1, z = 1 has no y satisfying the relation
+    assert_eq!(projected, e.not(e.and(e.var(0), e.var(2))));
+
+    // keeping all variables, or quantifying none of them, leaves the relation unchanged
+    assert_eq!(
+        e.project(&[0, 1, 2], Rc::clone(&relation), &[0, 1, 2]),
+        relation
+    );
+    assert_eq!(e.project(&[], Rc::clone(&relation), &[]), relation);
+    assert_eq!(e.project(&[], relation, &[0, 1, 2]), e.mk_const(true));
+}
+
+#[test]
+fn test_count_models_matching() {
+    let e = BDDEnv::new();
+    let a_or_b = e.or(e.var(0), e.var(1));
+
+    // with a fixed to false, only b = true remains
+    assert_eq!(e.count_models_matching(&a_or_b, &[(0, false)], 2), 1);
+    assert_eq!(e.count_models_matching(&a_or_b, &[(0, true)], 2), 2);
+    // every unconstrained variable doubles the count
+    assert_eq!(e.count_models_matching(&a_or_b, &[(0, false)], 3), 2);
+    assert_eq!(
+        e.count_models_matching(&a_or_b, &[(0, false), (1, false)], 3),
+        0
+    );
+    assert_eq!(e.count_models_matching(&a_or_b, &[], 2), 3);
+}
+
+#[test]
+fn test_count_up_to() {
+    let e = BDDEnv::new();
+    let vars: Vec<Rc<BDD>> = (0..40).map(|i| e.var(i)).collect();
+    let any = vars
+        .iter()
+        .fold(e.mk_const(false), |acc, v| e.or(acc, Rc::clone(v)));
+
+    // the disjunction has 2^40 - 1 models, but counting stops at the limit
+    assert_eq!(e.count_up_to(&any, 40, 2), 2);
+    assert_eq!(e.count_up_to(&any, 40, u128::MAX), (1 << 40) - 1);
+
+    let a_and_b = e.and(e.var(0), e.var(1));
+    assert_eq!(e.count_up_to(&a_and_b, 2, 2), 1);
+    assert_eq!(e.count_up_to(&a_and_b, 3, 10), 2);
+    assert_eq!(e.count_up_to(&e.mk_const(false), 3, 10), 0);
+    assert_eq!(e.count_up_to(&e.mk_const(true), 3, 5), 5);
+    assert_eq!(e.count_up_to(&e.mk_const(true), 200, u128::MAX), u128::MAX);
+
+    // the parity of 100 variables has 2^99 paths to true, which are not visited one by one
+    let (_, parity) =
+        (0..100)
+            .rev()
+            .fold((e.mk_const(true), e.mk_const(false)), |(even, odd), i| {
+                (
+                    e.ite_var(i, Rc::clone(&odd), Rc::clone(&even)),
+                    e.ite_var(i, even, odd),
+                )
+            });
+    assert_eq!(e.count_up_to(&parity, 100, u128::MAX), 1 << 99);
+    assert_eq!(e.count_up_to(&parity, 130, u128::MAX), u128::MAX);
+    assert_eq!(e.count_up_to(&parity, 100, 1000), 1000);
+
+    // variables skipped on some paths
+    let ite = e.ite(e.var(0), e.and(e.var(1), e.var(2)), e.var(3));
+    assert_eq!(e.count_up_to(&ite, 4, u128::MAX), e.satcount(&ite, 4));
+    assert_eq!(e.count_up_to(&ite, 4, 5), 5);
+}
+
+#[test]
+fn test_marginal_prob() {
+    let e = BDDEnv::new();
+    let a_or_b = e.or(e.var(0), e.var(1));
+
+    // a is true in 2 of the 3 models 01, 10 and 11
+    assert!((e.marginal_prob(&a_or_b, &0, 2) - 2.0 / 3.0).abs() < 1e-9);
+    // unconstrained variables do not change the fraction
+    assert!((e.marginal_prob(&a_or_b, &2, 3) - 0.5).abs() < 1e-9);
+    assert_eq!(e.marginal_prob(&e.mk_const(false), &0, 2), 0.0);
+}
+
+#[test]
+fn test_marginal_counts() {
+    let e = BDDEnv::new();
+    let vars: Vec<usize> = (0..6).collect();
+    let mut rng = StdRng::seed_from_u64(2370);
+
+    for _ in 0..20 {
+        // a random formula, which does not depend on the last variable
+        let root = (0..4).fold(e.mk_const(false), |acc, _| {
+            let clause = (0..5).fold(e.mk_const(true), |c, v| match rng.gen_range(0..3) {
+                0 => c,
+                1 => e.and(c, e.var(v)),
+                _ => e.and(c, e.not(e.var(v))),
+            });
+            e.or(acc, clause)
+        });
+
+        let total = e.satcount(&root, vars.len());
+        let counts = e.marginal_counts(&root, &vars);
+
+        for (v, (t, f)) in vars.iter().zip(&counts) {
+            assert_eq!(t + f, total);
+            assert_eq!(
+                *t,
+                e.count_models_matching(&root, &[(*v, true)], vars.len())
+            );
+        }
+        assert_eq!(counts[5].0, counts[5].1);
+    }
+
+    // a | b over a, b and c
+    let a_or_b = e.or(e.var(0), e.var(1));
+    assert_eq!(
+        e.marginal_counts(&a_or_b, &[2, 0, 1]),
+        vec![(3, 3), (4, 2), (4, 2)]
+    );
+}
+
+#[test]
+#[should_panic(expected = "does not fit in a u128")]
+fn test_marginal_counts_overflow() {
+    let e = BDDEnv::new();
+    let vars: Vec<usize> = (0..128).collect();
+
+    e.marginal_counts(&e.var(0), &vars);
+}
+
+#[test]
+fn test_node_order() {
+    let sorted_hashes = || {
+        let e = BDDEnv::new();
+        let root = e.or(e.and(e.var(0), e.var(1)), e.xor(e.var(1), e.var(2)));
+
+        let mut nodes = root.node_list();
+        nodes.sort();
+        nodes.dedup();
+
+        nodes.iter().map(|n| n.get_hash()).collect::<Vec<_>>()
+    };
+
+    // the order does not depend on the memory location of the nodes
+    let hashes = sorted_hashes();
+    assert_eq!(hashes, sorted_hashes());
+    assert_eq!(hashes.len(), 7);
+
+    let e = BDDEnv::new();
+    assert!(e.mk_const(false) < e.mk_const(true));
+    assert!(e.mk_const(true) < e.var(1));
+    assert!(e.var(0) < e.var(1));
+    assert!(e.not(e.var(0)) < e.var(0));
+    assert_eq!(
+        e.var(0).cmp(&BDDEnv::new().var(0)),
+        std::cmp::Ordering::Equal
+    );
+}
+
+#[test]
+fn test_and_exists_chain() {
+    let n = 10;
+
+    // the conjunction of a_i <=> b_i is exponential in n when all a variables precede the b variables
+    let conjuncts = |e: &BDDEnv<usize>| -> Vec<Rc<BDD>> {
+        (0..n).map(|i| e.eq(e.var(i), e.var(n + i))).collect()
+    };
+    let quantify: Vec<usize> = (0..n).collect();
+
+    let naive_env = BDDEnv::new();
+    let naive = naive_env.exists(quantify.clone(), naive_env.and_all(conjuncts(&naive_env)));
+
+    let chain_env = BDDEnv::new();
+    let chain = chain_env.and_exists_chain(&conjuncts(&chain_env), &quantify);
+
+    assert_eq!(chain, naive);
+    assert!(chain.is_true());
+    assert!(chain_env.size() * 10 < naive_env.size());
+
+    // only the quantified variables are eliminated
+    let e = BDDEnv::new();
+    let cs = vec![
+        e.or(e.var(0), e.var(1)),
+        e.implies(e.var(1), e.var(2)),
+        e.not(e.var(2)),
+    ];
+    for quantify in [vec![], vec![1], vec![0, 2], vec![0, 1, 2, 3]] {
+        assert_eq!(
+            e.and_exists_chain(&cs, &quantify),
+            e.exists(quantify.clone(), e.and_all(cs.clone())),
+            "{quantify:?}"
+        );
+    }
+    assert!(e.and_exists_chain(&[], &[0]).is_true());
+}
+
+#[test]
+fn test_pb_leq() {
+    let e = BDDEnv::new();
+    let n = 4;
+
+    for weights in [
+        vec![2, 3],
+        vec![1, 2, 3, 4],
+        vec![3, -2, 5, 1],
+        vec![0, 7, 7, -1],
+    ] {
+        let weighted: Vec<(i64, Rc<BDD>)> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (*w, e.var(i)))
+            .collect();
+
+        for bound in -3..12 {
+            let constraint = e.pb_leq(&weighted, bound);
+
+            // compare with the brute-forced truth table
+            for assignment in 0..(1 << n) {
+                let value = (0..n).fold(Rc::clone(&constraint), |b, i| {
+                    e.restrict(b, &i, assignment >> i & 1 == 1)
+                });
+                let sum: i64 = weights
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| assignment >> i & 1 == 1)
+                    .map(|(_, w)| w)
+                    .sum();
+
+                assert_eq!(value.is_true(), sum <= bound, "{weights:?} <= {bound}");
+            }
+        }
+    }
+
+    assert!(e.pb_leq(&[], 0).is_true());
+    assert!(e.pb_leq(&[], -1).is_false());
+}
+
+#[test]
+fn test_eval_assignment() {
+    let e = BDDEnv::new();
+    let n = 12;
+
+    let vars: Vec<Rc<BDD>> = (0..n).map(|i| e.var(i)).collect();
+    let root = e.or(
+        e.xor(e.exn(&vars[..6], 3), Rc::clone(&vars[7])),
+        e.and(e.aln(&vars[4..], 5), e.not(Rc::clone(&vars[0]))),
+    );
+
+    let mut rng = StdRng::seed_from_u64(2365);
+    for _ in 0..200 {
+        let assignment: FxHashMap<usize, bool> = (0..n).map(|i| (i, rng.gen())).collect();
+
+        let restricted = assignment
+            .iter()
+            .fold(Rc::clone(&root), |b, (v, value)| e.restrict(b, v, *value));
+        assert!(restricted.is_const());
+
+        // no nodes are created
+        let size = e.size();
+        assert_eq!(e.eval_assignment(&root, &assignment), restricted.is_true());
+        assert_eq!(e.size(), size);
+    }
+}
+
 #[test]
 fn test_exn_model() {
     let e = BDDEnv::new();
@@ -532,6 +1315,29 @@ fn test_count_geq_leq_eq() {
     assert_eq!(e.count_geq(&[], &[e.var(0)]), e.not(e.var(0)));
 }
 
+#[test]
+fn test_count_ne() {
+    let e = BDDEnv::new();
+    assert_eq!(e.count_ne(&[], &[]), e.mk_const(false));
+
+    assert_eq!(e.count_ne(&[e.var(0)], &[e.var(0)]), e.mk_const(false));
+
+    assert_eq!(e.count_ne(&[e.var(0)], &[]), e.var(0));
+
+    assert_eq!(
+        e.count_ne(&[e.var(0)], &[e.var(1)]),
+        e.xor(e.var(0), e.var(1))
+    );
+
+    assert_eq!(
+        e.count_ne(&[e.var(0), e.var(1)], &[e.var(1), e.var(0)]),
+        e.mk_const(false)
+    );
+
+    assert_eq!(e.count_ne_offset(&[e.var(0)], &[], 1), e.not(e.var(0)));
+    assert_eq!(e.count_ne_offset(&[e.var(0)], &[], 2), e.mk_const(true));
+}
+
 #[test]
 fn test_queens() {
     let e = BDDEnv::new();
@@ -646,3 +1452,668 @@ fn test_basic_syntax_1() {
 
     println!("{:#?}\n{:#?}\n{:#?}", e1, e2, e3);
 }
+
+#[test]
+fn test_count_offset() {
+    let e = BDDEnv::new();
+
+    let a = [e.var(0), e.var(1)];
+    let b = [e.var(2)];
+
+    assert_eq!(e.count_eq_offset(&a, &b, 0), e.count_eq(&a, &b));
+    assert_eq!(e.count_leq_offset(&a, &b, 0), e.count_leq(&a, &b));
+    assert_eq!(e.count_lt_offset(&a, &b, 0), e.count_lt(&a, &b));
+    assert_eq!(e.count_geq_offset(&a, &b, 0), e.count_geq(&a, &b));
+    assert_eq!(e.count_gt_offset(&a, &b, 0), e.count_gt(&a, &b));
+
+    // count(a) < count(b) + 1 <=> count(a) <= count(b)
+    assert_eq!(e.count_lt_offset(&a, &b, 1), e.count_leq(&a, &b));
+    // count(a) > count(b) - 1 <=> count(a) >= count(b)
+    assert_eq!(e.count_gt_offset(&a, &b, -1), e.count_geq(&a, &b));
+
+    assert_eq!(e.count_eq_offset(&a, &[], 2), e.and(e.var(0), e.var(1)));
+    assert_eq!(e.count_geq_offset(&a, &b, 3), e.mk_const(false));
+    assert_eq!(e.count_leq_offset(&a, &b, 2), e.mk_const(true));
+}
+
+#[test]
+fn test_dot_compact() {
+    let e = BDDEnv::new();
+
+    // construct var(0) & var(1) | -var(0) & var(1) without the lookup table,
+    // such that both subtrees are structurally identical, but do not share the same pointer
+    let root = Rc::new(BDD::choice(
+        Rc::new(BDD::choice(e.mk_const(true), 1, e.mk_const(false))),
+        0,
+        Rc::new(BDD::choice(e.mk_const(true), 1, e.mk_const(false))),
+    ));
+
+    let node_count = |compact: bool| {
+        let mut output = Vec::new();
+
+        BDDGraph::new(&root, TruthTableEntry::Any)
+            .with_compact(compact)
+            .render_dot(&mut output)
+            .expect("failed to render dot");
+
+        let output = String::from_utf8(output).expect("dot output is not valid utf-8");
+
+        output
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|id| id.starts_with("n_"))
+            .collect::<std::collections::BTreeSet<_>>()
+            .len()
+    };
+
+    assert_eq!(node_count(false), 5);
+    assert_eq!(node_count(true), 4);
+}
+
+#[test]
+fn test_permute() {
+    let e = BDDEnv::new();
+
+    let original = e.amn(&[e.var(0), e.var(1), e.var(2)], 2);
+    let shifted = e.amn(&[e.var(3), e.var(4), e.var(5)], 2);
+
+    let mapping = [(0, 3), (1, 4), (2, 5)].into_iter().collect();
+    assert_eq!(
+        e.permute(&original, &mapping)
+            .expect("mapping is a bijection"),
+        shifted
+    );
+
+    // reversing the order of the variables requires re-canonicalization
+    let implication = e.implies(e.var(0), e.var(1));
+    let mapping = [(0, 1), (1, 0)].into_iter().collect();
+    assert_eq!(
+        e.permute(&implication, &mapping)
+            .expect("mapping is a bijection"),
+        e.implies(e.var(1), e.var(0))
+    );
+
+    // 0 and 1 are both renamed to 1
+    let mapping = std::iter::once((0, 1)).collect();
+    assert!(e.permute(&implication, &mapping).is_err());
+}
+
+#[test]
+fn test_entails_under() {
+    let e = BDDEnv::new();
+
+    let a = e.var(0);
+    let b = e.var(1);
+
+    // modus ponens: a, a => b entails b
+    assert!(e.entails_under(e.implies(e.var(0), e.var(1)), e.var(0), e.var(1)));
+    assert!(!e.entails_under(e.mk_const(true), Rc::clone(&a), Rc::clone(&b)));
+
+    // unsatisfiable assumptions entail everything
+    let contradiction = e.and(Rc::clone(&a), e.not(Rc::clone(&a)));
+    assert!(e.entails_under(
+        Rc::clone(&contradiction),
+        e.mk_const(true),
+        e.mk_const(false)
+    ));
+
+    // a and b are only equivalent when assuming a <=> b
+    assert!(!e.equivalent_under(e.mk_const(true), Rc::clone(&a), Rc::clone(&b)));
+    assert!(e.equivalent_under(
+        e.eq(Rc::clone(&a), Rc::clone(&b)),
+        Rc::clone(&a),
+        Rc::clone(&b)
+    ));
+    assert!(e.equivalent_under(contradiction, a, e.mk_const(false)));
+}
+
+#[test]
+fn test_forced_variables() {
+    let e = BDDEnv::new();
+
+    // a & b & (c | d)
+    let f = e.and(e.and(e.var(0), e.var(1)), e.or(e.var(2), e.var(3)));
+    assert_eq!(e.forced_variables(&f), vec![(0, true), (1, true)]);
+
+    // -a & (b | c) & -d
+    let g = e.and(
+        e.and(e.not(e.var(0)), e.or(e.var(1), e.var(2))),
+        e.not(e.var(3)),
+    );
+    assert_eq!(e.forced_variables(&g), vec![(0, false), (3, false)]);
+
+    // variables forced to true are also inferred by infer
+    for v in [0, 1] {
+        assert_eq!(e.infer(Rc::clone(&f), v), (true, true));
+    }
+
+    assert!(e.forced_variables(&e.mk_const(false)).is_empty());
+    assert!(e.forced_variables(&e.mk_const(true)).is_empty());
+    assert!(e.forced_variables(&e.or(e.var(0), e.var(1))).is_empty());
+}
+
+#[test]
+fn test_depth_and_level_widths() {
+    let e = BDDEnv::new();
+
+    // a chain: a & b & c & d
+    let chain = e.and(e.and(e.var(0), e.var(1)), e.and(e.var(2), e.var(3)));
+    assert_eq!(chain.depth(), 4);
+    assert_eq!(e.level_widths(&chain), vec![1, 1, 1, 1]);
+
+    // symmetric functions: the parity of 4 variables, and exactly 2 out of 4 variables
+    let parity = e.xor(e.xor(e.var(0), e.var(1)), e.xor(e.var(2), e.var(3)));
+    assert_eq!(parity.depth(), 4);
+    assert_eq!(e.level_widths(&parity), vec![1, 2, 2, 2]);
+
+    let two_of_four = e.exn(&[e.var(0), e.var(1), e.var(2), e.var(3)], 2);
+    assert_eq!(two_of_four.depth(), 4);
+    assert_eq!(e.level_widths(&two_of_four), vec![1, 2, 3, 2]);
+
+    // the shortest path of a | b skips b
+    let or = e.or(e.var(0), e.var(1));
+    assert_eq!(or.depth(), 2);
+    assert_eq!(e.level_widths(&or), vec![1, 1]);
+
+    assert_eq!(e.mk_const(true).depth(), 0);
+    assert!(e.level_widths(&e.mk_const(false)).is_empty());
+}
+
+#[test]
+fn test_level_map() {
+    let e = BDDEnv::new();
+
+    let parity = e.xor(e.xor(e.var(0), e.var(1)), e.xor(e.var(2), e.var(3)));
+    let levels = e.level_map(&parity);
+
+    assert_eq!(levels.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    assert_eq!(levels[&0], vec![Rc::clone(&parity)]);
+
+    // every level only contains distinct nodes labeled with its variable
+    for (v, nodes) in &levels {
+        assert!(nodes
+            .iter()
+            .all(|n| matches!(n.as_ref(), BDD::Choice(_, w, _, _) if w == v)));
+        let mut pointers: Vec<*const BDD> = nodes.iter().map(Rc::as_ptr).collect();
+        pointers.sort();
+        pointers.dedup();
+        assert_eq!(pointers.len(), nodes.len());
+    }
+
+    // all reachable nodes except the terminals are grouped
+    let total: usize = levels.values().map(Vec::len).sum();
+    assert_eq!(total, e.size_reachable(&parity) - 2);
+
+    let or = e.or(e.var(0), e.not(e.var(2)));
+    let total: usize = e.level_map(&or).values().map(Vec::len).sum();
+    assert_eq!(total, e.size_reachable(&or) - 2);
+
+    assert!(e.level_map(&e.mk_const(true)).is_empty());
+}
+
+#[test]
+fn test_graphml() {
+    let e = BDDEnv::new();
+    let root = e.and(e.var(0), e.var(1));
+
+    let render = |filter: TruthTableEntry| {
+        let mut output = Vec::new();
+
+        BDDGraph::new(&root, filter)
+            .render_graphml(&mut output)
+            .expect("failed to render graphml");
+
+        String::from_utf8(output).expect("graphml output is not valid utf-8")
+    };
+
+    let all = render(TruthTableEntry::Any);
+    assert!(all.starts_with("<?xml"));
+    assert!(all.trim_end().ends_with("</graphml>"));
+    assert_eq!(all.matches("<node id=\"n_").count(), 4);
+    assert_eq!(all.matches("<edge ").count(), 4);
+    assert!(all.contains(r#"<node id="n_true"><data key="label">true</data></node>"#));
+    assert!(all.contains(r#"target="n_false"><data key="value">F</data>"#));
+
+    // the filter removes the false terminal and the edges leading to it
+    let filtered = render(TruthTableEntry::True);
+    assert_eq!(filtered.matches("<node ").count(), 3);
+    assert_eq!(filtered.matches("<edge ").count(), 2);
+    assert!(!filtered.contains("n_false"));
+}
+
+#[test]
+fn test_short_circuit() {
+    let e = BDDEnv::with_profiling();
+
+    let vars: Vec<Rc<BDD>> = (0..64).map(|i| e.var(i)).collect();
+
+    // without short-circuiting, these would take 2^64 steps
+    let start = std::time::Instant::now();
+    assert_eq!(e.amn(&vars, -1), e.mk_const(false));
+    assert_eq!(e.aln(&vars, 0), e.mk_const(true));
+    assert_eq!(e.aln(&vars, 65), e.mk_const(false));
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+    // constant branches are skipped
+    let branches = vec![e.mk_const(true), e.var(0), e.mk_const(false), e.var(1)];
+    assert_eq!(e.exn(&branches, 2), e.xor(e.var(0), e.var(1)));
+    assert_eq!(e.aln(&branches, 1), e.mk_const(true));
+    assert_eq!(e.amn(&branches, 0), e.mk_const(false));
+
+    // the conjunction stops at the first false intermediate result
+    let unsat = vec![
+        e.var(0),
+        e.not(e.var(0)),
+        e.or(vars[2].clone(), vars[3].clone()),
+        e.or(vars[4].clone(), vars[5].clone()),
+    ];
+    let calls = e.profile_report().mk_choice_calls;
+    assert_eq!(e.and_all(unsat), e.mk_const(false));
+    assert!(e.profile_report().mk_choice_calls - calls < 4);
+
+    // the results are unchanged
+    let sat = vec![e.var(0), e.var(2), e.not(e.var(4))];
+    assert_eq!(
+        e.and_all(sat.clone()),
+        sat.into_iter()
+            .fold(e.mk_const(true), |acc, b| e.and(acc, b))
+    );
+}
+
+#[test]
+fn test_support() {
+    let e = BDDEnv::new();
+
+    assert!(e.support(&e.or(e.var(0), e.not(e.var(0)))).is_empty());
+    assert!(e.support(&e.and(e.var(1), e.not(e.var(1)))).is_empty());
+
+    assert_eq!(e.support(&e.var(3)), vec![3]);
+    assert_eq!(
+        e.support(&e.and(e.var(2), e.or(e.var(0), e.var(5)))),
+        vec![0, 2, 5]
+    );
+
+    // a variable which does not affect the outcome is not part of the support
+    let irrelevant = e.or(e.and(e.var(0), e.var(1)), e.and(e.var(0), e.not(e.var(1))));
+    assert_eq!(e.support(&irrelevant), vec![0]);
+}
+
+#[test]
+fn test_max_weight_model() {
+    let e = BDDEnv::new();
+    let n = 4;
+
+    let clauses = vec![
+        (e.or(e.var(0), e.var(1)), 3),
+        (e.not(e.var(0)), 2),
+        (e.and(e.var(1), e.var(2)), 4),
+        (e.not(e.var(2)), 5),
+        (e.xor(e.var(1), e.var(3)), 1),
+        (e.and(e.var(3), e.not(e.var(3))), 10),
+    ];
+
+    // brute force over all full assignments
+    let best = (0..(1usize << n))
+        .map(|bits| {
+            let assignment = (0..n).fold(e.mk_const(true), |acc, v| {
+                if bits & (1 << v) != 0 {
+                    e.and(acc, e.var(v))
+                } else {
+                    e.and(acc, e.not(e.var(v)))
+                }
+            });
+
+            clauses
+                .iter()
+                .filter(|(c, _)| e.implies(assignment.clone(), c.clone()).is_true())
+                .map(|(_, w)| w)
+                .sum::<u64>()
+        })
+        .max()
+        .expect("no assignments");
+
+    let (weight, model) = e.max_weight_model(&clauses);
+    assert_eq!(weight, best);
+    assert_eq!(weight, 11);
+
+    // every full assignment covered by the model satisfies clauses of the reported weight
+    let satisfied: u64 = clauses
+        .iter()
+        .filter(|(c, _)| e.implies(model.clone(), c.clone()).is_true())
+        .map(|(_, w)| w)
+        .sum();
+    assert_eq!(satisfied, weight);
+
+    assert_eq!(e.max_weight_model(&[]), (0, e.mk_const(true)));
+
+    // the total weight saturates instead of overflowing
+    let heavy = vec![
+        (e.var(0), u64::MAX - 1),
+        (e.var(1), 2),
+        (e.not(e.var(0)), 1),
+    ];
+    let (weight, model) = e.max_weight_model(&heavy);
+    assert_eq!(weight, u64::MAX);
+    assert!(e.implies(model, e.var(0)).is_true());
+}
+
+// a 3x3 sudoku without boxes (a latin square), where variable 3 * cell + digit denotes that the cell contains the digit
+fn sudoku_3x3(e: &BDDEnv<usize>, puzzle: &str) -> Rc<BDD> {
+    let cell = |r: usize, c: usize| 3 * r + c;
+    let lines = (0..3).flat_map(|i| {
+        [
+            (0..3).map(|c| cell(i, c)).collect::<Vec<_>>(),
+            (0..3).map(|r| cell(r, i)).collect::<Vec<_>>(),
+        ]
+    });
+
+    let mut constraints = Vec::new();
+
+    for (i, hint) in puzzle.chars().enumerate() {
+        if let Some(d) = hint.to_digit(10) {
+            constraints.push(e.var(3 * i + d as usize - 1));
+        }
+
+        let digits: Vec<_> = (0..3).map(|d| e.var(3 * i + d)).collect();
+        constraints.push(e.exn(&digits, 1));
+    }
+
+    for line in lines {
+        for d in 0..3 {
+            let cells: Vec<_> = line.iter().map(|&i| e.var(3 * i + d)).collect();
+            constraints.push(e.exn(&cells, 1));
+        }
+    }
+
+    e.and_all(constraints)
+}
+
+#[test]
+fn test_uniqueness() {
+    let e = BDDEnv::new();
+
+    let unique = sudoku_3x3(&e, "12..3....");
+    assert_eq!(e.satcount(&unique, 27), 1);
+    assert_eq!(e.uniqueness(&unique, 27), Uniqueness::Unique);
+
+    let multiple = sudoku_3x3(&e, "12.......");
+    assert_eq!(e.satcount(&multiple, 27), 2);
+    assert_eq!(e.uniqueness(&multiple, 27), Uniqueness::Multiple);
+
+    let none = sudoku_3x3(&e, "11.......");
+    assert_eq!(e.uniqueness(&none, 27), Uniqueness::None);
+
+    // a model which skips a variable covers multiple assignments
+    assert_eq!(e.uniqueness(&e.var(0), 1), Uniqueness::Unique);
+    assert_eq!(e.uniqueness(&e.var(0), 2), Uniqueness::Multiple);
+    assert_eq!(e.uniqueness(&e.mk_const(true), 0), Uniqueness::Unique);
+}
+
+#[test]
+fn test_new_sized() {
+    let e = BDDEnv::new_sized(1024);
+    assert_eq!(e.size(), 2);
+    assert!(e.nodes.borrow().capacity() >= 1024);
+
+    let d = BDDEnv::new();
+    let f = e.xor(e.var(0), e.and(e.var(1), e.var(2)));
+    assert_eq!(f, d.xor(d.var(0), d.and(d.var(1), d.var(2))));
+}
+
+#[test]
+fn test_node_limit() {
+    // pairwise equivalences between the first and second half of the variables need exponentially many nodes
+    let pairs = |e: &BDDEnv<usize>, n: usize| {
+        (0..n).fold(e.mk_const(true), |acc, i| {
+            e.and(acc, e.eq(e.var(i), e.var(i + n)))
+        })
+    };
+
+    let e = BDDEnv::new_with_limits(100);
+    assert_eq!(e.max_nodes(), Some(100));
+
+    let small = pairs(&e, 2);
+    assert!(!small.is_false());
+    assert!(e.size() <= 100);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| pairs(&e, 8)));
+    let payload = result.expect_err("the node limit should have been exceeded");
+    assert_eq!(
+        payload.downcast_ref::<NodeLimitExceeded>(),
+        Some(&NodeLimitExceeded { limit: 100 })
+    );
+    assert!(e.size() <= 100);
+
+    // the same build succeeds without a limit
+    let d = BDDEnv::new();
+    assert_eq!(d.max_nodes(), None);
+    assert!(!pairs(&d, 8).is_false());
+    assert!(d.size() > 100);
+}
+
+#[test]
+fn test_dot_ranked() {
+    let e = BDDEnv::new();
+    let f = e.xor(e.var(0), e.xor(e.var(1), e.var(2)));
+
+    let render = |ranked: bool| {
+        let mut output = Vec::new();
+        BDDGraph::new(&f, TruthTableEntry::Any)
+            .with_compact(true)
+            .with_ranked(ranked)
+            .render_dot(&mut output)
+            .expect("failed to render dot");
+        String::from_utf8(output).expect("dot output is not valid utf-8")
+    };
+
+    let plain = render(false);
+    assert!(!plain.contains("rank="));
+
+    let ranked = render(true);
+    let ranks: Vec<&str> = ranked
+        .lines()
+        .map(str::trim)
+        .filter(|l| l.contains("rank="))
+        .collect();
+
+    // a level per variable, with two nodes for both 1 and 2, followed by the terminals
+    assert_eq!(ranks.len(), 4);
+    assert_eq!(ranks[0].matches("n_").count(), 1);
+    assert_eq!(ranks[1].matches("n_").count(), 2);
+    assert_eq!(ranks[2].matches("n_").count(), 2);
+    assert_eq!(ranks[3], "{ rank=sink; n_true; n_false; }");
+
+    // the ranking only adds lines to the plain output
+    assert!(plain.lines().all(|l| ranked.contains(l)));
+    assert!(ranked.trim_end().ends_with('}'));
+}
+
+#[test]
+fn test_to_rust_fn() {
+    let e = BDDEnv::new();
+    let f = e.xor(e.and(e.var(0), e.var(1)), e.var(2));
+
+    let mut output = Vec::new();
+    to_rust_fn(&f, "decide", &mut output).expect("failed to write rust function");
+    let output = String::from_utf8(output).expect("rust output is not valid utf-8");
+
+    let item: syn::ItemFn = syn::parse_str(&output).expect("output is not a valid rust function");
+    assert_eq!(item.sig.ident, "decide");
+    assert_eq!(item.sig.inputs.len(), 1);
+
+    // the node of variable 2 is shared by both branches of variable 1, and is hoisted into a closure, such that it is
+    // only evaluated when it is reached
+    let bindings: Vec<&syn::Local> = item
+        .block
+        .stmts
+        .iter()
+        .filter_map(|s| match s {
+            syn::Stmt::Local(local) => Some(local),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(bindings.len(), 1);
+    assert!(matches!(
+        bindings[0].init.as_ref().map(|init| init.expr.as_ref()),
+        Some(syn::Expr::Closure(_))
+    ));
+    assert_eq!(output.matches("n_0()").count(), 2);
+
+    for constant in [e.mk_const(true), e.mk_const(false)] {
+        let mut output = Vec::new();
+        to_rust_fn(&constant, "constant", &mut output).expect("failed to write rust function");
+        let output = String::from_utf8(output).expect("rust output is not valid utf-8");
+
+        syn::parse_str::<syn::ItemFn>(&output).expect("output is not a valid rust function");
+    }
+}
+
+#[test]
+fn test_assert_canonical() {
+    let e = BDDEnv::new();
+
+    let vars: Vec<Rc<BDD>> = (0..5).map(|i| e.var(i)).collect();
+    for f in [
+        e.mk_const(true),
+        e.mk_const(false),
+        e.xor(e.var(0), e.var(3)),
+        e.exn(&vars, 2),
+        e.mk_choice_unchecked(e.var(1), 0, e.var(2)),
+    ] {
+        assert_eq!(e.assert_canonical(&f), Ok(()));
+    }
+
+    // a choice with equal subtrees
+    let redundant = Rc::new(BDD::choice(e.var(1), 0, e.var(1)));
+    assert_eq!(
+        e.assert_canonical(&redundant),
+        Err(CanonicalityError::Redundant(0))
+    );
+
+    // a choice on 1 below a choice on 2
+    let unordered = Rc::new(BDD::choice(e.var(1), 2, e.mk_const(false)));
+    assert_eq!(
+        e.assert_canonical(&e.or(e.var(0), unordered)),
+        Err(CanonicalityError::Unordered {
+            parent: 2,
+            child: 1
+        })
+    );
+
+    // a second copy of the node of variable 2, next to the shared one
+    let copy = Rc::new(BDD::choice(e.mk_const(true), 2, e.mk_const(false)));
+    let duplicate = Rc::new(BDD::choice(
+        e.var(2),
+        0,
+        Rc::new(BDD::choice(e.mk_const(true), 1, copy)),
+    ));
+    assert_eq!(
+        e.assert_canonical(&duplicate),
+        Err(CanonicalityError::Duplicate(e.var(2).get_hash()))
+    );
+}
+
+#[test]
+fn test_image_preimage() -> anyhow::Result<()> {
+    let e = BDDEnv::new();
+    let (x, x_next) = (0, 1);
+
+    // a system with two states, where both states move to the state in which x is true
+    let trans = e.var(x_next);
+    let init = e.not(e.var(x));
+
+    assert_eq!(e.image(&init, &trans, &[x], &[x_next])?, e.var(x));
+    assert_eq!(
+        e.preimage(&e.var(x), &trans, &[x], &[x_next])?,
+        e.mk_const(true)
+    );
+    assert_eq!(
+        e.preimage(&init, &trans, &[x], &[x_next])?,
+        e.mk_const(false)
+    );
+
+    // reachability as least fixed point of the image
+    let reachable_from = |start: Rc<BDD>| -> anyhow::Result<Rc<BDD>> {
+        let mut reached = start;
+        loop {
+            let next = e.or(
+                Rc::clone(&reached),
+                e.image(&reached, &trans, &[x], &[x_next])?,
+            );
+            if next == reached {
+                return Ok(reached);
+            }
+            reached = next;
+        }
+    };
+
+    assert_eq!(reachable_from(init)?, e.mk_const(true));
+    assert_eq!(reachable_from(e.var(x))?, e.var(x));
+
+    assert!(e.image(&e.var(x), &trans, &[x], &[]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_deterministic_evaluation() {
+    // evaluation is sequential, so building the same formula twice results in the same lookup table
+    let build = || {
+        let e = BDDEnv::new();
+        let vars: Vec<Rc<BDD>> = (0..8).map(|i| e.var(i)).collect();
+        let root = e.and(
+            e.exn(&vars[..5], 2),
+            e.or(e.xor(e.var(5), e.var(6)), e.var(7)),
+        );
+        (e, root.get_hash())
+    };
+
+    let (e1, h1) = build();
+    let (e2, h2) = build();
+
+    assert_eq!(h1, h2);
+    assert_eq!(e1.size(), e2.size());
+    assert!(e1 == e2);
+}
+
+#[test]
+fn test_cube() {
+    let e = BDDEnv::new();
+
+    assert_eq!(
+        e.cube(&[(0, true), (1, false)]),
+        e.and(e.var(0), e.not(e.var(1)))
+    );
+    assert_eq!(e.cube(&[]), e.mk_const(true));
+
+    // the literals can be given in any order, and repeated
+    let literals = [(3, false), (0, true), (2, true), (0, true)];
+    let expected = literals.iter().fold(e.mk_const(true), |acc, &(v, value)| {
+        e.and(acc, if value { e.var(v) } else { e.not(e.var(v)) })
+    });
+    assert_eq!(e.cube(&literals), expected);
+
+    // a single path of choices
+    assert_eq!(e.size_reachable(&e.cube(&literals)), 5);
+
+    // contradicting literals
+    assert!(e.cube(&[(1, true), (0, false), (1, false)]).is_false());
+}
+
+#[test]
+fn test_decision_tree() {
+    let e = BDDEnv::new();
+    let vars: Vec<usize> = (0..5).collect();
+    let root = e.or(e.and(e.var(1), e.var(3)), e.not(e.var(4)));
+
+    let tree = e.to_decision_tree(&root, &vars);
+
+    // the nodes of the tree are not shared, so every node is visited once
+    let nodes = tree.node_list();
+    let leaves = nodes.iter().filter(|n| n.is_const()).count();
+    assert_eq!(leaves, 1 << vars.len());
+    assert_eq!(nodes.len(), (1 << (vars.len() + 1)) - 1);
+
+    // every path tests every variable, and the tree represents the same function
+    assert_eq!(e.satcount(&tree, vars.len()), e.satcount(&root, vars.len()));
+    assert_eq!(e.to_decision_tree(&e.mk_const(true), &[]), e.mk_const(true));
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "complemented_edges")]
+
+use std::rc::Rc;
+
+use pretty_assertions::assert_eq;
+
+use rsbdd::bdd;
+use rsbdd::bdd::BDDEnv;
+use rsbdd::complemented::*;
+
+type BDD = bdd::BDD<usize>;
+
+// all lines on an n by n board which can contain at most one queen
+fn queen_lines(n: usize) -> Vec<Vec<usize>> {
+    let cell = |r: usize, c: usize| r * n + c;
+
+    let rows = (0..n).map(|r| (0..n).map(|c| cell(r, c)).collect());
+    let cols = (0..n).map(|c| (0..n).map(|r| cell(r, c)).collect());
+    let diagonals = (0..(2 * n - 1)).flat_map(|d| {
+        let diag = (0..n)
+            .filter(|&r| d >= r && d - r < n)
+            .map(|r| cell(r, d - r))
+            .collect::<Vec<_>>();
+        let anti_diag = (0..n)
+            .filter(|&r| d + r + 1 >= n && d + r + 1 - n < n)
+            .map(|r| cell(r, d + r + 1 - n))
+            .collect::<Vec<_>>();
+
+        [diag, anti_diag]
+    });
+
+    rows.chain(cols).chain(diagonals).collect()
+}
+
+fn queens_standard(e: &BDDEnv<usize>, n: usize) -> Rc<BDD> {
+    let mut result = e.mk_const(true);
+
+    for (i, line) in queen_lines(n).iter().enumerate() {
+        for (k, &a) in line.iter().enumerate() {
+            for &b in &line[k + 1..] {
+                result = e.and(result, e.not(e.and(e.var(a), e.var(b))));
+            }
+        }
+
+        // every row must contain at least one queen
+        if i < n {
+            let any = line
+                .iter()
+                .fold(e.mk_const(false), |acc, &a| e.or(acc, e.var(a)));
+            result = e.and(result, any);
+        }
+    }
+
+    result
+}
+
+fn queens_complemented(e: &ComplementedEnv<usize>, n: usize) -> Edge<usize> {
+    let mut result = e.mk_const(true);
+
+    for (i, line) in queen_lines(n).iter().enumerate() {
+        for (k, &a) in line.iter().enumerate() {
+            for &b in &line[k + 1..] {
+                result = e.and(&result, &e.not(&e.and(&e.var(a), &e.var(b))));
+            }
+        }
+
+        // every row must contain at least one queen
+        if i < n {
+            let any = line
+                .iter()
+                .fold(e.mk_const(false), |acc, &a| e.or(&acc, &e.var(a)));
+            result = e.and(&result, &any);
+        }
+    }
+
+    result
+}
+
+#[test]
+fn test_complemented_basic() {
+    let e = ComplementedEnv::new();
+    let s = BDDEnv::new();
+
+    let a = e.var(0);
+    let b = e.var(1);
+
+    assert!(e.and(&a, &e.not(&a)).is_false());
+    assert!(e.or(&a, &e.not(&a)).is_true());
+    assert_eq!(e.not(&e.not(&a)), a);
+
+    // negation does not create new nodes
+    let size = e.size();
+    let nab = e.not(&e.and(&a, &b));
+    assert_eq!(e.size(), size + 1);
+    assert!(nab.complement);
+
+    assert_eq!(e.to_bdd(&nab, &s), s.not(s.and(s.var(0), s.var(1))));
+    assert_eq!(e.from_bdd(&e.to_bdd(&nab, &s)), nab);
+}
+
+#[test]
+fn test_complemented_queens() {
+    for n in 4..=5 {
+        let s = BDDEnv::new();
+        let e = ComplementedEnv::new();
+
+        let standard = queens_standard(&s, n);
+        let complemented = queens_complemented(&e, n);
+
+        assert_eq!(e.to_bdd(&complemented, &s), standard);
+        assert_eq!(e.from_bdd(&standard), complemented);
+
+        // the negation is free in the complemented representation
+        let size = e.size();
+        assert_eq!(e.to_bdd(&e.not(&complemented), &s), s.not(standard));
+        assert_eq!(e.size(), size);
+    }
+}
+
+#[test]
+fn test_complemented_dot() {
+    let e = ComplementedEnv::new();
+    let f = e.not(&e.or(&e.var(0), &e.var(1)));
+
+    let mut output = Vec::new();
+    ComplementedGraph::new(&f)
+        .render_dot(&mut output)
+        .expect("failed to render dot");
+
+    let output = String::from_utf8(output).expect("dot output is not valid utf-8");
+
+    assert!(output.contains("n_root"));
+    assert!(output.contains("dashed"));
+    assert!(!output.contains("false"));
+}
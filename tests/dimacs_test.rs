@@ -0,0 +1,58 @@
+use std::io;
+use std::io::BufReader;
+use std::rc::Rc;
+
+use pretty_assertions::assert_eq;
+
+use rsbdd::bdd::BDDEnv;
+use rsbdd::dimacs::read_dimacs;
+use rsbdd::parser::ParsedFormula;
+
+fn read(src: &str) -> io::Result<ParsedFormula> {
+    read_dimacs(Rc::new(BDDEnv::new()), &mut BufReader::new(src.as_bytes()))
+}
+
+#[test]
+fn test_read_dimacs() -> io::Result<()> {
+    let parsed = read("c a comment\np cnf 3 2\n1 -2 0\n2 3\n0\n")?;
+
+    assert_eq!(
+        parsed
+            .vars
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["1", "2", "3"]
+    );
+    assert_eq!(parsed.conjuncts().len(), 2);
+
+    let result = parsed.eval();
+    assert_eq!(parsed.env.satcount(&result, 3), 4);
+
+    // the final clause does not need to be terminated, and trailing '%' lines are ignored
+    let parsed = read("p cnf 2 2\n1 0\n-1 2\n%\n0\n")?;
+    let e = &parsed.env;
+    assert_eq!(
+        parsed.eval(),
+        e.and(e.var(parsed.vars[0].clone()), e.var(parsed.vars[1].clone()))
+    );
+
+    // the empty clause is unsatisfiable
+    assert!(read("p cnf 1 2\n1 0\n0\n")?.eval().is_false());
+
+    Ok(())
+}
+
+#[test]
+fn test_read_dimacs_errors() {
+    for src in [
+        "",
+        "1 2 0\n",
+        "p cnf x 1\n",
+        "p cnf 2 1\n1 3 0\n",
+        "p cnf 2 1\n1 a 0\n",
+    ] {
+        let err = read(src).expect_err(src);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{src}");
+    }
+}
@@ -19,8 +19,8 @@ fn file_assert_eq<P: AsRef<Path>>(file1: P, file2: P, ordering: &[&str]) {
         })
         .collect();
 
-    let f1 = File::open(file1).unwrap();
-    let f2 = File::open(file2).unwrap();
+    let f1 = File::open(file1).expect("Could not open input file 1");
+    let f2 = File::open(file2).expect("Could not open input file 2");
 
     let input_parsed_1 = ParsedFormula::new(&mut BufReader::new(f1), Some(ord.clone()))
         .expect("Could not parse input file 1");
@@ -34,7 +34,7 @@ fn file_assert_eq<P: AsRef<Path>>(file1: P, file2: P, ordering: &[&str]) {
 }
 
 fn file_assert_true<P: AsRef<Path>>(file: P) {
-    let f1 = File::open(file).unwrap();
+    let f1 = File::open(file).expect("Could not open input file");
 
     let input_parsed =
         ParsedFormula::new(&mut BufReader::new(f1), None).expect("Could not parse input file");
@@ -68,3 +68,778 @@ fn test_files_true() {
         file_assert_true(f);
     }
 }
+
+#[test]
+fn test_cold_environment() {
+    let f = File::open("examples/4_queens.txt").expect("Could not open input file");
+
+    let mut input_parsed =
+        ParsedFormula::new(&mut BufReader::new(f), None).expect("Could not parse input file");
+
+    let warm_result = input_parsed.eval();
+    let warm_size = input_parsed.env.size();
+
+    // a warm environment re-uses all nodes computed in the previous evaluation
+    input_parsed.eval();
+    assert_eq!(input_parsed.env.size(), warm_size);
+
+    // a cold environment must rebuild the complete node table
+    input_parsed.env = Rc::new(BDDEnv::new());
+    assert_eq!(input_parsed.env.size(), 2);
+
+    let cold_result = input_parsed.eval();
+    assert_eq!(input_parsed.env.size(), warm_size);
+    assert_eq!(cold_result, warm_result);
+
+    // the profile of the last benchmark iteration, and the number of paths in the result
+    let run = |extra: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["examples/4_queens.txt", "--profile", "--count-paths"])
+            .args(extra)
+            .output()
+            .expect("failed to run rsbdd");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let profile: Vec<String> = stderr
+            .lines()
+            .filter(|l| l.starts_with("mk_choice calls:") || l.starts_with("Cache misses:"))
+            .map(str::to_string)
+            .collect();
+        assert_eq!(profile.len(), 2, "{stderr}");
+        (profile, stdout)
+    };
+
+    let (single, single_result) = run(&["--benchmark", "1"]);
+    let (warm, warm_result) = run(&["--benchmark", "2"]);
+    let (cold, cold_result) = run(&["--benchmark", "2", "--cold"]);
+
+    // every cold iteration repeats the work of a single run, while a warm iteration re-uses the nodes of the previous
+    assert_eq!(cold, single);
+    assert_ne!(warm, single);
+    assert_eq!(warm[1], single[1]);
+    assert_eq!(cold_result, single_result);
+    assert_eq!(warm_result, single_result);
+}
+
+#[test]
+fn test_limit_vars() {
+    let run = |limit: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-t", "--limit-vars", limit, "-e", "a | b | c"])
+            .output()
+            .expect("failed to run rsbdd")
+    };
+
+    let limited = run("2");
+    assert!(!limited.status.success());
+    let stderr = String::from_utf8_lossy(&limited.stderr);
+    assert!(stderr.contains("exceeds the limit of 2"));
+    assert!(stderr.contains("--model or --count"));
+
+    for limit in ["3", "0"] {
+        let output = run(limit);
+        assert!(output.status.success());
+        // header, separator and 4 rows
+        assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 6);
+    }
+}
+
+#[test]
+fn test_truthtable_csv() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["--truthtable-csv", "-e", "a & (b | c)"])
+        .output()
+        .expect("failed to run rsbdd");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "a,b,c,result\nF,*,*,F\nT,F,F,F\nT,F,T,T\nT,T,*,T\n"
+    );
+}
+
+#[test]
+fn test_interactive() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .arg("--interactive")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run rsbdd");
+
+    child
+        .stdin
+        .take()
+        .expect("no stdin")
+        .write_all(b"a & b\n:count\n:def x a | c\n(b | !b) & {x}\n:count\na & &\nb => b\n:quit\n")
+        .expect("failed to write to stdin");
+
+    let output = child.wait_with_output().expect("failed to wait for rsbdd");
+    assert!(output.status.success());
+
+    // variables and definitions persist across lines, parse errors do not end the session
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "> satisfiable\n> 1\n> defined {x}\n> satisfiable\n> 6\n> > true\n> "
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error:"));
+}
+
+#[test]
+fn test_interactive_count_overflow() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .arg("--interactive")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run rsbdd");
+
+    // the model count of 130 variables does not fit in a u128, which does not end the session
+    let formula = (0..130)
+        .map(|i| format!("x{i}"))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    child
+        .stdin
+        .take()
+        .expect("no stdin")
+        .write_all(format!("{formula}\n:count\nx0 & x1\n:count\n:quit\n").as_bytes())
+        .expect("failed to write to stdin");
+
+    let output = child.wait_with_output().expect("failed to wait for rsbdd");
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "> satisfiable\n> > satisfiable\n> 1\n> "
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not fit in a u128"));
+}
+
+#[test]
+fn test_equiv() {
+    let run = |formula: &str, other: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", formula, "--equiv", other])
+            .output()
+            .expect("failed to run rsbdd")
+    };
+
+    let output = run("c | (b | a)", "tests/data/set_abc.txt");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "equivalent\n");
+
+    let output = run("c | a", "tests/data/set_abc.txt");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "not equivalent\ndistinguishing assignment: c=0,a=0,b=1\n"
+    );
+
+    let output = run("a", "tests/data/true.txt");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "not equivalent\ndistinguishing assignment: a=0\n"
+    );
+}
+
+#[test]
+fn test_check_unique_sat() {
+    let run = |formula: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", formula, "--check-unique-sat"])
+            .output()
+            .expect("failed to run rsbdd");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert_eq!(run("a & !b"), "unique\n");
+    assert_eq!(run("a | b"), "multiple\n");
+    assert_eq!(run("a & (b | !b)"), "multiple\n");
+    assert_eq!(run("a & !a"), "none\n");
+}
+
+#[test]
+fn test_count() {
+    let run = |formula: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", formula, "--count"])
+            .output()
+            .expect("failed to run rsbdd");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    assert_eq!(run("(a | b) & (c | !c)"), "6\n");
+    assert_eq!(run("a & !a"), "0\n");
+}
+
+#[test]
+fn test_count_paths() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "(a | b) & (c | !c)", "--count-paths"])
+        .output()
+        .expect("failed to run rsbdd");
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n");
+}
+
+#[test]
+fn test_statements() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["--statements", "tests/data/statements.txt"])
+        .output()
+        .expect("failed to run rsbdd");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let blocks: Vec<&str> = stdout.split("\n\n").collect();
+    assert_eq!(
+        blocks,
+        vec![
+            "statement 1: sat\nmodel: a=1,b=1,x=1=1,x=2=0",
+            "statement 2: unsat",
+            "statement 3: sat\nmodel: c=1,x=1=1,x=2=0\n",
+        ]
+    );
+}
+
+#[test]
+fn test_max_nodes() {
+    let run = |max_nodes: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args([
+                "-e",
+                "(a <=> e) & (b <=> f) & (c <=> g) & (d <=> h)",
+                "-m",
+                "--max-nodes",
+                max_nodes,
+            ])
+            .output()
+            .expect("failed to run rsbdd")
+    };
+
+    let limited = run("10");
+    assert!(!limited.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&limited.stderr),
+        "Error: node limit of 10 exceeded\n"
+    );
+
+    let unlimited = run("1000");
+    assert!(unlimited.status.success());
+}
+
+#[test]
+fn test_explain_unsat() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args([
+            "-e",
+            "a | b\nc => d\nc & !d\n",
+            "--conjunction",
+            "--explain-unsat",
+        ])
+        .output()
+        .expect("failed to run rsbdd");
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "unsat core:\nconstraint 2: c => d\nconstraint 3: c & !d\n"
+    );
+}
+
+#[test]
+fn test_dump_vars() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args([
+            "-e",
+            "if exists a # a <=> b then b <=> c else false | c",
+            "--dump-vars",
+        ])
+        .output()
+        .expect("failed to run rsbdd");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .starts_with("0\ta\tbound\n1\tb\tfree\t0\n2\tc\tfree\t1\n"));
+}
+
+#[test]
+fn test_input_format() {
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(args)
+            .output()
+            .expect("failed to run rsbdd")
+    };
+
+    // DIMACS input is detected by its header, also after comments
+    let dimacs = run(&["-e", "c example\np cnf 2 2\n1 2 0\n-1 0\n", "-m", "-v"]);
+    assert!(dimacs.status.success());
+    assert_eq!(String::from_utf8_lossy(&dimacs.stdout), "2;\n");
+
+    // any other input is parsed as rsbdd, also when starting with a variable named c
+    let rsbdd = run(&["-e", "c & !p", "-m", "-v"]);
+    assert!(rsbdd.status.success());
+    assert_eq!(String::from_utf8_lossy(&rsbdd.stdout), "c;\n");
+
+    // dot files are rejected
+    let dot = run(&["-e", "digraph bdd {\n}\n"]);
+    assert!(!dot.status.success());
+    assert!(String::from_utf8_lossy(&dot.stderr).contains("graphviz dot file"));
+
+    // the format can be forced
+    let forced = run(&["-e", "p cnf", "--input-format", "rsbdd"]);
+    assert!(!forced.status.success());
+    let forced = run(&["-e", "1 0\n", "--input-format", "dimacs"]);
+    assert!(String::from_utf8_lossy(&forced.stderr).contains("expected DIMACS header"));
+}
+
+#[test]
+fn test_expand_truth_table() {
+    let truth_table = |expand: bool| {
+        let mut args = vec!["-e", "a | b & c", "--truthtable-csv"];
+        if expand {
+            args.push("--expand");
+        }
+
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(args)
+            .output()
+            .expect("failed to run rsbdd");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let reduced = truth_table(false);
+    assert_eq!(
+        reduced,
+        "a,b,c,result\nF,F,*,F\nF,T,F,F\nF,T,T,T\nT,*,*,T\n"
+    );
+
+    let expanded = truth_table(true);
+    assert_eq!(expanded.lines().count(), 1 + 8);
+    assert!(!expanded.contains('*'));
+    assert!(expanded.ends_with("T,F,F,T\nT,F,T,T\nT,T,F,T\nT,T,T,T\n"));
+}
+
+#[test]
+fn test_out_dir() {
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("out_dir_test");
+    let _ = std::fs::remove_dir_all(&out_dir);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["--statements", "-e", "a & b; a & !a", "--out-dir"])
+        .arg(&out_dir)
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(output.status.success());
+
+    let mut files: Vec<String> = std::fs::read_dir(&out_dir)
+        .expect("the output directory is not created")
+        .map(|entry| {
+            entry
+                .expect("cannot read the output directory")
+                .file_name()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    files.sort();
+    assert_eq!(files, vec!["formula_1.dot", "formula_2.dot"]);
+
+    let unsat =
+        std::fs::read_to_string(out_dir.join("formula_2.dot")).expect("cannot read the dot file");
+    assert!(unsat.starts_with("digraph"));
+
+    // a single input file is named after its stem
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["tests/data/set_abc.txt", "--format", "json", "--out-dir"])
+        .arg(&out_dir)
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(output.status.success());
+    assert!(out_dir.join("set_abc.json").exists());
+}
+
+#[test]
+fn test_warm_ordering() {
+    let ord_file = Path::new(env!("CARGO_TARGET_TMPDIR")).join("warm_ordering.txt");
+    let _ = std::fs::remove_file(&ord_file);
+
+    let run = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", "b & a | c", "--warm-ordering"])
+            .arg(&ord_file)
+            .output()
+            .expect("failed to run rsbdd");
+        assert!(output.status.success());
+        std::fs::read_to_string(&ord_file).expect("the ordering is not written")
+    };
+
+    // a missing file starts from the default ordering
+    assert_eq!(run(), "b\na\nc\n");
+
+    // an existing file is used as ordering and written back
+    std::fs::write(&ord_file, "c\na\n").expect("cannot write the ordering");
+    assert_eq!(run(), "c\na\nb\n");
+}
+
+#[test]
+fn test_marginals() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "a | b & c", "--marginals"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(output.status.success());
+
+    // for every variable, the counts sum to the 5 models of the formula
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "| name | #true | #false |\n| a    |     4 |      1 |\n| b    |     3 |      2 |\n| c    |     3 |      2 |\n"
+    );
+
+    // the model counts of 130 variables do not fit in a u128
+    let formula = (0..130)
+        .map(|i| format!("x{i}"))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", &formula, "--marginals"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not fit in a u128"));
+}
+
+#[test]
+fn test_empty_input() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "  "])
+        .env("RUST_BACKTRACE", "1")
+        .output()
+        .expect("failed to run rsbdd");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Error: the input does not contain a formula, use 'true' or 'false' for a constant formula\n"
+    );
+}
+
+#[test]
+fn test_level_stats() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "[a, b, c, d, e, f] = 3", "--level-stats"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let widths: Vec<usize> = stdout
+        .lines()
+        .skip(1)
+        .map(|line| {
+            line.trim_matches(|c| c == '|' || c == ' ')
+                .rsplit(' ')
+                .next()
+                .and_then(|n| n.parse().ok())
+                .expect("expected a node count")
+        })
+        .collect();
+
+    // the symmetric function is widest in the middle levels
+    assert_eq!(widths, vec![1, 2, 3, 4, 3, 2]);
+}
+
+#[test]
+fn test_decision_tree() {
+    let dot_file = Path::new(env!("CARGO_TARGET_TMPDIR")).join("decision_tree.dot");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "a | b & c", "--decision-tree", "--dot"])
+        .arg(&dot_file)
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(output.status.success());
+
+    let dot = std::fs::read_to_string(&dot_file).expect("cannot read the dot file");
+    let count = |label: &str| dot.lines().filter(|l| l.contains(label)).count();
+
+    // 2^3 leaves, of which the 3 assignments satisfying !a & !(b & c) lead to false
+    assert_eq!(count("[label=\"true\"]"), 5);
+    assert_eq!(count("[label=\"false\"]"), 3);
+    assert_eq!(count("[label=<b>]"), 2);
+    assert_eq!(count("[label=<c>]"), 4);
+}
+
+#[test]
+fn test_watch() {
+    use std::io::BufRead;
+    use std::process::{Command, Stdio};
+
+    let input = Path::new(env!("CARGO_TARGET_TMPDIR")).join("watch_test.txt");
+    std::fs::write(&input, "a | b").expect("cannot write the input");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .arg("--watch")
+        .arg(&input)
+        .arg("--count-paths")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run rsbdd");
+
+    let mut lines = std::io::BufReader::new(child.stdout.take().expect("no stdout")).lines();
+    let mut next_line = || {
+        lines
+            .next()
+            .expect("rsbdd stopped watching")
+            .expect("cannot read stdout")
+    };
+
+    assert_eq!(next_line(), "2");
+
+    // a parse error is reported without ending the session
+    std::fs::write(&input, "a & &").expect("cannot write the input");
+    std::fs::write(&input, "a & (b | c)").expect("cannot write the input");
+    assert_eq!(next_line(), "2");
+
+    std::fs::write(&input, "a").expect("cannot write the input");
+    assert_eq!(next_line(), "1");
+
+    child.kill().expect("failed to stop rsbdd");
+    child.wait().expect("failed to wait for rsbdd");
+}
+
+#[test]
+fn test_care_set() {
+    let care_file = Path::new(env!("CARGO_TARGET_TMPDIR")).join("care_set.txt");
+
+    let run = |care: &str| {
+        std::fs::write(&care_file, care).expect("cannot write the care set");
+        std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", "(a & b) | (!a & c)", "--count-paths", "--care-set"])
+            .arg(&care_file)
+            .output()
+            .expect("failed to run rsbdd")
+    };
+
+    // if a always holds, the choice on c is irrelevant
+    let output = run("a");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("the care set reduced the bdd from 5 to 3 nodes"));
+    // only the path through b remains
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+
+    let output = run("a | d");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("the care set refers to variable d, which does not occur in the input"));
+}
+
+#[test]
+fn test_summary_json() {
+    let run = |formula: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", formula, "--summary-json", "-t"])
+            .output()
+            .expect("failed to run rsbdd");
+        assert!(output.status.success());
+        assert!(output.stderr.is_empty());
+
+        // the summary is the only output, even if other output is requested
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("invalid json")
+    };
+
+    let summary = run("(a & b) | c");
+    assert_eq!(summary["sat"], true);
+    assert_eq!(summary["model_count"], 5);
+    assert_eq!(summary["node_count"], 5);
+    assert_eq!(summary["free_vars"], serde_json::json!(["a", "b", "c"]));
+    assert!(summary["elapsed_ms"].as_f64().is_some_and(|ms| ms >= 0.0));
+
+    let summary = run("a & !a");
+    assert_eq!(summary["sat"], false);
+    assert_eq!(summary["model_count"], 0);
+    assert_eq!(summary["node_count"], 1);
+
+    // the summary describes an evaluated result
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "a", "--summary-json", "--benchmark", "0"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--summary-json requires"));
+
+    // the model count of 130 variables does not fit in the summary
+    let formula = (0..130)
+        .map(|i| format!("x{i}"))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", &formula, "--summary-json"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not fit in a u128"));
+}
+
+#[test]
+fn test_is_tautology_contradiction() {
+    let run = |formula: &str, flag: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", formula, flag])
+            .output()
+            .expect("failed to run rsbdd");
+
+        // the outcome is only reported by the exit code
+        assert!(output.stdout.is_empty());
+        assert!(output.stderr.is_empty());
+        output.status.code()
+    };
+
+    assert_eq!(run("a | !a", "--is-tautology"), Some(0));
+    assert_eq!(run("a & !a", "--is-tautology"), Some(1));
+    assert_eq!(run("a", "--is-tautology"), Some(1));
+
+    assert_eq!(run("a | !a", "--is-contradiction"), Some(1));
+    assert_eq!(run("a & !a", "--is-contradiction"), Some(0));
+    assert_eq!(run("a", "--is-contradiction"), Some(1));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "a", "--is-contradiction", "--verbose"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "not a contradiction\n"
+    );
+
+    // errors are distinguished from a property that does not hold
+    for flag in ["--is-tautology", "--is-contradiction"] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", "a & (", flag])
+            .output()
+            .expect("failed to run rsbdd");
+        assert_eq!(output.status.code(), Some(2), "{flag}");
+        assert!(!output.stderr.is_empty());
+    }
+
+    // the checked properties cannot be combined with watching the input
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["formula.txt", "--watch", "--is-tautology"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn test_show_metadata() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args([
+            "-e",
+            "\"Generated by test-gen puzzle=[1.2]\"\na & b \"not metadata\"",
+            "--show-metadata",
+        ])
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Generated by test-gen puzzle=[1.2]\n"
+    );
+
+    // the metadata is shared by all statements
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+        .args(["-e", "\"meta\"\na; !b;", "--statements", "--show-metadata"])
+        .output()
+        .expect("failed to run rsbdd");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "meta\n\nstatement 1: sat\nmodel: a=1\n\nstatement 2: sat\nmodel: b=0\n"
+    );
+
+    // the metadata would break outputs that are meant to be the only output
+    for flag in ["--summary-json", "--is-tautology", "--is-contradiction"] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rsbdd"))
+            .args(["-e", "\"meta\"\na", "--show-metadata", flag])
+            .output()
+            .expect("failed to run rsbdd");
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("cannot be used with"),
+            "{flag}"
+        );
+    }
+}
+
+#[test]
+fn test_bddset_literals() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let run = |input: &str, bits: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_bddset"))
+            .args(["--bits", bits])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to run bddset");
+
+        child
+            .stdin
+            .take()
+            .expect("no stdin")
+            .write_all(input.as_bytes())
+            .expect("failed to write to stdin");
+
+        child.wait_with_output().expect("failed to wait for bddset")
+    };
+
+    let output = run("insert 0x1f 0b101 7; contains 0x1F 31 0b101 6; card", "8");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "0x1F: true\n31: true\n0b101: true\n6: false\n3\n"
+    );
+
+    // the largest element of a 64 bit set
+    let output = run("insert 0xffffffffffffffff; card", "64");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+
+    // prefixes without digits, invalid digits and literals exceeding the number of bits are rejected
+    for (literal, error) in [
+        ("0x", "cannot parse literal '0x'"),
+        ("0b", "cannot parse literal '0b'"),
+        ("0b102", "cannot parse literal '0b102'"),
+        ("256", "does not fit in 8 bits"),
+        ("0x100", "does not fit in 8 bits"),
+        ("0b100000000", "does not fit in 8 bits"),
+        ("18446744073709551616", "cannot parse literal"),
+    ] {
+        let output = run(&format!("insert {literal}"), "8");
+        assert!(!output.status.success(), "{literal}");
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains(error),
+            "{literal}"
+        );
+    }
+}
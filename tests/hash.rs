@@ -142,3 +142,26 @@ fn test_duplicates() {
         }
     }
 }
+
+// structurally identical bdds must have the same hash, regardless of the environment they are constructed in
+#[test]
+fn test_structural_hash() {
+    let a = BDDEnv::new();
+    let b = BDDEnv::new();
+
+    let expr_a = a.or(a.and(a.var(0), a.var(1)), a.not(a.var(2)));
+    let expr_b = b.or(b.not(b.var(2)), b.and(b.var(1), b.var(0)));
+
+    assert!(!Rc::ptr_eq(&expr_a, &expr_b));
+    assert_eq!(expr_a.get_hash(), expr_b.get_hash());
+    assert_eq!(expr_a, expr_b);
+
+    let other = a.or(a.and(a.var(0), a.var(1)), a.var(2));
+    assert_ne!(expr_a.get_hash(), other.get_hash());
+    assert_ne!(expr_a, other);
+
+    // a manually constructed choice matches the choice created by the environment
+    let manual = BDD::choice(a.mk_const(true), 0, a.mk_const(false));
+    assert_eq!(manual.get_hash(), a.var(0).get_hash());
+    assert_eq!(&manual, a.var(0).as_ref());
+}
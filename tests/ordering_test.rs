@@ -0,0 +1,74 @@
+use std::io;
+use std::io::BufReader;
+
+use pretty_assertions::assert_eq;
+
+use rsbdd::ordering::*;
+use rsbdd::parser::*;
+
+// pairwise equivalences guarded by a disjunction, such that all a variables appear before all b variables
+fn pairs_formula(n: usize) -> String {
+    let guard = (0..n)
+        .map(|i| format!("a{i}"))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let pairs = (0..n)
+        .map(|i| format!("(a{i} <=> b{i})"))
+        .collect::<Vec<_>>()
+        .join(" & ");
+
+    format!("({guard}) => ({pairs})")
+}
+
+#[test]
+fn test_force_order() -> io::Result<()> {
+    let parsed = ParsedFormula::new(&mut BufReader::new(pairs_formula(8).as_bytes()), None)?;
+
+    let size = |heuristic: OrderingHeuristic| {
+        let order = parsed.heuristic_order(heuristic);
+        assert_eq!(order.len(), parsed.vars.len());
+
+        let reordered = parsed
+            .with_order(&order)
+            .expect("the formula can be reordered");
+        let result = reordered.eval();
+        reordered.env.size_reachable(&result)
+    };
+
+    let appearance = size(OrderingHeuristic::Appearance);
+    let force = size(OrderingHeuristic::Force);
+    let dfs = size(OrderingHeuristic::Dfs);
+
+    // the order of appearance separates every pair, which is exponential in the number of pairs
+    assert!(
+        force * 4 < appearance,
+        "force: {force}, appearance: {appearance}"
+    );
+    assert!(dfs * 4 < appearance, "dfs: {dfs}, appearance: {appearance}");
+
+    // the order of appearance is the original order
+    assert_eq!(
+        parsed.heuristic_order(OrderingHeuristic::Appearance),
+        parsed.vars
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_heuristic_names() -> anyhow::Result<()> {
+    for heuristic in [
+        OrderingHeuristic::Appearance,
+        OrderingHeuristic::Dfs,
+        OrderingHeuristic::Force,
+    ] {
+        assert_eq!(
+            heuristic.to_string().parse::<OrderingHeuristic>()?,
+            heuristic
+        );
+    }
+
+    assert!("sifting".parse::<OrderingHeuristic>().is_err());
+
+    Ok(())
+}
@@ -6,8 +6,11 @@ use std::rc::Rc;
 use pretty_assertions::assert_eq;
 
 use rsbdd::bdd::*;
+use rsbdd::bdd_io::{to_rust_fn, BDDGraph};
+use rsbdd::escape::{json_string, sanitize_name};
 use rsbdd::parser::*;
-use rsbdd::NamedSymbol;
+use rsbdd::parser_io::{write_blif, SymbolicParseTree};
+use rsbdd::{NamedSymbol, TruthTableEntry};
 
 #[test]
 fn test_basic_tokens() -> io::Result<()> {
@@ -71,6 +74,63 @@ fn test_parser() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_unicode_tokens() -> io::Result<()> {
+    let tokenize = |src: &str| SymbolicBDD::tokenize(&mut BufReader::new(src.as_bytes()), None);
+
+    // unicode operators map onto the same tokens as their ascii counterparts
+    assert_eq!(
+        tokenize("¬a ∧ (b ∨ c) → d ↔ e ⊕ f")?,
+        tokenize("!a & (b | c) => d <=> e ^ f")?
+    );
+    assert_eq!(
+        tokenize("∀ a # ∃ b # a ∧ b")?,
+        tokenize("forall a # exists b # a & b")?
+    );
+
+    // ascii and unicode can be mixed in a single formula
+    assert_eq!(tokenize("a & b ∨ ¬c => d")?, tokenize("a ∧ b | !c → d")?);
+
+    assert_eq!(parse_and_evaluate("∀ a # (a ∨ ¬a)")?, env().mk_const(true));
+    assert_eq!(
+        parse_and_evaluate("(a ∧ b) ↔ ¬(¬a | !b)")?,
+        env().mk_const(true)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_range_expansion() -> io::Result<()> {
+    let tokenize = |src: &str| SymbolicBDD::tokenize(&mut BufReader::new(src.as_bytes()), None);
+
+    // the generated variables are assigned ids in order
+    assert_eq!(
+        tokenize("[@range(x,0,2)] = 1")?,
+        tokenize("[x_0, x_1, x_2] = 1")?
+    );
+    assert_eq!(
+        tokenize("a & [@range( y , 3, 4 ), b] >= 1")?,
+        tokenize("a & [y_3, y_4, b] >= 1")?
+    );
+    assert_eq!(tokenize("@range(z, 5, 5)")?, tokenize("z_5")?);
+
+    assert!(tokenize("[@range(x, 2, 1)] = 1").is_err());
+
+    // the length of a range is limited
+    let max = MAX_RANGE_LENGTH - 1;
+    assert!(tokenize(&format!("[@range(x, 0, {max})] = 1")).is_ok());
+    assert_eq!(
+        tokenize(&format!("[@range(x, 0, {})] = 1", max + 1))
+            .map(|_| ())
+            .map_err(|e| e.kind()),
+        Err(io::ErrorKind::InvalidData)
+    );
+    assert!(ParsedFormula::try_parse("[@range(x, 0, 4294967295)] = 1").is_err());
+
+    Ok(())
+}
+
 fn parse_and_evaluate(test_str: &str) -> io::Result<Rc<BDD<usize>>> {
     let result = ParsedFormula::new(&mut BufReader::new(test_str.as_bytes()), None)?;
     Ok(Rc::new(BDD::<usize>::from(result.eval().as_ref().clone())))
@@ -164,3 +224,1241 @@ fn test_cliques_file() -> io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_countable_offset() -> io::Result<()> {
+    let parse = |s: &str| ParsedFormula::new(&mut BufReader::new(s.as_bytes()), None);
+
+    assert!(matches!(
+        parse("[a, b, c] = [d, e] + 1")?.bdd,
+        SymbolicBDD::CountableVariable(CountableOperator::Exactly, _, _, 1)
+    ));
+    assert!(matches!(
+        parse("[a] <= [b] - 2")?.bdd,
+        SymbolicBDD::CountableVariable(CountableOperator::AtMost, _, _, -2)
+    ));
+    assert!(matches!(
+        parse("[a] = [b]")?.bdd,
+        SymbolicBDD::CountableVariable(CountableOperator::Exactly, _, _, 0)
+    ));
+
+    // a sign followed by a formula is not an offset
+    assert!(matches!(
+        parse("[a] = [b] + c")?.bdd,
+        SymbolicBDD::BinaryOp(BinaryOperator::Or, _, _)
+    ));
+
+    // only the '+' and '-' signs start an offset, not the other spellings of disjunction and negation
+    for src in [
+        "[a] = [b] | 1",
+        "[a] = [b] or 1",
+        "[a] = [b] ∨ 1",
+        "[a] = [b] ! 1",
+    ] {
+        assert!(parse(src).is_err(), "{src} is accepted");
+    }
+    assert_eq!(parse_and_evaluate("a + b")?, parse_and_evaluate("a | b")?);
+
+    assert_eq!(parse_and_evaluate("[a] = [] + 1")?, env().var(0));
+    assert_eq!(
+        parse_and_evaluate("[a, b] = [c] + 1")?,
+        parse_and_evaluate("(a & b & c) | ((a ^ b) & -c)")?
+    );
+    assert_eq!(
+        parse_and_evaluate("[a] = [b] - 1")?,
+        parse_and_evaluate("-a & b")?
+    );
+    assert_eq!(
+        parse_and_evaluate("[a, b] >= [c] + 2")?,
+        parse_and_evaluate("a & b & -c")?
+    );
+    assert_eq!(
+        parse_and_evaluate("[a] < [b] + 1")?,
+        parse_and_evaluate("a => b")?
+    );
+    assert_eq!(
+        parse_and_evaluate("[a] > [b] - 1")?,
+        parse_and_evaluate("a | -b")?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_countable_not_equal() -> io::Result<()> {
+    let parse = |s: &str| ParsedFormula::new(&mut BufReader::new(s.as_bytes()), None);
+
+    assert!(matches!(
+        parse("[a, b] != 1")?.bdd,
+        SymbolicBDD::CountableConst(CountableOperator::NotEqual, _, 1)
+    ));
+    assert!(matches!(
+        parse("[a] != [b] + 1")?.bdd,
+        SymbolicBDD::CountableVariable(CountableOperator::NotEqual, _, _, 1)
+    ));
+
+    assert_eq!(
+        parse_and_evaluate("[a, b] != 1")?,
+        parse_and_evaluate("a <=> b")?
+    );
+    assert_eq!(
+        parse_and_evaluate("[a] != [b]")?,
+        parse_and_evaluate("a ^ b")?
+    );
+    assert_eq!(parse_and_evaluate("[a] != []")?, parse_and_evaluate("a")?);
+    assert_eq!(
+        parse_and_evaluate("[a, b] != [c] + 1")?,
+        parse_and_evaluate("-([a, b] = [c] + 1)")?
+    );
+
+    // the negation operator is still recognized
+    assert_eq!(parse_and_evaluate("!a")?, parse_and_evaluate("-a")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_relabel() -> io::Result<()> {
+    let input_parsed = ParsedFormula::new(&mut BufReader::new(&b"a & (-b | c)"[..]), None)?;
+
+    let named = input_parsed.eval();
+    let compact = BDD::<usize>::from(named.as_ref().clone());
+
+    assert_eq!(
+        BDD::<NamedSymbol>::relabel(&compact, &input_parsed.vars),
+        named.as_ref().clone()
+    );
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "no name provided for variable with id 1")]
+fn test_relabel_missing_name() {
+    let e = env();
+    let compact = e.and(e.var(0), e.var(1));
+
+    let names = vec![NamedSymbol {
+        name: Rc::new("a".to_string()),
+        id: 0,
+    }];
+
+    BDD::<NamedSymbol>::relabel(&compact, &names);
+}
+
+#[test]
+fn test_reeval_with_order() -> io::Result<()> {
+    let src = &b"(a1 & b1) | (a2 & b2) | (a3 & b3)"[..];
+
+    let order: Vec<NamedSymbol> = ["a1", "a2", "a3", "b1", "b2", "b3"]
+        .iter()
+        .enumerate()
+        .map(|(id, name)| NamedSymbol {
+            name: Rc::new(name.to_string()),
+            id,
+        })
+        .collect();
+
+    let input_parsed = ParsedFormula::new(&mut BufReader::new(src), None)?;
+    let expected = ParsedFormula::new(&mut BufReader::new(src), Some(order.clone()))?;
+
+    let original = input_parsed.eval();
+    let reordered = input_parsed.reeval_with_order(&order)?;
+
+    assert_eq!(reordered, expected.eval());
+    assert!(expected.env.size_reachable(&reordered) > input_parsed.env.size_reachable(&original));
+
+    // variables missing from the ordering are placed after the ordered variables
+    let partial = input_parsed.with_order(&order[3..])?;
+    let names: Vec<&str> = partial.vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["b1", "b2", "b3", "a1", "a2", "a3"]);
+    assert_eq!(partial.free_vars, partial.vars);
+
+    Ok(())
+}
+
+#[test]
+fn test_reorder_unsupported() -> io::Result<()> {
+    let input_parsed = ParsedFormula::new(&mut BufReader::new(&b"a & {r}"[..]), None)?;
+    let order = input_parsed.vars.clone();
+    assert!(input_parsed.with_order(&order).is_ok());
+
+    // a definition holding a bdd cannot be reordered
+    input_parsed.define("r", ReferenceContents::BDD(input_parsed.eval()));
+    let Err(error) = input_parsed.with_order(&order) else {
+        panic!("the reordering succeeded");
+    };
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(
+        error.to_string(),
+        "reordering a formula with BDD references/subtrees is not supported"
+    );
+
+    // neither can a bdd subtree in the formula
+    let mut input_parsed = ParsedFormula::new(&mut BufReader::new(&b"a"[..]), None)?;
+    input_parsed.bdd = SymbolicBDD::Not(Box::new(SymbolicBDD::Subtree(input_parsed.eval())));
+    let Err(error) = input_parsed.with_order(&order) else {
+        panic!("the reordering succeeded");
+    };
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+    Ok(())
+}
+
+fn parse_with_encoding(src: &str, encoding: DomainEncoding) -> io::Result<ParsedFormula> {
+    ParsedFormula::new_with_encoding(
+        Rc::new(BDDEnv::new()),
+        &mut BufReader::new(src.as_bytes()),
+        None,
+        encoding,
+    )
+}
+
+#[test]
+fn test_domain_one_hot() -> io::Result<()> {
+    let expanded = parse_with_encoding(
+        "var cell[0..1] in 1..3; cell[0] = 2 & -(cell[1] = 2) | other",
+        DomainEncoding::OneHot,
+    )?;
+
+    // the domain name itself is not a variable, the encoding variables follow the regular variables
+    let names: Vec<&str> = expanded.vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "other",
+            "cell[0]=1",
+            "cell[0]=2",
+            "cell[0]=3",
+            "cell[1]=1",
+            "cell[1]=2",
+            "cell[1]=3"
+        ]
+    );
+
+    // variables are compared by id, so the hand-written formula must introduce its variables in the same order
+    // note that binary operators are right-associative: 'a & b | c' is parsed as 'a & (b | c)'
+    let hand_written = ParsedFormula::new(
+        &mut BufReader::new(
+            &b"(other | -other) & [c01, c02, c03] = 1 & [c11, c12, c13] = 1 & (c02 & (-c12 | other))"[..],
+        ),
+        None,
+    )?;
+
+    assert_eq!(expanded.eval(), hand_written.eval());
+
+    Ok(())
+}
+
+#[test]
+fn test_domain_log() -> io::Result<()> {
+    let expanded = parse_with_encoding("var x in 4..6; x = 5 | x = 6", DomainEncoding::Log)?;
+
+    let names: Vec<&str> = expanded.vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["x.0", "x.1"]);
+
+    // the index 3 (0b11) is excluded by the implicit range constraint
+    let hand_written = ParsedFormula::new(
+        &mut BufReader::new(
+            &b"((-b0 & -b1) | (b0 & -b1) | (-b0 & b1)) & ((b0 & -b1) | (-b0 & b1))"[..],
+        ),
+        None,
+    )?;
+
+    assert_eq!(expanded.eval(), hand_written.eval());
+
+    Ok(())
+}
+
+#[test]
+fn test_domain_errors() {
+    for src in [
+        "var x in 1..3; x = 4",
+        "var x in 1..3; y = 1",
+        "var x in 1..3; x[0] = 1",
+        "var x in 3..1; true",
+        "var x in 1..3; var x in 1..2; true",
+        "var x in 1..3; x",
+        "var x => 1..3; x = 1",
+    ] {
+        assert!(
+            parse_with_encoding(src, DomainEncoding::OneHot).is_err(),
+            "expected an error for '{src}'"
+        );
+    }
+}
+
+#[test]
+fn test_undeclared_domain() {
+    // domain references are checked even if no domain is declared at all
+    for src in ["x = 5", "a & x[0] = 1", "(x = 1) | a"] {
+        let err = ParsedFormula::try_parse(src).expect_err("expected an error");
+        assert!(
+            err.to_string().contains("Undeclared domain variable x"),
+            "unexpected error for '{src}': {err}"
+        );
+    }
+}
+
+#[test]
+fn test_var_name() -> io::Result<()> {
+    // 'var' only starts a domain declaration if it is followed by the name of the declared variable
+    let parsed = ParsedFormula::try_parse("var & !b | (var => c)")?;
+    let names: Vec<&str> = parsed.vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["var", "b", "c"]);
+    assert!(parsed.domains.is_empty());
+
+    assert_eq!(
+        parse_and_evaluate("(exists var # var) & (var nor false) <=> !var")?,
+        env().mk_const(true)
+    );
+
+    // 'in' is still an implication outside of a domain declaration
+    assert_eq!(
+        parse_and_evaluate("(a in b) <=> (a => b)")?,
+        env().mk_const(true)
+    );
+
+    let parsed = ParsedFormula::try_parse("var x in 1..2;\nx = 2 & var")?;
+    assert_eq!(parsed.domains.len(), 1);
+    assert!(parsed.vars.iter().any(|v| v.name.as_str() == "var"));
+
+    // quoted names can be declared as well
+    let parsed =
+        ParsedFormula::try_parse("var `x y` in 1..2; var 'in' in 1..2;\n`x y` = 2 & 'in' = 1")?;
+    let names: Vec<&str> = parsed
+        .domains
+        .iter()
+        .map(|d| d.symbol.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["x y", "in"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_assign() -> io::Result<()> {
+    let mut input_parsed = ParsedFormula::new(&mut BufReader::new(&b"a & b | c"[..]), None)?;
+
+    let result = input_parsed.eval();
+    let assigned = input_parsed.assign(result, &[("a".to_string(), false)])?;
+
+    // a & (b | c) with a = false
+    assert_eq!(assigned, input_parsed.env.mk_const(false));
+
+    let names: Vec<&str> = input_parsed
+        .free_vars
+        .iter()
+        .map(|v| v.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["b", "c"]);
+
+    let b = input_parsed.name2var("b").expect("b is a variable");
+    let c = input_parsed.name2var("c").expect("c is a variable");
+    assert_eq!(input_parsed.to_free_index(&b), 0);
+    assert_eq!(input_parsed.to_free_index(&c), 1);
+
+    let result = input_parsed.eval();
+    assert!(input_parsed
+        .assign(result, &[("d".to_string(), true)])
+        .is_err());
+
+    // an unknown name leaves the free variables unchanged, even if it follows a known name
+    let result = input_parsed.eval();
+    assert!(input_parsed
+        .assign(result, &[("b".to_string(), true), ("d".to_string(), true)])
+        .is_err());
+    assert_eq!(input_parsed.free_vars.len(), 2);
+    assert_eq!(input_parsed.to_free_index(&b), 0);
+    assert_eq!(input_parsed.to_free_index(&c), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_free_vars_of() -> io::Result<()> {
+    let parsed = ParsedFormula::new(
+        &mut BufReader::new(&b"exists a # (a & b) | (forall c # c => d)"[..]),
+        None,
+    )?;
+
+    let names = |vars: Vec<NamedSymbol>| -> Vec<String> {
+        vars.iter().map(|v| v.name.as_ref().clone()).collect()
+    };
+
+    // bound variables are excluded
+    assert_eq!(names(parsed.free_vars_of(&parsed.bdd)), vec!["b", "d"]);
+    assert_eq!(names(parsed.free_vars.clone()), vec!["b", "d"]);
+
+    // the body of the outer quantifier has a as a free variable
+    match &parsed.bdd {
+        SymbolicBDD::Quantifier(_, _, body) => {
+            assert_eq!(names(parsed.free_vars_of(body)), vec!["a", "b", "d"]);
+        }
+        other => panic!("expected a quantifier, got {other:?}"),
+    }
+
+    // references are resolved using the definitions
+    let mut parsed = ParsedFormula::new(&mut BufReader::new(&b"{x} | c"[..]), None)?;
+    let definition = ParsedFormula::new(
+        &mut BufReader::new(&b"c & forall d # d | e"[..]),
+        Some(parsed.vars.clone()),
+    )?;
+    parsed.define("x", ReferenceContents::Syntax(definition.bdd));
+    parsed.add_vars(&definition.vars);
+
+    assert_eq!(names(parsed.free_vars_of(&parsed.bdd)), vec!["c", "e"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_conjunction_mode() -> io::Result<()> {
+    let src = std::fs::read_to_string("examples/4_queens.txt")?;
+
+    // remove the trailing '&' from every line
+    let lines = src
+        .lines()
+        .map(|l| l.trim_end().trim_end_matches('&'))
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    let env = Rc::new(BDDEnv::new());
+
+    let joined =
+        ParsedFormula::new_with_env(Rc::clone(&env), &mut BufReader::new(src.as_bytes()), None)?;
+    let separated = ParsedFormula::new_conjunction(
+        Rc::clone(&env),
+        &mut BufReader::new(lines.as_bytes()),
+        None,
+        DomainEncoding::default(),
+    )?;
+
+    assert_eq!(joined.vars, separated.vars);
+    assert_eq!(joined.eval(), separated.eval());
+    assert_eq!(
+        env.satcount(&separated.eval(), separated.free_vars.len()),
+        2
+    );
+
+    // domain declarations precede the formulas, empty lines are ignored
+    let domains = ParsedFormula::new_conjunction(
+        Rc::clone(&env),
+        &mut BufReader::new(&b"var x in 1..3;\n\nx = 1 | x = 2\n\"comment\"\n!(x = 1)\n"[..]),
+        None,
+        DomainEncoding::OneHot,
+    )?;
+    let names: Vec<&str> = domains.free_vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["x=1", "x=2", "x=3"]);
+    assert_eq!(env.satcount(&domains.eval(), 3), 1);
+
+    // an empty input is true
+    let empty = ParsedFormula::new_conjunction(
+        Rc::clone(&env),
+        &mut BufReader::new(&b"\n\n"[..]),
+        None,
+        DomainEncoding::default(),
+    )?;
+    assert!(empty.eval().is_true());
+
+    Ok(())
+}
+
+#[test]
+fn test_parsetree_json() -> io::Result<()> {
+    let parsed = ParsedFormula::new(
+        &mut BufReader::new(&b"exists a # [a, b] >= 2 | !{r}"[..]),
+        None,
+    )?;
+
+    let mut output = Vec::new();
+    SymbolicParseTree::new(&parsed.bdd).to_json(&mut output)?;
+    let output = String::from_utf8(output).expect("json output is not valid utf-8");
+
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "{",
+            "\"root\": 6,",
+            "\"nodes\": [",
+            "{\"id\": 0, \"kind\": \"CountableConst\", \"operator\": \"AtLeast\", \"threshold\": 2, \"children\": [1, 2]},",
+            "{\"id\": 1, \"kind\": \"Var\", \"name\": \"a\", \"var_id\": 0},",
+            "{\"id\": 2, \"kind\": \"Var\", \"name\": \"b\", \"var_id\": 1},",
+            "{\"id\": 3, \"kind\": \"Reference\", \"name\": \"r\"},",
+            "{\"id\": 4, \"kind\": \"Not\", \"children\": [3]},",
+            "{\"id\": 5, \"kind\": \"BinaryOp\", \"operator\": \"Or\", \"children\": [0, 4]},",
+            "{\"id\": 6, \"kind\": \"Quantifier\", \"operator\": \"Exists\", \"variables\": [\"a\"], \"children\": [5]}",
+            "]",
+            "}",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_vars() -> io::Result<()> {
+    let parse = |src: &str, sort_vars: bool| {
+        ParsedFormula::new_with_options(
+            Rc::new(BDDEnv::new()),
+            &mut BufReader::new(src.as_bytes()),
+            None,
+            ParseOptions {
+                conjunction: true,
+                sort_vars,
+                ..Default::default()
+            },
+        )
+    };
+
+    let original = "c | b\nb => a\n!(a & c)\n";
+    let reordered = "!(a & c)\nb => a\nc | b\n";
+
+    // without sorting, the ids depend on the order of occurrence
+    assert_ne!(
+        parse(original, false)?.eval(),
+        parse(reordered, false)?.eval()
+    );
+
+    let sorted_1 = parse(original, true)?;
+    let sorted_2 = parse(reordered, true)?;
+
+    assert_eq!(sorted_1.vars, sorted_2.vars);
+    assert_eq!(sorted_1.eval(), sorted_2.eval());
+
+    let names: Vec<&str> = sorted_1.vars.iter().map(|v| v.name.as_str()).collect();
+    let ids: Vec<usize> = sorted_1.vars.iter().map(|v| v.id).collect();
+    assert_eq!(names, vec!["a", "b", "c"]);
+    assert_eq!(ids, vec![0, 1, 2]);
+
+    Ok(())
+}
+
+#[test]
+fn test_quoted_identifiers() -> io::Result<()> {
+    let tokens = SymbolicBDD::tokenize(
+        &mut BufReader::new(&b"`queen at (2,3)` & 'say \"hi\"' | `and` & queen"[..]),
+        None,
+    )?;
+
+    let var = |name: &str, id: usize| {
+        SymbolicBDDToken::Var(NamedSymbol {
+            name: Rc::new(name.to_string()),
+            id,
+        })
+    };
+
+    // quoted names are never keywords, and are distinct from the unquoted names
+    assert_eq!(
+        tokens,
+        vec![
+            var("queen at (2,3)", 0),
+            SymbolicBDDToken::And,
+            var("say \"hi\"", 1),
+            SymbolicBDDToken::Or,
+            var("and", 2),
+            SymbolicBDDToken::And,
+            var("queen", 3),
+            SymbolicBDDToken::Eof,
+        ]
+    );
+
+    // both delimiters refer to the same variable
+    assert_eq!(parse_and_evaluate("`x y` <=> 'x y'")?, env().mk_const(true));
+
+    // the names are escaped in the rendered outputs
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"'say \"hi\"'"[..]), None)?;
+
+    let mut json = Vec::new();
+    SymbolicParseTree::new(&parsed.bdd).to_json(&mut json)?;
+    let json = String::from_utf8(json).expect("json output is not valid utf-8");
+    assert!(json.contains(r#""name": "say \"hi\"""#));
+
+    let mut dot = Vec::new();
+    SymbolicParseTree::new(&parsed.bdd).render_dot(&mut dot)?;
+    let dot = String::from_utf8(dot).expect("dot output is not valid utf-8");
+    assert!(dot.contains("label=<Var say &quot;hi&quot;>"));
+
+    Ok(())
+}
+
+#[test]
+fn test_parsetree_flatten() -> io::Result<()> {
+    let parsed = ParsedFormula::new(
+        &mut BufReader::new(&b"a & b & (c & d) & e | !(f | g)"[..]),
+        None,
+    )?;
+    let tree = SymbolicParseTree::new(&parsed.bdd).with_flatten(true);
+
+    let mut dot = Vec::new();
+    tree.render_dot(&mut dot)?;
+    let dot = String::from_utf8(dot).expect("dot output is not valid utf-8");
+
+    // the 5-way conjunction is a single node with an edge to every operand
+    assert_eq!(dot.matches("label=<And>").count(), 1);
+    assert_eq!(dot.matches("label=<Or>").count(), 2);
+    for j in 0..5 {
+        assert!(dot.contains(&format!("[label=\"{{{j}}}\"]")));
+    }
+    assert!(!dot.contains("[label=\"{5}\"]"));
+    assert!(!dot.contains("[label=\"L\"]"));
+
+    let mut json = Vec::new();
+    tree.to_json(&mut json)?;
+    let json = String::from_utf8(json).expect("json output is not valid utf-8");
+    let and_children = json
+        .lines()
+        .find(|line| line.contains(r#""operator": "And""#))
+        .and_then(|line| line.split("\"children\": [").nth(1))
+        .and_then(|children| children.split(']').next())
+        .expect("no conjunction in the json output");
+    assert_eq!(and_children.split(", ").count(), 5);
+
+    // without flattening, every operator is a separate node
+    let mut dot = Vec::new();
+    SymbolicParseTree::new(&parsed.bdd).render_dot(&mut dot)?;
+    let dot = String::from_utf8(dot).expect("dot output is not valid utf-8");
+    assert_eq!(dot.matches("label=<And>").count(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_metadata() -> io::Result<()> {
+    let src = "\"Generated by test-gen version 1.0 puzzle=[1.2]\"\n\n\" sudoku\nhints \"\na & b \"not metadata\"\n";
+    let parse = |options: ParseOptions| {
+        ParsedFormula::new_with_options(
+            Rc::new(BDDEnv::new()),
+            &mut BufReader::new(src.as_bytes()),
+            None,
+            options,
+        )
+    };
+
+    let expected = vec![
+        "Generated by test-gen version 1.0 puzzle=[1.2]".to_string(),
+        "sudoku\nhints".to_string(),
+    ];
+
+    // the leading comments are collected by every way of reading the input
+    assert_eq!(parse(ParseOptions::default())?.metadata, expected);
+    for options in [
+        ParseOptions {
+            sort_vars: true,
+            ..Default::default()
+        },
+        ParseOptions {
+            conjunction: true,
+            ..Default::default()
+        },
+    ] {
+        assert_eq!(parse(options)?.metadata, expected);
+    }
+
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"a \"no metadata\""[..]), None)?;
+    assert!(parsed.metadata.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_name_escaping() -> io::Result<()> {
+    let parsed = ParsedFormula::new(
+        &mut BufReader::new("`a b` & 'q\"x' | `é∧` & `1<2>`".as_bytes()),
+        None,
+    )?;
+    let result = parsed.eval();
+
+    let mut dot = Vec::new();
+    SymbolicParseTree::new(&parsed.bdd).render_dot(&mut dot)?;
+    BDDGraph::new(&result, TruthTableEntry::Any).render_dot(&mut dot)?;
+    let dot = String::from_utf8(dot).expect("dot output is not valid utf-8");
+    for label in ["a b", "q&quot;x", "é∧", "1&lt;2&gt;"] {
+        assert!(dot.contains(&format!("label=<Var {label}>")), "{label}");
+        assert!(dot.contains(&format!("label=<{label}>")), "{label}");
+    }
+    assert!(!dot.contains("\\u{"));
+
+    let mut graphml = Vec::new();
+    BDDGraph::new(&result, TruthTableEntry::Any).render_graphml(&mut graphml)?;
+    let graphml = String::from_utf8(graphml).expect("graphml output is not valid utf-8");
+    assert!(graphml.contains(r#"<data key="label">q&quot;x</data>"#));
+    assert!(graphml.contains(r#"<data key="label">1&lt;2&gt;</data>"#));
+
+    let mut json = Vec::new();
+    SymbolicParseTree::new(&parsed.bdd).to_json(&mut json)?;
+    let json = String::from_utf8(json).expect("json output is not valid utf-8");
+    for name in [r#""a b""#, r#""q\"x""#, r#""é∧""#, r#""1<2>""#] {
+        assert!(json.contains(name), "{name}");
+    }
+
+    let mut blif = Vec::new();
+    write_blif(&parsed, &result, &mut blif)?;
+    let blif = String::from_utf8(blif).expect("blif output is not valid utf-8");
+    assert!(blif.contains("\n.inputs a_b q_x __ _1_2_\n"));
+
+    let mut rust = Vec::new();
+    to_rust_fn(&result, "f", &mut rust)?;
+    let rust = String::from_utf8(rust).expect("rust output is not valid utf-8");
+    assert!(rust.contains(r#"vars["q\"x"]"#));
+
+    // control characters are escaped in json
+    assert_eq!(json_string("a\tb\u{1}\""), r#""a\tb\u0001\"""#);
+    assert_eq!(sanitize_name(""), "_");
+
+    Ok(())
+}
+
+#[test]
+fn test_ite() -> io::Result<()> {
+    assert_eq!(
+        parse_and_evaluate("(if a then b else c) <=> ((a => b) & (!a => c))")?,
+        env().mk_const(true)
+    );
+
+    // the else branch defaults to true
+    assert_eq!(
+        parse_and_evaluate("(if a then b) <=> (a => b)")?,
+        env().mk_const(true)
+    );
+
+    // a trailing else belongs to the nearest if
+    assert_eq!(
+        parse_and_evaluate("(if a then if b then c else d) <=> (a => (if b then c else d))")?,
+        env().mk_const(true)
+    );
+
+    // constraint lists can be written without else branches
+    assert_eq!(
+        parse_and_evaluate("(if a then b) & (if b then c) & a & !c")?,
+        env().mk_const(false)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_when() -> io::Result<()> {
+    assert_eq!(
+        parse_and_evaluate("(when a then b) <=> (a => b)")?,
+        env().mk_const(true)
+    );
+    assert_eq!(
+        parse_and_evaluate("(when !a & b then (c | d)) <=> ((!a & b) => (c | d))")?,
+        env().mk_const(true)
+    );
+    assert_eq!(
+        parse_and_evaluate("(when a then b) & (when b then c) & a & !c")?,
+        env().mk_const(false)
+    );
+
+    // 'when' only starts a formula if it is followed by one
+    let parsed = ParsedFormula::try_parse("when & !b | (when => c) | [when, b] = 1")?;
+    let names: Vec<&str> = parsed.vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["when", "b", "c"]);
+
+    assert_eq!(
+        parse_and_evaluate("(when when then b) <=> (when => b)")?,
+        env().mk_const(true)
+    );
+
+    // the condition and the consequence are required
+    assert!(ParsedFormula::try_parse("when a").is_err());
+    assert!(ParsedFormula::try_parse("when a then").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_parity_majority() -> io::Result<()> {
+    assert_eq!(
+        parse_and_evaluate("parity(a, b, c) <=> a ^ b ^ c")?,
+        env().mk_const(true)
+    );
+    assert_eq!(
+        parse_and_evaluate("majority(a, b, c) <=> [a, b, c] >= 2")?,
+        env().mk_const(true)
+    );
+
+    // more than half of an even number of arguments
+    assert_eq!(
+        parse_and_evaluate("majority(a, b, c, d) <=> [a, b, c, d] > 2")?,
+        env().mk_const(true)
+    );
+
+    // the arguments are arbitrary formulas
+    assert_eq!(
+        parse_and_evaluate("parity(a & b, !c) <=> ((a & b) <=> c)")?,
+        env().mk_const(true)
+    );
+
+    // without arguments, neither an odd number nor more than half of the arguments can be true
+    assert_eq!(parse_and_evaluate("parity()")?, env().mk_const(false));
+    assert_eq!(parse_and_evaluate("majority()")?, env().mk_const(false));
+
+    // the builtins are only recognized when followed by their arguments, otherwise they are variables
+    assert_eq!(
+        parse_and_evaluate("(parity (a, b) <=> a ^ b) & (majority\t(a, b, c) <=> [a, b, c] >= 2)")?,
+        env().mk_const(true)
+    );
+    let parsed = ParsedFormula::try_parse("parity & !majority")?;
+    let names: Vec<&str> = parsed.vars.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["parity", "majority"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_all_eq() -> io::Result<()> {
+    assert_eq!(
+        parse_and_evaluate("all_eq(a, b) <=> (a <=> b)")?,
+        env().mk_const(true)
+    );
+    assert_eq!(parse_and_evaluate("all_eq(a, a, a)")?, env().mk_const(true));
+    assert_eq!(
+        parse_and_evaluate("all_eq(a, b, c) <=> ((a & b & c) | (!a & !b & !c))")?,
+        env().mk_const(true)
+    );
+    assert_eq!(parse_and_evaluate("all_eq(a, !a)")?, env().mk_const(false));
+    assert_eq!(parse_and_evaluate("all_eq()")?, env().mk_const(true));
+
+    // without arguments, all_eq is a variable
+    assert_eq!(
+        parse_and_evaluate("all_eq(all_eq, all_eq)")?,
+        env().mk_const(true)
+    );
+    assert_eq!(
+        parse_and_evaluate("all_eq | !all_eq")?,
+        env().mk_const(true)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_fold_constants() -> io::Result<()> {
+    let fold = |formula: &str| -> io::Result<String> {
+        let parsed = ParsedFormula::new(&mut BufReader::new(formula.as_bytes()), None)?;
+        Ok(parsed.fold_constants(&parsed.bdd).to_string())
+    };
+
+    assert_eq!(fold("x & true")?, "x");
+    assert_eq!(fold("x | false")?, "x");
+    assert_eq!(fold("!true | y")?, "y");
+    assert_eq!(fold("!!x")?, "x");
+    assert_eq!(fold("x & (y & false)")?, "false");
+    assert_eq!(fold("(a => false) & (true ^ b)")?, "!a & !b");
+    assert_eq!(fold("(a <= false) & (false <= b)")?, "!b");
+    assert_eq!(fold("(a nand true) | (a nor false)")?, "!a | !a");
+    assert_eq!(fold("if true then a else b")?, "a");
+    assert_eq!(fold("if c then a & true else b")?, "if c then a else b");
+    assert_eq!(fold("exists a # a | true")?, "true");
+    assert_eq!(fold("[a & true, b | false] >= 1")?, "[a, b] >= 1");
+    assert_eq!(fold("parity(a ^ false, b)")?, "parity(a, b)");
+
+    Ok(())
+}
+
+#[test]
+fn test_weighted_countable() -> io::Result<()> {
+    let tautology = |formula: &str| -> io::Result<()> {
+        assert_eq!(
+            parse_and_evaluate(formula)?,
+            env().mk_const(true),
+            "{formula}"
+        );
+        Ok(())
+    };
+
+    tautology("[2 * a, 3 * b] <= 4 <=> !(a & b)")?;
+    tautology("[2*a, 3*b, c] >= 4 <=> (b & (a | c))")?;
+    tautology("[2 * a, 3 * b, c] = 3 <=> (b & !a & !c) | (a & c & !b)")?;
+    tautology("[2 * a, 3 * b, c] != 3 <=> !((b & !a & !c) | (a & c & !b))")?;
+    tautology("[2 * a, b] < 2 <=> !a")?;
+    tautology("[2 * a, b] > 2 <=> (a & b)")?;
+
+    // unweighted elements count once, and weights apply to arbitrary formulas
+    tautology("[1 * a, b, c] >= 2 <=> [a, b, c] >= 2")?;
+    tautology("[4 * (a | b), c] >= 5 <=> ((a | b) & c)")?;
+
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"[2*a, b | c] <= 2"[..]), None)?;
+    assert_eq!(parsed.bdd.to_string(), "[2 * a, 1 * b | c] <= 2");
+
+    // weighted lists cannot be compared with other lists
+    assert!(parse_and_evaluate("[2 * a] = [b]").is_err());
+
+    // only '*' separates a weight from its formula, not the other spellings of conjunction
+    for src in ["[2 & a, b] <= 2", "[2 and a, b] <= 2", "[2 ∧ a, b] <= 2"] {
+        assert!(parse_and_evaluate(src).is_err(), "{src} is accepted");
+    }
+    tautology("(a * b) <=> (a & b)")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_validate() -> io::Result<()> {
+    let validate = |src: &str| -> io::Result<Vec<String>> {
+        let parsed = ParsedFormula::new(&mut BufReader::new(src.as_bytes()), None)?;
+        Ok(parsed.validate().iter().map(ToString::to_string).collect())
+    };
+
+    assert!(validate("[a, b] = 1 & [a, b, c] >= 3 & [a] <= 1")?.is_empty());
+
+    assert_eq!(
+        validate("[a, b] = 3")?,
+        vec!["countable constraint = 3 exceeds the list length 2, the constraint is always false"]
+    );
+    assert_eq!(
+        validate("a | !([a, b] >= 3 & [c] < 2)")?,
+        vec![
+            "countable constraint >= 3 exceeds the list length 2, the constraint is always false",
+            "countable constraint < 2 exceeds the list length 1, the constraint is always true",
+        ]
+    );
+    // more than all formulas cannot be true
+    assert_eq!(
+        validate("[a, b] > 2 | [a, b] > 1")?,
+        vec!["countable constraint > 2 exceeds the list length 2, the constraint is always false"]
+    );
+    assert_eq!(
+        validate("exists a # [] = 1")?,
+        vec!["countable constraint [] = 1 has an empty list"]
+    );
+
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"[[a] > 2, b] = 1"[..]), None)?;
+    assert_eq!(
+        parsed.validate(),
+        vec![Warning::ThresholdExceedsLength {
+            operator: CountableOperator::MoreThan,
+            threshold: 2,
+            length: 1,
+        }]
+    );
+
+    Ok(())
+}
+
+// parse the cover of a single-output BLIF model back into a bdd over the given inputs
+fn parse_blif_cover(
+    blif: &str,
+    env: &BDDEnv<NamedSymbol>,
+    inputs: &[NamedSymbol],
+) -> Rc<BDD<NamedSymbol>> {
+    let rows = blif
+        .lines()
+        .skip_while(|l| !l.starts_with(".names"))
+        .skip(1)
+        .take_while(|l| !l.starts_with('.'));
+
+    rows.fold(env.mk_const(false), |acc, row| {
+        let cube = row
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .zip(inputs)
+            .fold(env.mk_const(true), |cube, (c, v)| match c {
+                '1' => env.and(cube, env.var(v.clone())),
+                '0' => env.and(cube, env.not(env.var(v.clone()))),
+                _ => cube,
+            });
+
+        env.or(acc, cube)
+    })
+}
+
+#[test]
+fn test_blif() -> io::Result<()> {
+    for formula in [
+        "(a & b) | !c",
+        "a ^ b ^ c",
+        "[a, b, c, d] = 2",
+        "a & !a",
+        "a | !a",
+    ] {
+        let parsed = ParsedFormula::new(&mut BufReader::new(formula.as_bytes()), None)?;
+        let result = parsed.eval();
+
+        let mut output = Vec::new();
+        write_blif(&parsed, &result, &mut output)?;
+        let output = String::from_utf8(output).expect("blif output is not valid utf-8");
+
+        assert!(output.starts_with(".model formula\n"));
+        assert!(output.contains("\n.outputs out\n"));
+        assert!(output.ends_with(".end\n"));
+        assert_eq!(
+            parse_blif_cover(&output, &parsed.env, &parsed.free_vars),
+            result
+        );
+    }
+
+    // names are sanitized and kept unique
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"`x y` & x_y & out & 'a-b'"[..]), None)?;
+    let mut output = Vec::new();
+    write_blif(&parsed, &parsed.eval(), &mut output)?;
+    let output = String::from_utf8(output).expect("blif output is not valid utf-8");
+
+    assert!(output.contains("\n.inputs x_y x_y_1 out_1 a_b\n"));
+    assert!(output.contains("\n1111 1\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_statements() -> io::Result<()> {
+    let statements = ParsedFormula::new_statements(
+        Rc::new(BDDEnv::new()),
+        &mut BufReader::new(&b"a & b; b | c; !a;"[..]),
+        None,
+        ParseOptions::default(),
+    )?;
+
+    assert_eq!(statements.len(), 3);
+
+    // the statements share the environment and the variable ids
+    let env = &statements[0].env;
+    let a = statements[0].name2var("a").expect("a is a variable");
+    assert_eq!(statements[2].name2var("a"), Some(a.clone()));
+    assert!(statements.iter().all(|s| Rc::ptr_eq(&s.env, env)));
+
+    assert_eq!(statements[2].eval(), env.not(env.var(a)));
+    assert_eq!(statements[1].free_vars.len(), 2);
+
+    // the statements are independent, rather than a conjunction
+    assert!(statements.iter().all(|s| !s.eval().is_false()));
+
+    assert!(ParsedFormula::new_statements(
+        Rc::new(BDDEnv::new()),
+        &mut BufReader::new(&b"a;; b"[..]),
+        None,
+        ParseOptions::default(),
+    )
+    .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_display_round_trip() -> io::Result<()> {
+    let formulas = [
+        "a & b | !c",
+        "(a & b) | !(c ^ d)",
+        "(a => b) <= (c <=> d) nand e nor f",
+        "!!a & -(b | c)",
+        "exists a, b # a & (forall c # c | b)",
+        "(exists a # a & b) & c",
+        "[a, b, c & d] >= 2 & [a, !b] != 1",
+        "[a, b] = [c, d, e] - 1 | [a] < [b] + 2",
+        "[] = 0",
+        "if a then b else c & d",
+        "(if a then b) | (if c then if d then e else f)",
+        "gfp x # x & a",
+        "!lfp x # (x | a) & b",
+        "'first name' & `last-name` & `true` | 'with `tick`'",
+        "true & (false | a)",
+        "parity(a, b & c, !d) | majority(a, b, c)",
+    ];
+
+    for formula in formulas {
+        let parsed = ParsedFormula::new(&mut formula.as_bytes(), None)?;
+        let rendered = parsed.bdd.to_string();
+
+        let reparsed = ParsedFormula::new(&mut rendered.as_bytes(), Some(parsed.vars.clone()))?;
+
+        assert_eq!(reparsed.bdd.to_string(), rendered, "{formula}");
+        assert_eq!(
+            reparsed.eval(),
+            parsed.eval(),
+            "{formula} rendered as {rendered}"
+        );
+    }
+
+    let render = |formula: &str| -> io::Result<String> {
+        Ok(ParsedFormula::new(&mut formula.as_bytes(), None)?
+            .bdd
+            .to_string())
+    };
+
+    assert_eq!(render("a and (b or not c)")?, "a & b | !c");
+    assert_eq!(render("((a & b)) | c")?, "(a & b) | c");
+    assert_eq!(render("∀ x, y # x ⊕ y")?, "forall x, y # x ^ y");
+    assert_eq!(render("[a, b] <= [c] + 1")?, "[a, b] <= [c] + 1");
+    assert_eq!(render("if a then b")?, "if a then b else true");
+    assert_eq!(render("`a b` & 'c'")?, "`a b` & c");
+
+    let e = BDDEnv::new();
+    let a = NamedSymbol {
+        name: Rc::new("a".to_string()),
+        id: 0,
+    };
+    let subtree = SymbolicBDD::Subtree(e.not(e.var(a)));
+    assert_eq!(subtree.to_string(), "(if a then false else true)");
+
+    Ok(())
+}
+
+#[test]
+fn test_unsat_core() -> io::Result<()> {
+    let env = Rc::new(BDDEnv::new());
+
+    // the second and third line conflict, the first line is unrelated
+    let parsed = ParsedFormula::new_conjunction(
+        Rc::clone(&env),
+        &mut BufReader::new(&b"a | b\nc => d\nc & !d\n"[..]),
+        None,
+        DomainEncoding::default(),
+    )?;
+
+    let conjuncts: Vec<String> = parsed.conjuncts().iter().map(|f| f.to_string()).collect();
+    assert_eq!(conjuncts, vec!["a | b", "c => d", "c & !d"]);
+    assert_eq!(parsed.unsat_core(), Some(vec![1, 2]));
+
+    // the operands of a top-level conjunction are conjuncts as well
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"x & (y | z) & !x & !y"[..]), None)?;
+    assert_eq!(parsed.conjuncts().len(), 4);
+    assert_eq!(parsed.unsat_core(), Some(vec![0, 2]));
+
+    // satisfiable formulas have no core
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"a & (a => b)"[..]), None)?;
+    assert_eq!(parsed.unsat_core(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_streaming_tokenizer() -> io::Result<()> {
+    // a large formula, with variable names in sorted order of first occurrence and comments spanning multiple lines
+    let mut src = String::from("\"a generated\nchain of implications\"\n");
+    for i in 0..500 {
+        if i % 50 == 0 {
+            src.push_str(&format!("[v{i:04}, !'v{i:04} quoted'] >= 1 &\n"));
+        }
+        src.push_str(&format!("(v{:04} => v{:04}) & \"step {i}\"\n", i, i + 1));
+    }
+    src.push_str("\"multi\nline\" v0000");
+
+    let collected = SymbolicBDD::tokenize(&mut BufReader::new(src.as_bytes()), None)?;
+    let streamed = TokenStream::new(&mut BufReader::new(src.as_bytes()), None)
+        .collect::<io::Result<Vec<SymbolicBDDToken>>>()?;
+    assert_eq!(streamed, collected);
+
+    // sorting the variables uses the non-streaming path, which assigns the same ids for this input
+    let env = Rc::new(BDDEnv::new());
+    let streaming =
+        ParsedFormula::new_with_env(Rc::clone(&env), &mut BufReader::new(src.as_bytes()), None)?;
+    let collecting = ParsedFormula::new_with_options(
+        Rc::clone(&env),
+        &mut BufReader::new(src.as_bytes()),
+        None,
+        ParseOptions {
+            sort_vars: true,
+            ..Default::default()
+        },
+    )?;
+
+    assert_eq!(streaming.vars, collecting.vars);
+    assert_eq!(streaming.bdd, collecting.bdd);
+    assert_eq!(streaming.eval(), collecting.eval());
+
+    // errors in later lines are still reported
+    let error = ParsedFormula::new(&mut BufReader::new(&b"a &\nb &\n(c"[..]), None);
+    assert!(error.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_truth_table_rows() -> io::Result<()> {
+    use TruthTableEntry::*;
+
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"a ^ b"[..]), None)?;
+    let rows: Vec<_> = parsed
+        .truth_table_rows(&parsed.eval(), &parsed.free_vars)
+        .collect();
+
+    assert_eq!(
+        rows,
+        vec![
+            (vec![False, False], False),
+            (vec![False, True], True),
+            (vec![True, False], True),
+            (vec![True, True], False),
+        ]
+    );
+
+    // skipped variables can take any value
+    let parsed = ParsedFormula::new(&mut BufReader::new(&b"a | b"[..]), None)?;
+    let rows: Vec<_> = parsed
+        .truth_table_rows(&parsed.eval(), &parsed.free_vars)
+        .collect();
+
+    assert_eq!(
+        rows,
+        vec![
+            (vec![False, False], False),
+            (vec![False, True], True),
+            (vec![True, Any], True),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_adversarial_input() {
+    let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+
+    let rejected = [
+        "[a, b] >= 99999999999999999999999999".to_string(),
+        "[a, b] >= 4294967296".to_string(),
+        "[3 * a, b] <= 99999999999999999999".to_string(),
+        "var x in 0..99999999999999999999; x = 1".to_string(),
+        "((a & b)".to_string(),
+        "(a & b))".to_string(),
+        "[a, b".to_string(),
+        "a & ".to_string(),
+        "!".repeat(100_000) + "a",
+        nested(100_000),
+    ];
+
+    for src in &rejected {
+        assert!(
+            ParsedFormula::try_parse(src).is_err(),
+            "{src:.40} is accepted"
+        );
+    }
+
+    // the nesting depth is limited, while chains of associative operators are not
+    assert!(ParsedFormula::try_parse(&nested(MAX_NESTING_DEPTH - 1)).is_ok());
+    assert!(ParsedFormula::try_parse(&nested(MAX_NESTING_DEPTH)).is_err());
+    assert!(ParsedFormula::try_parse(&vec!["a"; 2000].join(" & ")).is_ok());
+    assert!(ParsedFormula::try_parse(&vec!["a"; 1_000_000].join(" & ")).is_ok());
+    assert!(ParsedFormula::try_parse(&vec!["a"; 100_000].join(" => ")).is_err());
+    assert!(ParsedFormula::try_parse(&vec!["a & a"; 100_000].join(" | ")).is_err());
+    assert!(ParsedFormula::try_parse("[a, b] >= 4294967295").is_ok());
+}
+
+#[test]
+fn test_empty_input() {
+    for src in ["", "  \n\t\n", "\"only a comment\"\n", "var x in 0..3;"] {
+        let error = ParsedFormula::try_parse(src).expect_err("empty input is accepted");
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<ParseError>()),
+            Some(&ParseError::EmptyInput),
+            "{src:?}"
+        );
+    }
+
+    // a syntax error is not reported as empty input
+    let error = ParsedFormula::try_parse("a &").expect_err("incomplete input is accepted");
+    assert!(error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<ParseError>())
+        .is_none());
+}
@@ -0,0 +1,48 @@
+use pretty_assertions::assert_eq;
+
+use rsbdd::plot::*;
+
+fn histogram(samples: &[f64], bins: usize) -> Vec<String> {
+    let min = samples.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let mut output = Vec::new();
+    write_ascii_histogram(&mut output, samples, min, max, bins).expect("failed to write histogram");
+
+    String::from_utf8(output)
+        .expect("histogram is not valid utf-8")
+        .lines()
+        .map(|l| l.trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn test_ascii_histogram() {
+    let lines = histogram(&[0.0, 0.1, 0.1, 0.2, 1.0], 10);
+
+    assert_eq!(lines.len(), 10);
+
+    let counts: Vec<&str> = lines
+        .iter()
+        .map(|l| l.rsplit(' ').next().unwrap_or_default())
+        .collect();
+    assert_eq!(
+        counts,
+        vec!["1", "2", "1", "0", "0", "0", "0", "0", "0", "1"]
+    );
+
+    // the largest bin has the full width
+    assert_eq!(lines[1].matches('#').count(), 50);
+    assert_eq!(lines[0].matches('#').count(), 25);
+    assert_eq!(lines[3].matches('#').count(), 0);
+    assert!(lines[0].starts_with("0.0000s - 0.1000s |"));
+}
+
+#[test]
+fn test_ascii_histogram_single_sample() {
+    assert_eq!(
+        histogram(&[0.5], 10),
+        vec![format!("0.5000s - 0.5000s | {} 1", "#".repeat(50))]
+    );
+    assert_eq!(histogram(&[0.5, 0.5, 0.5], 10).len(), 1);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "serde")]
+
+use std::rc::Rc;
+
+use pretty_assertions::assert_eq;
+
+use rsbdd::bdd::{BDDEnv, BDD};
+use rsbdd::parser::{ParsedFormula, SymbolicBDD};
+use rsbdd::TruthTableEntry;
+
+#[test]
+fn test_serde_truth_table_entry() -> serde_json::Result<()> {
+    for entry in [
+        TruthTableEntry::True,
+        TruthTableEntry::False,
+        TruthTableEntry::Any,
+    ] {
+        let json = serde_json::to_string(&entry)?;
+        assert_eq!(serde_json::from_str::<TruthTableEntry>(&json)?, entry);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_serde_bdd() -> serde_json::Result<()> {
+    let e = BDDEnv::new();
+
+    // the parity function shares the subtrees of every level
+    let root = (0..8).fold(e.mk_const(false), |acc, i| e.xor(acc, e.var(i)));
+
+    let json = serde_json::to_value(root.as_ref())?;
+    let nodes = json["nodes"].as_array().expect("expected a node table");
+    assert_eq!(nodes.len(), e.size_reachable(&root) - 2);
+
+    let restored: Rc<BDD<usize>> = Rc::new(serde_json::from_value(json)?);
+    assert_eq!(restored, root);
+
+    // the restored bdd shares its nodes in the same way
+    let mut unique = restored.node_list();
+    unique.sort_by_key(Rc::as_ptr);
+    unique.dedup_by_key(|n| Rc::as_ptr(n));
+    assert_eq!(unique.len(), e.size_reachable(&root));
+
+    for constant in [e.mk_const(false), e.mk_const(true)] {
+        let json = serde_json::to_string(constant.as_ref())?;
+        assert_eq!(serde_json::from_str::<BDD<usize>>(&json)?, *constant);
+    }
+
+    // structurally identical nodes which are not shared are written once
+    let leaf = || Rc::new(BDD::choice(e.mk_const(true), 2, e.mk_const(false)));
+    let unshared = BDD::choice(
+        Rc::new(BDD::choice(leaf(), 1, e.mk_const(false))),
+        0,
+        Rc::new(BDD::choice(leaf(), 1, e.mk_const(true))),
+    );
+    let json = serde_json::to_value(&unshared)?;
+    assert_eq!(json["nodes"].as_array().map(Vec::len), Some(4));
+    assert_eq!(serde_json::from_value::<BDD<usize>>(json)?, unshared);
+
+    // nodes can only refer to preceding nodes
+    assert!(serde_json::from_str::<BDD<usize>>(r#"{"nodes": [[3, 0, 0]], "root": 2}"#).is_err());
+    assert!(serde_json::from_str::<BDD<usize>>(r#"{"nodes": [], "root": 2}"#).is_err());
+
+    // the table must describe a reduced bdd
+    assert!(serde_json::from_str::<BDD<usize>>(r#"{"nodes": [[1, 0, 1]], "root": 2}"#).is_err());
+    assert!(serde_json::from_str::<BDD<usize>>(
+        r#"{"nodes": [[1, 1, 0], [1, 1, 0], [2, 0, 3]], "root": 4}"#
+    )
+    .is_err());
+
+    // and an ordered bdd, in which every symbol precedes the symbols of its subtrees
+    assert!(
+        serde_json::from_str::<BDD<usize>>(r#"{"nodes": [[1, 1, 0], [2, 0, 0]], "root": 3}"#)
+            .is_ok()
+    );
+    assert!(
+        serde_json::from_str::<BDD<usize>>(r#"{"nodes": [[1, 1, 0], [2, 1, 0]], "root": 3}"#)
+            .is_err()
+    );
+    assert!(
+        serde_json::from_str::<BDD<usize>>(r#"{"nodes": [[1, 0, 0], [1, 1, 2]], "root": 3}"#)
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_serde_parse_tree() -> serde_json::Result<()> {
+    let parsed = ParsedFormula::try_parse("exists a # (a & b | [a, c] = 1) => !c")
+        .expect("failed to parse the formula");
+
+    let json = serde_json::to_string(&parsed.bdd)?;
+    assert_eq!(serde_json::from_str::<SymbolicBDD>(&json)?, parsed.bdd);
+
+    Ok(())
+}
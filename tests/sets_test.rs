@@ -87,3 +87,63 @@ fn test_set_ops() {
         set_template.empty()
     );
 }
+
+#[test]
+fn test_set_cardinality() {
+    let bits = 4;
+
+    let env = Rc::new(BDDEnv::new());
+
+    let set = BDDSet::with_env(bits, &env);
+    assert_eq!(set.cardinality(), 0);
+
+    set.insert(0x2).insert(0b101).insert(5);
+    assert_eq!(set.cardinality(), 2);
+
+    assert!(set.contains(2));
+    assert!(set.contains(5));
+    assert!(!set.contains(3));
+
+    // membership tests do not modify the set
+    assert_eq!(set.cardinality(), 2);
+
+    set.universe();
+    assert_eq!(set.cardinality(), 16);
+}
+
+#[test]
+fn test_set_filter_map() {
+    let bits = 4;
+
+    let env = Rc::new(BDDEnv::new());
+
+    let set = BDDSet::with_env(bits, &env);
+    set.insert(2).insert(3).insert(4).insert(5);
+
+    assert_eq!(set.iter_members().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+
+    let evens = set.filter(|x| x % 2 == 0);
+    assert_eq!(evens.iter_members().collect::<Vec<_>>(), vec![2, 4]);
+    assert_eq!(evens.cardinality(), 2);
+
+    let successors = set.map(|x| x + 1);
+    assert_eq!(
+        successors.iter_members().collect::<Vec<_>>(),
+        vec![3, 4, 5, 6]
+    );
+
+    // the original set is unchanged
+    assert_eq!(set.cardinality(), 4);
+
+    // elements are enumerated for bits which do not occur in the bdd
+    assert_eq!(set.universe().iter_members().count(), 16);
+    assert_eq!(set.empty().map(|x| x + 1).cardinality(), 0);
+
+    // variables outside of the bits of the set are existentially quantified
+    let outside = BDDSet::from_bdd(&env.and(env.var(0), env.var(bits + 2)), bits, &env);
+    assert_eq!(
+        outside.iter_members().collect::<Vec<_>>(),
+        vec![0, 2, 4, 6, 8, 10, 12, 14]
+    );
+    assert_eq!(outside.filter(|x| x < 4).iter_members().count(), 2);
+}
@@ -0,0 +1,55 @@
+use std::rc::Rc;
+
+use pretty_assertions::assert_eq;
+
+use rsbdd::bdd::*;
+use rsbdd::solver::*;
+
+#[test]
+fn test_solver_scopes() {
+    let e = Rc::new(BDDEnv::new());
+    let mut solver = Solver::new(Rc::clone(&e));
+
+    assert!(solver.is_sat());
+    assert_eq!(solver.model(), e.mk_const(true));
+
+    solver.assert(e.or(e.var(0), e.var(1)));
+    solver.assert(e.not(e.var(0)));
+    assert!(solver.is_sat());
+    assert_eq!(solver.model(), e.and(e.not(e.var(0)), e.var(1)));
+
+    solver.push();
+    solver.assert(e.not(e.var(1)));
+    assert!(!solver.is_sat());
+    assert_eq!(solver.depth(), 1);
+
+    // popping restores the conjunction before the push
+    assert!(solver.pop());
+    assert!(solver.is_sat());
+    assert_eq!(solver.root(), &e.and(e.not(e.var(0)), e.var(1)));
+
+    assert!(!solver.pop());
+    assert!(solver.is_sat());
+    assert_eq!(solver.depth(), 0);
+}
+
+#[test]
+fn test_solver_nested_scopes() {
+    let e = Rc::new(BDDEnv::new());
+    let mut solver = Solver::new(Rc::clone(&e));
+
+    solver.push();
+    solver.assert(e.var(0));
+    solver.push();
+    solver.assert(e.var(1));
+    solver.push();
+    solver.assert(e.not(e.and(e.var(0), e.var(1))));
+    assert!(!solver.is_sat());
+
+    assert!(solver.pop());
+    assert_eq!(solver.root(), &e.and(e.var(0), e.var(1)));
+    assert!(solver.pop());
+    assert_eq!(solver.root(), &e.var(0));
+    assert!(solver.pop());
+    assert_eq!(solver.root(), &e.mk_const(true));
+}
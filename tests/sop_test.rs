@@ -0,0 +1,55 @@
+use pretty_assertions::assert_eq;
+
+use rsbdd::bdd::*;
+use rsbdd::sop::*;
+
+#[test]
+fn test_sop_constants() {
+    let e: BDDEnv<usize> = BDDEnv::new();
+
+    assert_eq!(e.to_sop(&e.mk_const(false)).to_string(), "false");
+    assert_eq!(e.to_sop(&e.mk_const(true)).to_string(), "true");
+    assert_eq!(e.to_sop(&e.var(0)).to_string(), "0");
+    assert_eq!(e.to_sop(&e.not(e.var(0))).to_string(), "!0");
+}
+
+#[test]
+fn test_sop_xor() {
+    let e = BDDEnv::new();
+    let xor = e.xor(e.var(0), e.var(1));
+
+    let sop: SumOfProducts<usize> = e.to_sop(&xor);
+    assert_eq!(sop.cubes.len(), 2);
+    assert_eq!(sop.to_string(), "!0&1 | 0&!1");
+    assert_eq!(sop.to_bdd(&e), xor);
+}
+
+#[test]
+fn test_sop_absorption() {
+    let e = BDDEnv::new();
+
+    // a | (a & b) | (!a & b) reduces to a | b
+    let f = e.or(
+        e.var(0),
+        e.or(e.and(e.var(0), e.var(1)), e.and(e.not(e.var(0)), e.var(1))),
+    );
+
+    let sop = e.to_sop(&f);
+    assert_eq!(sop.to_string(), "0 | 1");
+    assert_eq!(sop.to_bdd(&e), f);
+
+    // the consensus term b & c is redundant in (a & b) | (!a & c)
+    let g = e.or(e.and(e.var(0), e.var(1)), e.and(e.not(e.var(0)), e.var(2)));
+
+    let sop = e.to_sop(&g);
+    assert_eq!(sop.to_string(), "!0&2 | 0&1");
+    assert_eq!(sop.to_bdd(&e), g);
+
+    // the majority function has three prime implicants of two literals
+    let majority = e.aln(&[e.var(0), e.var(1), e.var(2)], 2);
+
+    let sop = e.to_sop(&majority);
+    assert_eq!(sop.cubes.len(), 3);
+    assert!(sop.cubes.iter().all(|c| c.len() == 2));
+    assert_eq!(sop.to_bdd(&e), majority);
+}